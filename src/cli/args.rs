@@ -1,4 +1,6 @@
+use crate::formats::{csv::Column, Format};
 use crate::provider::ProviderKind;
+use crate::state::{config::ConfigScope, Operation};
 use clap::{Parser, Subcommand};
 
 /// grit - Git-like version control for playlists
@@ -25,7 +27,7 @@ pub struct Cli {
         short,
         long,
         global = true,
-        help = "Override provider (spotify/youtube)"
+        help = "Override provider (spotify, youtube, local, subsonic or a plugin name)"
     )]
     pub provider: Option<ProviderKind>,
 
@@ -46,6 +48,14 @@ pub struct Cli {
     )]
     pub verbose: bool,
 
+    #[arg(
+        long,
+        global = true,
+        default_value_t = false,
+        help = "Print machine-readable JSON (status, log, diff, list, find, playlists, curr, search, whoami)"
+    )]
+    pub json: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -67,8 +77,29 @@ pub enum Commands {
         provider: Option<ProviderKind>,
     },
 
+    /// Start a new playlist locally; the first push creates it on the provider
+    New {
+        #[arg(help = "Name of the playlist")]
+        name: String,
+        #[arg(
+            short,
+            long,
+            help = "Provider to create it on (defaults to core.provider, then Spotify)"
+        )]
+        provider: Option<ProviderKind>,
+        #[arg(
+            long,
+            value_name = "PLAYLIST|FILE",
+            help = "Start with the tracks of a tracked playlist or a playlist file"
+        )]
+        from: Option<String>,
+    },
+
     /// Pull latest changes from remote (like 'git pull')
-    Pull,
+    Pull {
+        #[arg(long, help = "Show the changes and files a pull would write")]
+        dry_run: bool,
+    },
 
     /// Show sync status (like 'git status')
     #[command(visible_alias = "st")]
@@ -78,12 +109,86 @@ pub enum Commands {
     },
 
     /// Show commit history (like 'git log')
-    Log,
+    Log {
+        #[arg(long, help = "Show each entry on a single line")]
+        oneline: bool,
+        #[arg(long, help = "Show the tracks changed by each entry")]
+        stat: bool,
+        #[arg(long, help = "Show entries after a date (YYYY-MM-DD or '2 weeks ago')")]
+        since: Option<String>,
+        #[arg(
+            long,
+            help = "Show entries up to a date, including that day (YYYY-MM-DD or '2 weeks ago')"
+        )]
+        until: Option<String>,
+        #[arg(long, help = "Show entries whose author contains this text")]
+        author: Option<String>,
+        #[arg(long, help = "Show entries whose message contains this text")]
+        grep: Option<String>,
+        #[arg(
+            long = "operation",
+            value_name = "OP",
+            help = "Show only these operations"
+        )]
+        operations: Vec<Operation>,
+        #[arg(
+            short = 'n',
+            long = "max-count",
+            help = "Limit the number of entries shown"
+        )]
+        max_count: Option<usize>,
+        #[arg(
+            long,
+            help = "Custom format: %h %H hash, %d date, %o operation, %s message,\n                       %an author, %a %r %m added/removed/moved, %n newline"
+        )]
+        format: Option<String>,
+    },
 
     /// Apply a playlist state from file
     Apply {
         #[arg(help = "Path to the YAML file")]
         file: String,
+        #[arg(long, help = "Show the changes and files apply would write")]
+        dry_run: bool,
+    },
+
+    /// Write a playlist as M3U8, XSPF, CSV or JSPF for other players
+    Export {
+        #[arg(help = "Playlist ID to export")]
+        playlist: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            help = "Output format (default: from --output, else m3u8)"
+        )]
+        format: Option<Format>,
+        #[arg(
+            long,
+            value_name = "HASH",
+            help = "Export a past revision instead of the current state"
+        )]
+        rev: Option<String>,
+        #[arg(short = 'o', long, help = "Write to a file instead of stdout")]
+        output: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            help = "CSV columns, comma separated (default: all but location)"
+        )]
+        columns: Vec<Column>,
+    },
+
+    /// Stage the changes needed to match an M3U8, XSPF, CSV or JSPF playlist file
+    Import {
+        #[arg(help = "Playlist file to import")]
+        file: String,
+        #[arg(
+            long,
+            value_enum,
+            help = "Input format (default: from the file extension)"
+        )]
+        format: Option<Format>,
     },
 
     /// Start playback with TUI player
@@ -142,10 +247,107 @@ pub enum Commands {
 
     /// Push local changes to remote (like 'git push')
     Push {
+        #[arg(help = "Remote to push to (defaults to origin)")]
+        remote: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "remote",
+            help = "Push to origin and every mirror remote"
+        )]
+        all: bool,
+        #[arg(
+            long = "continue",
+            conflicts_with_all = ["remote", "all", "abort"],
+            help = "Finish an interrupted push"
+        )]
+        resume: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["remote", "all"],
+            help = "Roll the remote of an interrupted push back"
+        )]
+        abort: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["resume", "abort"],
+            help = "Show the push plan and the requests that would change the remote"
+        )]
+        dry_run: bool,
         #[arg(short = 'l', long, help = "Playlist ID to push")]
         playlist: Option<String>,
     },
 
+    /// Track all playlists from a Spotify account export or Google Takeout
+    ImportExport {
+        #[arg(help = "Playlist1.json, or the folder of a Spotify or Takeout export")]
+        path: String,
+    },
+
+    /// Fetch full track details for playlists imported from an export
+    Enrich {
+        #[arg(help = "Playlist ID to enrich")]
+        playlist: Option<String>,
+    },
+
+    /// Copy a playlist to another provider and track the copy
+    Convert {
+        #[arg(help = "Tracked playlist ID, or URL of any playlist")]
+        playlist: Option<String>,
+        #[arg(long, help = "Provider to convert to")]
+        to: ProviderKind,
+        #[arg(
+            long,
+            value_name = "ID",
+            help = "Add to the end of an existing playlist instead of creating one"
+        )]
+        into: Option<String>,
+        #[arg(long, help = "Choose among candidates for low-confidence matches")]
+        review: bool,
+    },
+
+    /// Copy a read-only source such as an album into a new editable playlist
+    Fork {
+        #[arg(help = "Tracked album or playlist ID")]
+        playlist: Option<String>,
+        #[arg(long, help = "Name of the new playlist (default: the source's name)")]
+        name: Option<String>,
+    },
+
+    /// Manage mirror remotes of a playlist on other providers
+    Remote {
+        #[command(subcommand)]
+        action: RemoteAction,
+    },
+
+    /// Share a playlist with its history as a single file
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+
+    /// Track a playlist from a bundle file, or every playlist of an account
+    Clone {
+        #[arg(
+            required_unless_present = "all",
+            help = "Bundle file created with 'grit bundle create'"
+        )]
+        source: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "source",
+            help = "Track every playlist of the signed-in account (see 'grit remote ls')"
+        )]
+        all: bool,
+        #[arg(long, requires = "all", help = "Only playlists the account can change")]
+        owned: bool,
+        #[arg(
+            long,
+            requires = "all",
+            help = "Only playlists whose name contains this"
+        )]
+        filter: Option<String>,
+    },
+
     /// Show differences between versions (like 'git diff')
     #[command(visible_alias = "d")]
     Diff {
@@ -203,11 +405,95 @@ pub enum Commands {
     /// Show current working playlist info
     Curr,
 
+    /// Get and set options (global, repository or per-playlist)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
     /// Revert playlist to a previous commit
     Revert {
         #[arg(help = "Commit hash (defaults to previous commit)")]
         hash: Option<String>,
         #[arg(short = 'l', long, help = "Playlist ID")]
         playlist: Option<String>,
+        #[arg(long, help = "Show the changes and files a revert would write")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Print the effective value of a setting
+    Get {
+        #[arg(help = "Setting name, e.g. search.page_size")]
+        key: String,
+        #[arg(
+            long,
+            help = "Print client secrets and passwords instead of masking them"
+        )]
+        show_secret: bool,
+    },
+    /// Set a value
+    Set {
+        #[arg(help = "Setting name, e.g. search.page_size")]
+        key: String,
+        #[arg(help = "Value to store")]
+        value: String,
+        #[arg(long, value_enum, default_value_t = ConfigScope::Repo, help = "Where to store the value")]
+        scope: ConfigScope,
+    },
+    /// Remove a value
+    Unset {
+        #[arg(help = "Setting name")]
+        key: String,
+        #[arg(long, value_enum, default_value_t = ConfigScope::Repo, help = "Where to remove the value from")]
+        scope: ConfigScope,
+    },
+    /// List effective settings and where they come from
+    #[command(visible_alias = "ls")]
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RemoteAction {
+    /// Add a mirror remote
+    Add {
+        #[arg(help = "Remote name, e.g. yt")]
+        name: String,
+        #[arg(help = "Playlist URL or ID on the mirror provider")]
+        url: String,
+    },
+    /// Remove a mirror remote
+    #[command(visible_alias = "rm")]
+    Remove {
+        #[arg(help = "Remote name")]
+        name: String,
+    },
+    /// Show origin and mirror remotes
+    Show,
+    /// List the playlists of the signed-in account
+    #[command(visible_alias = "list")]
+    Ls {
+        #[arg(long, help = "Only playlists the account can change")]
+        owned: bool,
+        #[arg(long, help = "Only playlists whose name contains this")]
+        filter: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BundleAction {
+    /// Pack a playlist, its journal and snapshots into a file
+    Create {
+        #[arg(help = "Bundle file to write")]
+        file: String,
+        #[arg(help = "Playlist ID to bundle")]
+        playlist: Option<String>,
+    },
+    /// Recreate a playlist from a bundle file
+    Unbundle {
+        #[arg(help = "Bundle file to read")]
+        file: String,
     },
 }
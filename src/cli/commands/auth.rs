@@ -1,42 +1,87 @@
-use crate::provider::{Provider, ProviderKind, SpotifyProvider, YoutubeProvider};
-use crate::state::credentials;
+use crate::cli::commands::{
+    output::{print_json, QuotaOutput, WhoamiOutput},
+    utils::{client_credentials, endpoints, subsonic_account},
+};
+use crate::provider::{
+    OAuthToken, Provider, ProviderKind, SpotifyProvider, SubsonicProvider, YoutubeProvider,
+};
+use crate::state::{
+    config::Config,
+    credentials,
+    quota::{self, QuotaLedger},
+};
 use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use std::io::{BufRead, BufReader, Write};
 use std::net::TcpListener;
 use std::path::Path;
 
-const REDIRECT_URI: &str = "http://127.0.0.1:8888/callback";
+const DEFAULT_AUTH_PORT: u16 = 8888;
 
 /// Run the authentication flow for the given provider.
 pub async fn run(provider: ProviderKind, grit_dir: &Path) -> Result<()> {
-    match provider {
-        ProviderKind::Spotify => auth_spotify(grit_dir).await,
-        ProviderKind::Youtube => auth_youtube(grit_dir).await,
+    if provider == ProviderKind::Local {
+        anyhow::bail!(
+            "The local provider needs no authentication; set the music directory with 'grit config set local.root <dir>'"
+        );
+    }
+    if let ProviderKind::Plugin(name) = &provider {
+        anyhow::bail!("The {} plugin handles its own authentication", name);
+    }
+    if provider == ProviderKind::Subsonic {
+        return auth_subsonic(grit_dir).await;
     }
-}
 
-async fn auth_spotify(grit_dir: &Path) -> Result<()> {
-    let client_id =
-        std::env::var("SPOTIFY_CLIENT_ID").context("Set SPOTIFY_CLIENT_ID environment variable")?;
-    let client_secret = std::env::var("SPOTIFY_CLIENT_SECRET")
-        .context("Set SPOTIFY_CLIENT_SECRET environment variable")?;
+    let config = Config::load(grit_dir, None)?;
+    let port = match config.get_u64("auth.port")? {
+        Some(port) => u16::try_from(port)
+            .ok()
+            .filter(|&port| port > 0)
+            .context("Config key 'auth.port' must be between 1 and 65535")?,
+        None => DEFAULT_AUTH_PORT,
+    };
+    let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+    let (client_id, client_secret) = client_credentials(&provider, &config)?;
 
-    let provider = SpotifyProvider::new(client_id, client_secret);
+    match provider {
+        ProviderKind::Spotify => {
+            let provider = SpotifyProvider::new(client_id, client_secret).with_endpoints(
+                endpoints(&provider, &config, SpotifyProvider::default_endpoints()),
+            );
+            auth_spotify(grit_dir, provider, port, &redirect_uri).await
+        }
+        ProviderKind::Youtube => {
+            let provider = YoutubeProvider::new(client_id, client_secret).with_endpoints(
+                endpoints(&provider, &config, YoutubeProvider::default_endpoints()),
+            );
+            auth_youtube(grit_dir, provider, port, &redirect_uri).await
+        }
+        ProviderKind::Local | ProviderKind::Subsonic | ProviderKind::Plugin(_) => {
+            unreachable!("handled above")
+        }
+    }
+}
 
+async fn auth_spotify(
+    grit_dir: &Path,
+    provider: SpotifyProvider,
+    port: u16,
+    redirect_uri: &str,
+) -> Result<()> {
     let state = format!("{:016x}", rand::random::<u64>());
-    let auth_url = provider.oauth_url(REDIRECT_URI, &state);
+    let auth_url = provider.oauth_url(redirect_uri, &state);
 
     println!("Opening browser for Spotify authorization...\n");
     println!("If it doesn't open, visit:\n{}\n", auth_url);
 
     let _ = open::that(auth_url.clone());
 
-    let code = wait_for_callback(&state)?;
+    let code = wait_for_callback(port, &state)?;
 
     println!("Exchanging code for token...");
-    let token = provider.exchange_code(&code, REDIRECT_URI).await?;
+    let token = provider.exchange_code(&code, redirect_uri).await?;
 
-    credentials::save(grit_dir, ProviderKind::Spotify, &token)?;
+    credentials::save(grit_dir, &ProviderKind::Spotify, &token)?;
 
     println!("\nSuccessfully authenticated with Spotify!");
     println!(
@@ -47,28 +92,26 @@ async fn auth_spotify(grit_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-async fn auth_youtube(grit_dir: &Path) -> Result<()> {
-    let client_id =
-        std::env::var("YOUTUBE_CLIENT_ID").context("Set YOUTUBE_CLIENT_ID environment variable")?;
-    let client_secret = std::env::var("YOUTUBE_CLIENT_SECRET")
-        .context("Set YOUTUBE_CLIENT_SECRET environment variable")?;
-
-    let provider = YoutubeProvider::new(client_id, client_secret);
-
+async fn auth_youtube(
+    grit_dir: &Path,
+    provider: YoutubeProvider,
+    port: u16,
+    redirect_uri: &str,
+) -> Result<()> {
     let state = format!("{:016x}", rand::random::<u64>());
-    let auth_url = provider.oauth_url(REDIRECT_URI, &state);
+    let auth_url = provider.oauth_url(redirect_uri, &state);
 
     println!("Opening browser for YouTube authorization...\n");
     println!("If it doesn't open, visit:\n{}\n", auth_url);
 
     let _ = open::that(auth_url.clone());
 
-    let code = wait_for_callback(&state)?;
+    let code = wait_for_callback(port, &state)?;
 
     println!("Exchanging code for token...");
-    let token = provider.exchange_code(&code, REDIRECT_URI).await?;
+    let token = provider.exchange_code(&code, redirect_uri).await?;
 
-    credentials::save(grit_dir, ProviderKind::Youtube, &token)?;
+    credentials::save(grit_dir, &ProviderKind::Youtube, &token)?;
 
     println!("\nSuccessfully authenticated with YouTube!");
     println!(
@@ -79,9 +122,78 @@ async fn auth_youtube(grit_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-fn wait_for_callback(expected_state: &str) -> Result<String> {
-    let listener = TcpListener::bind("127.0.0.1:8888")
-        .context("Failed to bind to port 8888. Is another instance running?")?;
+/// Subsonic servers take a username and password instead of OAuth. The
+/// password is checked against the server, then stored encrypted like the
+/// OAuth tokens of other providers.
+async fn auth_subsonic(grit_dir: &Path) -> Result<()> {
+    let config = Config::load(grit_dir, None)?;
+    let (url, username) = subsonic_account(&config)?;
+
+    let password = match std::env::var("SUBSONIC_PASSWORD") {
+        Ok(password) => password,
+        Err(_) => read_password(&format!("Password for {} on {}: ", username, url))?,
+    };
+
+    println!("Checking credentials...");
+    SubsonicProvider::new(&url, &username, &password)
+        .ping()
+        .await?;
+
+    let token = OAuthToken {
+        access_token: password,
+        refresh_token: None,
+        expires_at: None,
+        token_type: "password".to_string(),
+        scope: None,
+    };
+    credentials::save(grit_dir, &ProviderKind::Subsonic, &token)?;
+
+    println!("\nSuccessfully authenticated with {} as {}!", url, username);
+    println!(
+        "  Password saved to {:?}",
+        grit_dir.join("credentials/subsonic.json")
+    );
+
+    Ok(())
+}
+
+/// Prompt for a password without echoing it.
+fn read_password(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    std::io::stdout().flush()?;
+
+    crossterm::terminal::enable_raw_mode().context("Failed to read password from terminal")?;
+    let mut password = String::new();
+    let result = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Enter => break Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break Err(anyhow::anyhow!("Cancelled"))
+                }
+                KeyCode::Char(c) => password.push(c),
+                KeyCode::Backspace => {
+                    password.pop();
+                }
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    crossterm::terminal::disable_raw_mode()?;
+    println!();
+
+    result.map(|_| password)
+}
+
+fn wait_for_callback(port: u16, expected_state: &str) -> Result<String> {
+    let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).with_context(|| {
+        format!(
+            "Failed to bind to port {}. Is another instance running? (change it with 'grit config set auth.port <port>')",
+            port
+        )
+    })?;
 
     println!("Waiting for callback...");
 
@@ -139,7 +251,7 @@ fn send_response(stream: &mut impl Write, status: &str, body: &str) -> Result<()
 
 pub async fn logout(provider: ProviderKind, grit_dir: &Path) -> Result<()> {
     // Check if credentials exist
-    let token = credentials::load(grit_dir, provider)?;
+    let token = credentials::load(grit_dir, &provider)?;
 
     if token.is_none() {
         println!("Not logged in to {:?}", provider);
@@ -147,7 +259,7 @@ pub async fn logout(provider: ProviderKind, grit_dir: &Path) -> Result<()> {
     }
 
     // Delete credentials
-    credentials::delete(grit_dir, provider)?;
+    credentials::delete(grit_dir, &provider)?;
 
     println!("Logged out from {:?}", provider);
     println!("Run 'grit auth {:?}' to login again", provider);
@@ -155,10 +267,41 @@ pub async fn logout(provider: ProviderKind, grit_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub async fn whoami(provider: ProviderKind, grit_dir: &Path) -> Result<()> {
-    let token = credentials::load(grit_dir, provider)?
+pub async fn whoami(provider: ProviderKind, grit_dir: &Path, json: bool) -> Result<()> {
+    if provider == ProviderKind::Local {
+        anyhow::bail!("The local provider has no account");
+    }
+    if let ProviderKind::Plugin(name) = &provider {
+        anyhow::bail!("The {} plugin handles its own authentication", name);
+    }
+
+    let token = credentials::load(grit_dir, &provider)?
         .context("Not authenticated. Run 'grit auth <provider>' first")?;
 
+    let quota = match provider {
+        ProviderKind::Youtube => Some(QuotaOutput {
+            day: quota::today(),
+            used: QuotaLedger::load(grit_dir)?.used_today(),
+            budget: quota::budget(&Config::load(grit_dir, None)?)?,
+        }),
+        _ => None,
+    };
+
+    if json {
+        return print_json(&WhoamiOutput {
+            provider,
+            token_type: token.token_type.clone(),
+            scopes: token
+                .scope
+                .as_deref()
+                .map(|s| s.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            expires_at: token.expires_at,
+            expired: credentials::is_expired(&token),
+            quota,
+        });
+    }
+
     match provider {
         ProviderKind::Spotify => {
             println!("Logged in to Spotify");
@@ -197,7 +340,26 @@ pub async fn whoami(provider: ProviderKind, grit_dir: &Path) -> Result<()> {
                     println!("Token expired (will auto-refresh on next use)");
                 }
             }
+            if let Some(quota) = quota {
+                if quota.budget > 0 {
+                    println!(
+                        "Quota used today: {} of {} units (resets at midnight Pacific time)",
+                        quota.used, quota.budget
+                    );
+                } else {
+                    println!(
+                        "Quota used today: {} units (resets at midnight Pacific time)",
+                        quota.used
+                    );
+                }
+            }
+        }
+        ProviderKind::Subsonic => {
+            let config = Config::load(grit_dir, None)?;
+            let (url, username) = subsonic_account(&config)?;
+            println!("Logged in to {} as {}", url, username);
         }
+        ProviderKind::Local | ProviderKind::Plugin(_) => unreachable!("handled above"),
     }
 
     Ok(())
@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::state::{bundle::Bundle, snapshot, working_playlist};
+
+/// Pack a tracked playlist and its history into `file`.
+pub async fn create(file: &str, playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+
+    if !snapshot::snapshot_path(grit_dir, playlist_id).exists() {
+        bail!("Playlist not initialized. Run 'grit init' first.");
+    }
+
+    let bundle = Bundle::create(grit_dir, playlist_id)?;
+    bundle.write(Path::new(file))?;
+
+    let snapshots = bundle
+        .files
+        .keys()
+        .filter(|name| name.starts_with("snapshots/"))
+        .count();
+    println!("Bundled {} into {}", playlist_id, file);
+    println!(
+        "  {} journal entries, {} snapshots",
+        bundle.journal()?.len(),
+        snapshots
+    );
+
+    Ok(())
+}
+
+/// Recreate the playlist in `file` with its history.
+pub async fn unbundle(file: &str, grit_dir: &Path) -> Result<String> {
+    let bundle = Bundle::read(Path::new(file))?;
+    let snap = bundle.snapshot()?;
+
+    bundle.unpack(grit_dir)?;
+
+    println!("Unbundled {} ({})", bundle.playlist_id, snap.name);
+    println!(
+        "  {} tracks, {} journal entries",
+        snap.tracks.len(),
+        bundle.journal()?.len()
+    );
+
+    Ok(bundle.playlist_id)
+}
+
+/// Unbundle and switch to the playlist.
+pub async fn clone(source: &str, grit_dir: &Path) -> Result<()> {
+    let playlist_id = unbundle(source, grit_dir).await?;
+    working_playlist::save(grit_dir, &playlist_id)?;
+    println!("\nSwitched to {}", playlist_id);
+    Ok(())
+}
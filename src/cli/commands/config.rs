@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::state::config::{self, Config, ConfigScope, KNOWN_KEYS};
+
+fn scope_path(
+    scope: ConfigScope,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+) -> Result<std::path::PathBuf> {
+    match scope {
+        ConfigScope::Global => config::global_path()
+            .context("Could not determine the global config location (set HOME or GRIT_CONFIG)"),
+        ConfigScope::Repo => Ok(config::repo_path(grit_dir)),
+        ConfigScope::Playlist => {
+            let playlist_id = playlist.context(
+                "Playlist required for --scope playlist (use --playlist or 'grit switch <id>')",
+            )?;
+            Ok(config::playlist_path(grit_dir, playlist_id))
+        }
+    }
+}
+
+fn is_secret(key: &str) -> bool {
+    key.ends_with("secret") || key.ends_with("password")
+}
+
+/// Secrets are masked in listings and by `grit config get` unless asked for.
+fn display_value(key: &str, value: &toml::Value) -> String {
+    if is_secret(key) {
+        return "********".to_string();
+    }
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub async fn get(
+    key: &str,
+    show_secret: bool,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    let config = Config::load(grit_dir, playlist)?;
+    let value = config
+        .get(key)
+        .with_context(|| format!("Config key '{}' is not set", key))?;
+    match value {
+        toml::Value::String(s) if show_secret => println!("{}", s),
+        other if show_secret => println!("{}", other),
+        other => println!("{}", display_value(key, other)),
+    }
+    Ok(())
+}
+
+pub async fn set(
+    key: &str,
+    value: &str,
+    scope: ConfigScope,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    // The repository and playlist files live in .grit, which may be shared
+    // in bundles or checked in, so secrets only go in the global file
+    if is_secret(key) && scope != ConfigScope::Global {
+        bail!(
+            "Refusing to store {} in the {} config; use --scope global or set {} in the environment",
+            key,
+            scope,
+            key.replace('.', "_").to_uppercase()
+        );
+    }
+
+    if scope == ConfigScope::Playlist && !config::PLAYLIST_KEYS.contains(&key) {
+        bail!(
+            "{} can't be set per playlist (only {} can); use --scope repo or global",
+            key,
+            config::PLAYLIST_KEYS.join(", ")
+        );
+    }
+
+    if config::NUMERIC_KEYS.contains(&key) && value.parse::<u64>().is_err() {
+        bail!("Config key '{}' must be a number, not '{}'", key, value);
+    }
+
+    let path = scope_path(scope, playlist, grit_dir)?;
+    config::set(&path, key, value)?;
+
+    let known = KNOWN_KEYS
+        .iter()
+        .any(|(k, _)| *k == key || (k.starts_with("alias.") && key.starts_with("alias.")));
+    if !known {
+        eprintln!("Warning: '{}' is not a setting grit uses", key);
+    }
+
+    println!("Set {} ({} config)", key, scope);
+    Ok(())
+}
+
+pub async fn unset(
+    key: &str,
+    scope: ConfigScope,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    let path = scope_path(scope, playlist, grit_dir)?;
+    if config::unset(&path, key)? {
+        println!("Unset {} ({} config)", key, scope);
+    } else {
+        println!("{} is not set in the {} config", key, scope);
+    }
+    Ok(())
+}
+
+pub async fn list(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let config = Config::load(grit_dir, playlist)?;
+    let entries = config.entries();
+
+    if entries.is_empty() {
+        println!("No configuration set.\n");
+        println!("Available settings:");
+        for (key, description) in KNOWN_KEYS {
+            println!("  {:<24} {}", key, description);
+        }
+        return Ok(());
+    }
+
+    for (key, value, scope) in entries {
+        println!("{}={}  ({})", key, display_value(&key, &value), scope);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_secrets_stay_out_of_the_repo_config() {
+        let dir = TempDir::new().unwrap();
+
+        let err = set(
+            "spotify.client_secret",
+            "hunter2",
+            ConfigScope::Repo,
+            None,
+            dir.path(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("SPOTIFY_CLIENT_SECRET"));
+        assert!(!config::repo_path(dir.path()).exists());
+
+        set(
+            "spotify.client_id",
+            "abc",
+            ConfigScope::Repo,
+            None,
+            dir.path(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            display_value("spotify.client_secret", &toml::Value::from("hunter2")),
+            "********"
+        );
+        assert_eq!(
+            display_value("spotify.client_id", &toml::Value::from("abc")),
+            "abc"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_numeric_keys_reject_other_values() {
+        let dir = TempDir::new().unwrap();
+
+        for key in ["http.max_retries", "auth.port", "search.page_size"] {
+            let err = set(key, "lots", ConfigScope::Repo, None, dir.path())
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("must be a number"));
+        }
+        assert!(!config::repo_path(dir.path()).exists());
+
+        set("http.max_retries", "2", ConfigScope::Repo, None, dir.path())
+            .await
+            .unwrap();
+        let config = Config::load(dir.path(), None).unwrap();
+        assert_eq!(config.get_u64("http.max_retries").unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_playlist_scope_takes_only_playlist_keys() {
+        let dir = TempDir::new().unwrap();
+
+        let err = set(
+            "search.page_size",
+            "5",
+            ConfigScope::Playlist,
+            Some("pl1"),
+            dir.path(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("can't be set per playlist"));
+        assert!(!config::playlist_path(dir.path(), "pl1").exists());
+
+        set(
+            "player.backend",
+            "mpv",
+            ConfigScope::Playlist,
+            Some("pl1"),
+            dir.path(),
+        )
+        .await
+        .unwrap();
+        assert!(config::playlist_path(dir.path(), "pl1").exists());
+    }
+}
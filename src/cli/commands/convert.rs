@@ -0,0 +1,491 @@
+use std::{
+    fs,
+    io::{self, IsTerminal},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::{
+    cli::commands::{
+        init::{detect_provider, resolve_id},
+        output::print_json,
+        remote::pick_match,
+        utils::create_provider,
+    },
+    matching::{self, Candidate, Confidence, MatchCache},
+    provider::{PlaylistSnapshot, Provider, ProviderKind, SourceKind, Track},
+    state::{
+        clear_staged, diff, quota, remotes, snapshot, working_playlist, JournalEntry, Operation,
+    },
+};
+
+/// Outcome of a conversion, saved as `conversion-report.json` next to the
+/// new playlist's snapshot.
+#[derive(Debug, Serialize)]
+pub struct ConversionReport {
+    pub source_provider: ProviderKind,
+    pub source_id: String,
+    pub target_provider: ProviderKind,
+    pub target_id: String,
+    pub matched: Vec<ReportEntry>,
+    pub low_confidence: Vec<ReportEntry>,
+    pub unmatched: Vec<ReportEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportEntry {
+    pub source_id: String,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+impl ReportEntry {
+    fn new(source: &Track, candidate: Option<&Candidate>) -> Self {
+        Self {
+            source_id: source.id.clone(),
+            source: describe(source),
+            target_id: candidate.map(|c| c.track.id.clone()),
+            target: candidate.map(|c| describe(&c.track)),
+            score: candidate.map(|c| c.score),
+        }
+    }
+}
+
+pub struct ConvertOptions {
+    pub to: ProviderKind,
+    pub into: Option<String>,
+    pub review: bool,
+}
+
+/// Copy a playlist to another provider and start tracking the copy.
+///
+/// `source` is a tracked playlist ID, or the URL/ID of any playlist on
+/// `source_provider`.
+pub async fn run(
+    source: &str,
+    source_provider: Option<ProviderKind>,
+    options: &ConvertOptions,
+    grit_dir: &Path,
+    json: bool,
+) -> Result<()> {
+    if json && options.review {
+        bail!("--review is interactive and can't be combined with --json");
+    }
+    if options.review && !io::stdin().is_terminal() {
+        bail!("--review asks which match to use, so it needs a terminal");
+    }
+
+    let source_snapshot = load_source(source, source_provider, grit_dir).await?;
+
+    if source_snapshot.provider == options.to {
+        bail!(
+            "Playlist is already on {}. Use 'grit remote add' to mirror it to another {} playlist.",
+            options.to,
+            options.to
+        );
+    }
+
+    let provider = create_provider(&options.to, grit_dir)?;
+    let report = convert(&source_snapshot, provider.as_ref(), options, grit_dir).await?;
+
+    if json {
+        return print_json(&report);
+    }
+
+    println!(
+        "\nConverted to {} playlist {}",
+        options.to, report.target_id
+    );
+    println!("  Matched:        {}", report.matched.len());
+    println!("  Low confidence: {}", report.low_confidence.len());
+    println!("  Unmatched:      {}", report.unmatched.len());
+
+    if !report.low_confidence.is_empty() {
+        println!("\nCheck these matches:");
+        for entry in &report.low_confidence {
+            println!(
+                "  {} -> {}",
+                entry.source,
+                entry.target.as_deref().unwrap_or("?")
+            );
+        }
+    }
+
+    println!("\nReport: {:?}", report_path(grit_dir, &report.target_id));
+
+    Ok(())
+}
+
+/// Match the tracks of `source_snapshot` on `provider`, write them to a new
+/// playlist or after the tracks already in `options.into`, and track the
+/// result.
+async fn convert(
+    source_snapshot: &PlaylistSnapshot,
+    provider: &dyn Provider,
+    options: &ConvertOptions,
+    grit_dir: &Path,
+) -> Result<ConversionReport> {
+    // Tracks already in the target are kept; matches are added after them
+    let existing = match &options.into {
+        Some(into) => {
+            if snapshot::snapshot_path(grit_dir, into).exists() {
+                bail!(
+                    "Playlist {} is already tracked. Use 'grit remote add' to mirror into it.",
+                    into
+                );
+            }
+            if !provider.can_modify_playlist(into).await? {
+                bail!("You don't have write access to playlist {}", into);
+            }
+            Some(provider.fetch(into).await?)
+        }
+        None => None,
+    };
+
+    let mut cache = MatchCache::load(grit_dir)?;
+
+    // Searches cost YouTube 100 units each, so check them before the first
+    if options.to == ProviderKind::Youtube {
+        let searches = matching::searches_needed(&source_snapshot.tracks, &options.to, &cache);
+        let inserts = source_snapshot.tracks.len() + usize::from(existing.is_none());
+        let cost = searches as u64 * quota::SEARCH + inserts as u64 * quota::WRITE;
+        quota::check_budget(grit_dir, "This conversion", cost, false)?;
+    }
+
+    println!(
+        "Matching {} tracks on {}...",
+        source_snapshot.tracks.len(),
+        options.to
+    );
+
+    let mut tracks: Vec<Track> = existing
+        .as_ref()
+        .map(|current| current.tracks.clone())
+        .unwrap_or_default();
+    let mut matched = Vec::new();
+    let mut low_confidence = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for track in &source_snapshot.tracks {
+        let candidates = matching::find_matches(track, &options.to, provider, &mut cache).await?;
+        let best = candidates.first();
+
+        let chosen = match best.map(Candidate::confidence) {
+            Some(Confidence::High) => best.map(|c| c.track.clone()),
+            Some(Confidence::Low) if options.review => pick_match(track, &candidates)?,
+            Some(Confidence::Low) => best.map(|c| c.track.clone()),
+            Some(Confidence::None) | None => None,
+        };
+
+        let Some(mut found) = chosen else {
+            println!("  Unmatched: {}", describe(track));
+            unmatched.push(ReportEntry::new(track, best));
+            continue;
+        };
+
+        let candidate = candidates.iter().find(|c| c.track.id == found.id);
+        let entry = ReportEntry::new(track, candidate);
+        match candidate.map(Candidate::confidence) {
+            Some(Confidence::High) => matched.push(entry),
+            _ => low_confidence.push(entry),
+        }
+
+        if tracks.iter().any(|t| t.id == found.id) {
+            println!("  Duplicate: {} (already in the playlist)", describe(track));
+            continue;
+        }
+
+        // Link back to the source track, like a mirror push would
+        remotes::record_mirror(&mut found, track)?;
+        tracks.push(found);
+    }
+
+    cache.save()?;
+
+    let current = match existing {
+        Some(current) => current,
+        None => {
+            println!(
+                "\nCreating {} playlist '{}'...",
+                options.to, source_snapshot.name
+            );
+            let description = format!(
+                "Converted from {} playlist {} with grit",
+                source_snapshot.provider, source_snapshot.id
+            );
+            let id = provider
+                .create_playlist(&source_snapshot.name, Some(&description))
+                .await?;
+            provider.fetch(&id).await?
+        }
+    };
+    let target_id = current.id.clone();
+
+    let desired = PlaylistSnapshot {
+        id: target_id.clone(),
+        name: current.name.clone(),
+        description: current.description.clone(),
+        tracks,
+        provider: options.to.clone(),
+        source: SourceKind::Playlist,
+        snapshot_hash: String::new(),
+        metadata: None,
+    };
+
+    let patch = diff(&current, &desired);
+    if !patch.changes.is_empty() {
+        println!(
+            "Writing {} tracks...",
+            desired.tracks.len() - current.tracks.len()
+        );
+        provider.apply(&target_id, &patch, &desired).await?;
+    }
+
+    // Track the result as a regular grit playlist
+    let mut converted = provider.fetch(&target_id).await?;
+    remotes::preserve_local_metadata(&desired.tracks, &mut converted.tracks);
+
+    let snapshot_path = snapshot::snapshot_path(grit_dir, &target_id);
+    snapshot::save(&converted, &snapshot_path)?;
+    let hash = snapshot::compute_hash(&converted)?;
+    snapshot::save_by_hash(&converted, &hash, grit_dir, &target_id)?;
+
+    let entry = JournalEntry::new_with_message(
+        Operation::Init,
+        hash,
+        converted.tracks.len(),
+        0,
+        0,
+        format!(
+            "Converted from {}:{}",
+            source_snapshot.provider, source_snapshot.id
+        ),
+    );
+    JournalEntry::record(grit_dir, &target_id, &entry)?;
+
+    clear_staged(grit_dir, &target_id)?;
+    working_playlist::save(grit_dir, &target_id)?;
+
+    let report = ConversionReport {
+        source_provider: source_snapshot.provider.clone(),
+        source_id: source_snapshot.id.clone(),
+        target_provider: options.to.clone(),
+        target_id: target_id.clone(),
+        matched,
+        low_confidence,
+        unmatched,
+    };
+
+    let report_path = report_path(grit_dir, &target_id);
+    let report_json =
+        serde_json::to_string_pretty(&report).context("Failed to serialize conversion report")?;
+    fs::write(&report_path, report_json)
+        .with_context(|| format!("Failed to write {:?}", report_path))?;
+
+    Ok(report)
+}
+
+pub fn report_path(grit_dir: &Path, playlist_id: &str) -> std::path::PathBuf {
+    grit_dir
+        .join("playlists")
+        .join(playlist_id)
+        .join("conversion-report.json")
+}
+
+async fn load_source(
+    source: &str,
+    provider: Option<ProviderKind>,
+    grit_dir: &Path,
+) -> Result<PlaylistSnapshot> {
+    let tracked = snapshot::snapshot_path(grit_dir, source);
+    if tracked.exists() {
+        return snapshot::load(&tracked);
+    }
+
+    let provider_kind = provider.or_else(|| detect_provider(source)).context(
+        "Playlist is not tracked and its provider could not be detected (use --provider)",
+    )?;
+    let id = resolve_id(&provider_kind, source, grit_dir)?;
+
+    println!("Fetching playlist {}...", id);
+    create_provider(&provider_kind, grit_dir)?.fetch(&id).await
+}
+
+fn describe(track: &Track) -> String {
+    format!("{} - {}", track.artists.join(", "), track.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::FakeProvider;
+    use tempfile::TempDir;
+
+    fn track(id: &str, name: &str, provider: ProviderKind) -> Track {
+        Track {
+            id: id.to_string(),
+            name: name.to_string(),
+            artists: vec!["Band".to_string()],
+            duration_ms: 200_000,
+            provider,
+            metadata: None,
+        }
+    }
+
+    fn source(names: &[&str]) -> PlaylistSnapshot {
+        PlaylistSnapshot {
+            id: "sp1".to_string(),
+            tracks: names
+                .iter()
+                .map(|name| track(&format!("sp-{}", name), name, ProviderKind::Spotify))
+                .collect(),
+            ..PlaylistSnapshot::fixture(ProviderKind::Spotify, &[])
+        }
+    }
+
+    fn target() -> FakeProvider {
+        let catalog = ["Alpha", "Beta", "Gamma"]
+            .iter()
+            .map(|name| track(&format!("sub-{}", name), name, ProviderKind::Subsonic))
+            .collect();
+        FakeProvider::new(ProviderKind::Subsonic).with_catalog(catalog)
+    }
+
+    fn options(into: Option<&str>) -> ConvertOptions {
+        ConvertOptions {
+            to: ProviderKind::Subsonic,
+            into: into.map(str::to_string),
+            review: false,
+        }
+    }
+
+    fn ids(provider: &FakeProvider, id: &str) -> Vec<String> {
+        let playlist = provider.playlist(id).unwrap();
+        playlist.tracks.into_iter().map(|t| t.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_convert_creates_a_tracked_playlist() {
+        let dir = TempDir::new().unwrap();
+        let provider = target();
+
+        let report = convert(
+            &source(&["Alpha", "Beta", "Unknown Song"]),
+            &provider,
+            &options(None),
+            dir.path(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.target_id, "fake-1");
+        assert_eq!(report.matched.len(), 2);
+        assert_eq!(report.unmatched.len(), 1);
+        assert_eq!(ids(&provider, "fake-1"), vec!["sub-Alpha", "sub-Beta"]);
+
+        let stored = snapshot::load(&snapshot::snapshot_path(dir.path(), "fake-1")).unwrap();
+        assert_eq!(stored.tracks.len(), 2);
+        let history = JournalEntry::history(dir.path(), "fake-1").unwrap();
+        assert_eq!(
+            history[0].message.as_deref(),
+            Some("Converted from spotify:sp1")
+        );
+        assert!(report_path(dir.path(), "fake-1").exists());
+    }
+
+    #[tokio::test]
+    async fn test_convert_into_keeps_existing_tracks() {
+        let dir = TempDir::new().unwrap();
+        let existing = PlaylistSnapshot {
+            id: "t1".to_string(),
+            provider: ProviderKind::Subsonic,
+            tracks: vec![
+                track("sub-Gamma", "Gamma", ProviderKind::Subsonic),
+                track("sub-Alpha", "Alpha", ProviderKind::Subsonic),
+            ],
+            ..source(&[])
+        };
+        let provider = target().with_playlist(existing);
+
+        convert(
+            &source(&["Alpha", "Beta"]),
+            &provider,
+            &options(Some("t1")),
+            dir.path(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            ids(&provider, "t1"),
+            vec!["sub-Gamma", "sub-Alpha", "sub-Beta"]
+        );
+        assert!(provider.playlist("fake-1").is_none());
+
+        // A tracked playlist is mirrored with 'grit remote add' instead
+        let err = convert(
+            &source(&["Alpha"]),
+            &provider,
+            &options(Some("t1")),
+            dir.path(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("already tracked"));
+    }
+
+    #[tokio::test]
+    async fn test_convert_adds_duplicate_source_tracks_once() {
+        let dir = TempDir::new().unwrap();
+        let provider = target();
+
+        let report = convert(
+            &source(&["Alpha", "Beta", "Alpha"]),
+            &provider,
+            &options(None),
+            dir.path(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.matched.len(), 3);
+        assert_eq!(ids(&provider, "fake-1"), vec!["sub-Alpha", "sub-Beta"]);
+    }
+
+    #[tokio::test]
+    async fn test_convert_checks_the_youtube_quota_before_searching() {
+        let dir = TempDir::new().unwrap();
+        crate::state::config::set(
+            &crate::state::config::repo_path(dir.path()),
+            "youtube.quota_budget",
+            "250",
+        )
+        .unwrap();
+        let provider = FakeProvider::new(ProviderKind::Youtube);
+        let options = ConvertOptions {
+            to: ProviderKind::Youtube,
+            into: None,
+            review: false,
+        };
+
+        // Three searches and four inserts (the playlist, then each track)
+        let err = convert(
+            &source(&["Alpha", "Beta", "Gamma"]),
+            &provider,
+            &options,
+            dir.path(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("needs about 500"));
+        assert!(provider.playlist("fake-1").is_none());
+        assert!(!MatchCache::path(dir.path()).exists());
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::{
+    cli::commands::utils::create_provider,
+    provider::{PlaylistSnapshot, Provider, SourceKind},
+    state::{clear_staged, diff, snapshot, working_playlist, JournalEntry, Operation},
+};
+
+/// Create a new playlist on the provider with the tracks of a tracked source
+/// (typically a read-only album) and start tracking the copy.
+pub async fn run(playlist_id: &str, name: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
+    if !snapshot_path.exists() {
+        bail!("Playlist not initialized. Run 'grit init' first.");
+    }
+
+    let source = snapshot::load(&snapshot_path)?;
+    let provider = create_provider(&source.provider, grit_dir)?;
+
+    let fork_id = fork(&source, name, provider.as_ref(), grit_dir).await?;
+
+    println!(
+        "\nForked {} {} into playlist {}",
+        source.source, source.id, fork_id
+    );
+    println!(
+        "  Now on playlist {}; use 'grit push' after committing changes",
+        fork_id
+    );
+
+    Ok(())
+}
+
+/// Copy `source` into a new playlist on `provider`, record it as a tracked
+/// playlist and make it the working playlist. Returns the new playlist's ID.
+async fn fork(
+    source: &PlaylistSnapshot,
+    name: Option<&str>,
+    provider: &dyn Provider,
+    grit_dir: &Path,
+) -> Result<String> {
+    let capabilities = provider.capabilities();
+    if !capabilities.create || !capabilities.write {
+        bail!("{} doesn't support creating playlists", source.provider);
+    }
+
+    let name = name.unwrap_or(&source.name);
+    println!("Creating {} playlist '{}'...", source.provider, name);
+    let description = format!("Forked from {} {} with grit", source.source, source.id);
+    let fork_id = provider.create_playlist(name, Some(&description)).await?;
+
+    let mut forked = provider.fetch(&fork_id).await?;
+    let desired = PlaylistSnapshot {
+        tracks: source.tracks.clone(),
+        ..forked.clone()
+    };
+
+    let patch = diff(&forked, &desired);
+    if !patch.changes.is_empty() {
+        println!("Writing {} tracks...", desired.tracks.len());
+        provider.apply(&fork_id, &patch, &desired).await?;
+        forked = provider.fetch(&fork_id).await?;
+    }
+    forked.source = SourceKind::Playlist;
+
+    let snapshot_path = snapshot::snapshot_path(grit_dir, &fork_id);
+    snapshot::save(&forked, &snapshot_path)?;
+    let hash = snapshot::compute_hash(&forked)?;
+    snapshot::save_by_hash(&forked, &hash, grit_dir, &fork_id)?;
+
+    let entry = JournalEntry::new_with_message(
+        Operation::Init,
+        hash,
+        forked.tracks.len(),
+        0,
+        0,
+        format!("Forked from {} {}", source.source, source.id),
+    );
+    JournalEntry::record(grit_dir, &fork_id, &entry)?;
+
+    clear_staged(grit_dir, &fork_id)?;
+    working_playlist::save(grit_dir, &fork_id)?;
+
+    Ok(fork_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{FakeProvider, ProviderKind};
+    use tempfile::TempDir;
+
+    fn album(ids: &[&str]) -> PlaylistSnapshot {
+        PlaylistSnapshot {
+            id: "al1".to_string(),
+            name: "Record".to_string(),
+            source: SourceKind::Album,
+            ..PlaylistSnapshot::fixture(ProviderKind::Spotify, ids)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fork_copies_album_into_editable_playlist() {
+        let dir = TempDir::new().unwrap();
+        let grit_dir = dir.path();
+        let provider = FakeProvider::new(ProviderKind::Spotify);
+
+        let fork_id = fork(&album(&["t1", "t2"]), None, &provider, grit_dir)
+            .await
+            .unwrap();
+
+        let remote = provider.playlist(&fork_id).unwrap();
+        assert_eq!(remote.name, "Record");
+        let ids: Vec<_> = remote.tracks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["t1", "t2"]);
+
+        let local = snapshot::load(&snapshot::snapshot_path(grit_dir, &fork_id)).unwrap();
+        assert_eq!(local.source, SourceKind::Playlist);
+        assert_eq!(local.tracks.len(), 2);
+        assert_eq!(working_playlist::load(grit_dir).unwrap(), fork_id);
+
+        let history = JournalEntry::history(grit_dir, &fork_id).unwrap();
+        assert_eq!(history[0].operation, Operation::Init);
+        assert_eq!(history[0].message.as_deref(), Some("Forked from album al1"));
+    }
+}
@@ -1,12 +1,26 @@
-use crate::provider::{Provider, ProviderKind, SpotifyProvider, YoutubeProvider};
+use crate::cli::commands::{
+    remote::account_playlists,
+    utils::{create_provider, local_root},
+};
+use crate::provider::{LocalProvider, PlaylistSnapshot, Provider, ProviderKind, SourceKind};
 use crate::state::{
-    clear_staged, credentials, snapshot, working_playlist, JournalEntry, Operation,
+    clear_staged, config::Config, snapshot, working_playlist, JournalEntry, Operation,
 };
-use anyhow::{Context, Result};
+use anyhow::Result;
 use std::path::Path;
 
 /// Extract playlist/album ID from URL or return as-is if already an ID
-fn extract_id(input: &str) -> String {
+pub fn extract_id(input: &str) -> String {
+    // Plugin and Subsonic playlists: "<provider>::<id>", as with git remote helpers
+    if let Some((_, id)) = plugin_address(input) {
+        return id.to_string();
+    }
+
+    // Liked songs: "spotify:liked", tracked as "spotify-liked"
+    if let Some(name) = liked_address(input) {
+        return format!("{}-liked", name);
+    }
+
     // Handle Spotify playlist URLs
     if input.contains("spotify.com/playlist/") {
         return input
@@ -35,26 +49,72 @@ fn extract_id(input: &str) -> String {
         }
     }
 
+    // Local M3U playlists are identified by their file name
+    if is_m3u_path(input) {
+        return std::path::Path::new(input)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| input.to_string());
+    }
+
     input.to_string()
 }
 
+/// Like `extract_id`, but checks that a local playlist file is one the
+/// local provider can read and write back.
+pub fn resolve_id(provider: &ProviderKind, input: &str, grit_dir: &Path) -> Result<String> {
+    if *provider == ProviderKind::Local && is_m3u_path(input) {
+        let root = local_root(&Config::load(grit_dir, None)?)?;
+        return LocalProvider::new(root).playlist_id(Path::new(input));
+    }
+    Ok(extract_id(input))
+}
+
+/// Split "<provider>::<id>" into the provider name and the playlist ID.
+fn plugin_address(input: &str) -> Option<(&str, &str)> {
+    let (name, id) = input.split_once("::")?;
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some((name, id))
+}
+
+/// The provider name of a liked songs address like "youtube:liked".
+fn liked_address(input: &str) -> Option<&str> {
+    let name = input.strip_suffix(":liked")?;
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then_some(name)
+}
+
+fn is_m3u_path(input: &str) -> bool {
+    let lower = input.to_lowercase();
+    lower.ends_with(".m3u8") || lower.ends_with(".m3u")
+}
+
 fn is_album_url(input: &str) -> bool {
     input.contains("spotify.com/album/")
 }
 
 /// Detect provider from playlist URL
 pub fn detect_provider(input: &str) -> Option<ProviderKind> {
-    if input.contains("spotify.com") {
+    if let Some((name, _)) = plugin_address(input) {
+        name.parse().ok()
+    } else if let Some(name) = liked_address(input) {
+        name.parse().ok()
+    } else if input.contains("spotify.com") {
         Some(ProviderKind::Spotify)
     } else if input.contains("youtube.com") || input.contains("youtu.be") {
         Some(ProviderKind::Youtube)
+    } else if is_m3u_path(input) {
+        Some(ProviderKind::Local)
     } else {
         None
     }
 }
 
 pub async fn run(provider: ProviderKind, input: &str, grit_dir: &Path) -> Result<()> {
-    let id = extract_id(input);
+    let id = resolve_id(&provider, input, grit_dir)?;
     let is_album = is_album_url(input);
 
     let snapshot_path = snapshot::snapshot_path(grit_dir, &id);
@@ -66,54 +126,31 @@ pub async fn run(provider: ProviderKind, input: &str, grit_dir: &Path) -> Result
         );
     }
 
-    let token = credentials::load(grit_dir, provider)?.context(format!(
-        "No credentials found. Please run 'grit auth {provider}' first."
-    ))?;
-
-    let playlist = match provider {
-        ProviderKind::Spotify => {
-            let client_id =
-                std::env::var("SPOTIFY_CLIENT_ID").context("SPOTIFY_CLIENT_ID not set")?;
-            let client_secret =
-                std::env::var("SPOTIFY_CLIENT_SECRET").context("SPOTIFY_CLIENT_SECRET not set")?;
-
-            let spotify =
-                SpotifyProvider::new(client_id, client_secret).with_token(&token, grit_dir);
-
-            if is_album {
-                println!("Fetching album {}...", id);
-                spotify.fetch_album(&id).await?
-            } else {
-                println!("Fetching playlist {}...", id);
-                spotify.fetch(&id).await?
-            }
-        }
-        ProviderKind::Youtube => {
-            let client_id =
-                std::env::var("YOUTUBE_CLIENT_ID").context("YOUTUBE_CLIENT_ID not set")?;
-            let client_secret =
-                std::env::var("YOUTUBE_CLIENT_SECRET").context("YOUTUBE_CLIENT_SECRET not set")?;
-
-            let youtube =
-                YoutubeProvider::new(client_id, client_secret).with_token(&token, grit_dir);
-            println!("Fetching playlist {}...", id);
-            youtube.fetch(&id).await?
-        }
+    let source = if is_album {
+        SourceKind::Album
+    } else if liked_address(input).is_some() {
+        SourceKind::Liked
+    } else {
+        SourceKind::Playlist
     };
+    let provider = create_provider(&provider, grit_dir)?;
+    if !provider.capabilities().sources.contains(&source) {
+        anyhow::bail!("This provider can't track {} sources", source);
+    }
+
+    println!("Fetching {} {}...", source, id);
+    let playlist = track(provider.as_ref(), source, &id, grit_dir).await?;
+    if !source.is_editable() {
+        println!(
+            "  Read-only {} source; use 'grit fork' to make an editable copy",
+            source
+        );
+    }
 
     println!("  Name: {}", playlist.name);
     println!("  Tracks: {}", playlist.tracks.len());
 
-    snapshot::save(&playlist, &snapshot_path)?;
-    let hash = snapshot::compute_hash(&playlist)?;
-
-    snapshot::save_by_hash(&playlist, &hash, grit_dir, &id)?;
-
     let journal_path = JournalEntry::journal_path(grit_dir, &id);
-    let entry = JournalEntry::new(Operation::Init, hash, playlist.tracks.len(), 0, 0);
-    JournalEntry::append(&journal_path, &entry)?;
-
-    clear_staged(grit_dir, &id)?;
 
     println!(
         "\n{} initialized!",
@@ -126,3 +163,135 @@ pub async fn run(provider: ProviderKind, input: &str, grit_dir: &Path) -> Result
 
     Ok(())
 }
+
+/// Fetch a source and record it as a newly tracked playlist: snapshot,
+/// stored copy, Init journal entry and an empty staging area.
+async fn track(
+    provider: &dyn Provider,
+    source: SourceKind,
+    id: &str,
+    grit_dir: &Path,
+) -> Result<PlaylistSnapshot> {
+    let playlist = provider.fetch_source(source, id).await?;
+
+    snapshot::save(&playlist, &snapshot::snapshot_path(grit_dir, id))?;
+    let hash = snapshot::compute_hash(&playlist)?;
+    snapshot::save_by_hash(&playlist, &hash, grit_dir, id)?;
+
+    let entry = JournalEntry::new(Operation::Init, hash, playlist.tracks.len(), 0, 0);
+    JournalEntry::record(grit_dir, id, &entry)?;
+
+    clear_staged(grit_dir, id)?;
+
+    Ok(playlist)
+}
+
+/// Track every playlist of the signed-in account on `provider`, optionally
+/// only those it can change or whose name contains `filter`.
+pub async fn clone_all(
+    provider: ProviderKind,
+    owned: bool,
+    filter: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    let provider = create_provider(&provider, grit_dir)?;
+    clone_playlists(provider.as_ref(), owned, filter, grit_dir).await
+}
+
+async fn clone_playlists(
+    provider: &dyn Provider,
+    owned: bool,
+    filter: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    println!("Listing playlists...");
+    let (tracked, new): (Vec<_>, Vec<_>) = account_playlists(provider, owned, filter)
+        .await?
+        .into_iter()
+        .partition(|p| snapshot::snapshot_path(grit_dir, &p.id).exists());
+
+    if !tracked.is_empty() {
+        println!("  Skipping {} already tracked", tracked.len());
+    }
+    if new.is_empty() {
+        println!("Nothing to clone.");
+        return Ok(());
+    }
+
+    let mut cloned = Vec::new();
+    let mut failed = Vec::new();
+    for (i, remote) in new.iter().enumerate() {
+        println!("[{}/{}] {}", i + 1, new.len(), remote.name);
+        match track(provider, SourceKind::Playlist, &remote.id, grit_dir).await {
+            Ok(playlist) => {
+                println!("  {} ({} tracks)", remote.id, playlist.tracks.len());
+                cloned.push(remote.id.clone());
+            }
+            Err(e) => {
+                println!("  Failed: {:#}", e);
+                failed.push(remote);
+            }
+        }
+    }
+
+    // Keep the current working playlist unless there is none to go back to
+    let current = working_playlist::load(grit_dir).unwrap_or_default();
+    if current.is_empty() || !snapshot::snapshot_path(grit_dir, &current).exists() {
+        if let Some(first) = cloned.first() {
+            working_playlist::save(grit_dir, first)?;
+            println!("\nNow on playlist {}", first);
+        }
+    }
+
+    println!("\nCloned {} playlist(s).", cloned.len());
+    if !failed.is_empty() {
+        println!("{} failed:", failed.len());
+        for remote in &failed {
+            println!("  {} {}", remote.id, remote.name);
+        }
+        anyhow::bail!("Some playlists could not be cloned; rerun to retry them");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::FakeProvider;
+    use tempfile::TempDir;
+
+    fn playlist(id: &str, name: &str) -> PlaylistSnapshot {
+        PlaylistSnapshot {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..PlaylistSnapshot::fixture(ProviderKind::Spotify, &[&format!("{}-t1", id)])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clone_playlists_skips_tracked_and_sets_working_playlist() {
+        let dir = TempDir::new().unwrap();
+        let grit_dir = dir.path();
+        let provider = FakeProvider::new(ProviderKind::Spotify)
+            .with_playlist(playlist("a", "Running"))
+            .with_playlist(playlist("b", "Evening"))
+            .with_playlist(playlist("c", "Morning run"));
+
+        clone_playlists(&provider, false, Some("RUN"), grit_dir)
+            .await
+            .unwrap();
+        assert!(snapshot::snapshot_path(grit_dir, "a").exists());
+        assert!(!snapshot::snapshot_path(grit_dir, "b").exists());
+        assert!(snapshot::snapshot_path(grit_dir, "c").exists());
+        assert_eq!(working_playlist::load(grit_dir).unwrap(), "a");
+
+        working_playlist::save(grit_dir, "c").unwrap();
+        clone_playlists(&provider, false, None, grit_dir)
+            .await
+            .unwrap();
+        assert!(snapshot::snapshot_path(grit_dir, "b").exists());
+        assert_eq!(working_playlist::load(grit_dir).unwrap(), "c");
+        assert_eq!(JournalEntry::history(grit_dir, "a").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    cli::commands::utils::{create_provider, placeholder_id},
+    formats::{spotify_export, takeout, ExportedPlaylist},
+    provider::{PlaylistSnapshot, ProviderKind, SourceKind, Track},
+    state::{clear_staged, remotes, snapshot, JournalEntry, Operation},
+};
+
+/// Track every playlist in a Spotify account export (`Playlist1.json`, or
+/// the folder holding it) or a Google Takeout playlists folder, without
+/// calling any API.
+pub async fn import_export(path: &str, grit_dir: &Path) -> Result<()> {
+    let path = Path::new(path);
+    if !path.exists() {
+        bail!("{:?} does not exist", path);
+    }
+
+    let mut found: Vec<(ProviderKind, &str, ExportedPlaylist)> = Vec::new();
+    for file in spotify_files(path)? {
+        let content =
+            fs::read_to_string(&file).with_context(|| format!("Failed to read {:?}", file))?;
+        for playlist in spotify_export::parse(&content)? {
+            found.push((ProviderKind::Spotify, "Spotify account export", playlist));
+        }
+    }
+    for dir in csv_dirs(path)? {
+        for playlist in takeout::parse_dir(&dir)? {
+            found.push((ProviderKind::Youtube, "Google Takeout", playlist));
+        }
+    }
+
+    if found.is_empty() {
+        bail!(
+            "No Spotify Playlist*.json files or Takeout playlist CSVs found in {:?}",
+            path
+        );
+    }
+
+    let earlier = earlier_imports(grit_dir)?;
+    let mut imported = Vec::new();
+    for (provider, source, playlist) in found {
+        let unpublished = playlist.id.is_none();
+        let id = match playlist.id {
+            Some(id) if snapshot::snapshot_path(grit_dir, &id).exists() => {
+                println!("  Skipped {} ({} is already tracked)", playlist.name, id);
+                continue;
+            }
+            Some(id) => id,
+            None => {
+                let key = (
+                    provider.clone(),
+                    import_message(source),
+                    playlist.name.clone(),
+                );
+                if let Some((_, id)) = earlier.iter().find(|(k, _)| *k == key) {
+                    println!("  Skipped {} (imported before as {})", playlist.name, id);
+                    continue;
+                }
+                placeholder_id("export", &playlist.name, grit_dir)
+            }
+        };
+
+        let snap = PlaylistSnapshot {
+            id: id.clone(),
+            name: playlist.name,
+            description: playlist.description,
+            tracks: playlist.tracks,
+            provider,
+            source: SourceKind::Playlist,
+            snapshot_hash: String::new(),
+            metadata: unpublished.then(remotes::unpublished_metadata),
+        };
+
+        let snapshot_path = snapshot::snapshot_path(grit_dir, &id);
+        if let Some(parent) = snapshot_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        snapshot::save(&snap, &snapshot_path)?;
+        let hash = snapshot::compute_hash(&snap)?;
+        snapshot::save_by_hash(&snap, &hash, grit_dir, &id)?;
+
+        let entry = JournalEntry::new_with_message(
+            Operation::Init,
+            hash,
+            snap.tracks.len(),
+            0,
+            0,
+            import_message(source),
+        );
+        JournalEntry::record(grit_dir, &id, &entry)?;
+        clear_staged(grit_dir, &id)?;
+
+        let skipped = if playlist.skipped > 0 {
+            format!(", {} non-track items skipped", playlist.skipped)
+        } else {
+            String::new()
+        };
+        println!(
+            "  {} {} ({} tracks{})",
+            id,
+            snap.name,
+            snap.tracks.len(),
+            skipped
+        );
+        imported.push((id, unpublished));
+    }
+
+    println!("\nImported {} playlist(s).", imported.len());
+    println!("Run 'grit enrich -l <id>' to fetch full track details.");
+    if imported.iter().any(|(_, unpublished)| *unpublished) {
+        println!(
+            "Playlists without an ID in the export aren't linked to the provider yet;\n\
+             'grit push' creates them there, or mirror them with 'grit remote add'."
+        );
+    }
+
+    Ok(())
+}
+
+/// Provider, journal message and name of an imported playlist
+type ImportKey = (ProviderKind, String, String);
+
+fn import_message(source: &str) -> String {
+    format!("Imported from {}", source)
+}
+
+/// IDs of playlists tracked by an earlier import, keyed by the name they
+/// were imported with, so exports without playlist IDs can be imported
+/// again without making copies.
+fn earlier_imports(grit_dir: &Path) -> Result<Vec<(ImportKey, String)>> {
+    let playlists_dir = grit_dir.join("playlists");
+    if !playlists_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut found = Vec::new();
+    let entries = fs::read_dir(&playlists_dir)
+        .with_context(|| format!("Failed to read directory {:?}", playlists_dir))?;
+    for entry in entries.flatten() {
+        let id = entry.file_name().to_string_lossy().into_owned();
+        let history = JournalEntry::history(grit_dir, &id).unwrap_or_default();
+        let Some(first) = history.first() else {
+            continue;
+        };
+        let Some(message) = first.message.clone() else {
+            continue;
+        };
+        if first.operation != Operation::Init || !message.starts_with("Imported from ") {
+            continue;
+        }
+        if let Ok(snap) = snapshot::load_by_hash(&first.snapshot_hash, grit_dir, &id) {
+            found.push(((snap.provider, message, snap.name), id));
+        }
+    }
+    Ok(found)
+}
+
+/// Spotify export files: the given file, or `Playlist*.json` under a folder.
+fn spotify_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        return Ok(if is_json {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        });
+    }
+
+    let mut files = Vec::new();
+    for dir in walk_dirs(path)? {
+        for entry in fs::read_dir(&dir)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            if name.starts_with("playlist") && name.ends_with(".json") {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Folders under `path` that contain CSV files (Takeout candidates).
+fn csv_dirs(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let mut dirs = Vec::new();
+    for dir in walk_dirs(path)? {
+        let has_csv = fs::read_dir(&dir)?.flatten().any(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+        });
+        if has_csv {
+            dirs.push(dir);
+        }
+    }
+    Ok(dirs)
+}
+
+fn walk_dirs(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries =
+            fs::read_dir(&dir).with_context(|| format!("Failed to read directory {:?}", dir))?;
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                pending.push(entry.path());
+            }
+        }
+        dirs.push(dir);
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Fetch full details (durations, video titles) for tracks imported from
+/// an account export.
+pub async fn enrich(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+
+    let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
+    if !snapshot_path.exists() {
+        bail!("Playlist not initialized. Run 'grit init' first.");
+    }
+
+    let mut snap = snapshot::load(&snapshot_path)?;
+    let pending: Vec<usize> = (0..snap.tracks.len())
+        .filter(|&i| is_incomplete(&snap.tracks[i]))
+        .collect();
+
+    if pending.is_empty() {
+        println!("All tracks already have full details.");
+        return Ok(());
+    }
+
+    let provider = create_provider(&snap.provider, grit_dir)?;
+    println!(
+        "Fetching details for {} track(s) from {}...",
+        pending.len(),
+        snap.provider
+    );
+
+    let mut enriched = 0;
+    for index in pending {
+        let track = &mut snap.tracks[index];
+        match provider.fetch_track(&track.id).await {
+            Ok(fetched) => {
+                *track = merge_details(track, fetched);
+                enriched += 1;
+            }
+            Err(e) => eprintln!("  Warning: {} ({})", e, track.id),
+        }
+    }
+
+    if enriched == 0 {
+        bail!("Could not fetch any track details");
+    }
+
+    snapshot::save(&snap, &snapshot_path)?;
+    let hash = snapshot::compute_hash(&snap)?;
+    snapshot::save_by_hash(&snap, &hash, grit_dir, playlist_id)?;
+
+    let entry = JournalEntry::new_with_message(
+        Operation::Commit,
+        hash,
+        0,
+        0,
+        0,
+        format!("Enrich {} track(s) from {}", enriched, snap.provider),
+    );
+    JournalEntry::record(grit_dir, playlist_id, &entry)?;
+
+    println!("Updated {} track(s).", enriched);
+    Ok(())
+}
+
+fn is_incomplete(track: &Track) -> bool {
+    track
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("incomplete"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// The fetched track, keeping metadata only known locally (album, when it
+/// was added, mirror mappings) unless the provider has its own value.
+fn merge_details(existing: &Track, fetched: Track) -> Track {
+    let mut metadata = existing
+        .metadata
+        .clone()
+        .unwrap_or_else(|| serde_json::json!({}));
+    if let Some(object) = metadata.as_object_mut() {
+        object.remove("incomplete");
+        if let Some(serde_json::Value::Object(new)) = &fetched.metadata {
+            for (key, value) in new {
+                object.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    Track {
+        metadata: Some(metadata).filter(|m| m.as_object().is_some_and(|o| !o.is_empty())),
+        ..fetched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_import_spotify_export_folder() {
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path().join(".grit");
+        let export = temp.path().join("Spotify Account Data");
+        fs::create_dir_all(&export).unwrap();
+        fs::write(
+            export.join("Playlist1.json"),
+            r#"{"playlists":[{"name":"Road Trip","items":[{"track":{"trackName":"Song","artistName":"Band","albumName":"LP","trackUri":"spotify:track:abc"},"addedDate":"2023-01-01"}]}]}"#,
+        )
+        .unwrap();
+
+        import_export(export.to_str().unwrap(), &grit_dir)
+            .await
+            .unwrap();
+        import_export(export.to_str().unwrap(), &grit_dir)
+            .await
+            .unwrap();
+
+        let snap = snapshot::load(&snapshot::snapshot_path(&grit_dir, "export-road-trip")).unwrap();
+        assert_eq!(snap.tracks[0].id, "abc");
+        assert!(!remotes::has_origin(&snap));
+        assert!(!snapshot::snapshot_path(&grit_dir, "export-road-trip-2").exists());
+
+        let journal =
+            JournalEntry::read_all(&JournalEntry::journal_path(&grit_dir, "export-road-trip"))
+                .unwrap();
+        assert_eq!(journal[0].operation, Operation::Init);
+    }
+
+    #[test]
+    fn test_merge_details_keeps_local_metadata() {
+        let existing = Track {
+            id: "abc".to_string(),
+            name: "abc".to_string(),
+            artists: vec![],
+            duration_ms: 0,
+            provider: ProviderKind::Youtube,
+            metadata: Some(serde_json::json!({ "incomplete": true, "added_at": "2020" })),
+        };
+        let fetched = Track {
+            name: "Real Title".to_string(),
+            duration_ms: 1000,
+            metadata: None,
+            ..existing.clone()
+        };
+
+        let merged = merge_details(&existing, fetched);
+        assert_eq!(merged.name, "Real Title");
+        assert_eq!(
+            merged.metadata,
+            Some(serde_json::json!({ "added_at": "2020" }))
+        );
+        assert!(!is_incomplete(&merged));
+    }
+}
@@ -3,9 +3,10 @@ use std::path::Path;
 
 use anyhow::{bail, Context, Result};
 
+use crate::cli::commands::output::{print_json, FindMatch, FindOutput, PlaylistSummary};
 use crate::state::{snapshot, working_playlist};
 
-pub async fn list(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+pub async fn list(playlist: Option<&str>, grit_dir: &Path, json: bool) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist)")?;
 
     let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
@@ -15,6 +16,10 @@ pub async fn list(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
 
     let snapshot = snapshot::load(&snapshot_path)?;
 
+    if json {
+        return print_json(&snapshot);
+    }
+
     println!("\nPlaylist: {}", snapshot.name);
     if let Some(desc) = &snapshot.description {
         println!("Description: {}", desc);
@@ -38,7 +43,7 @@ pub async fn list(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub async fn find(query: &str, playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+pub async fn find(query: &str, playlist: Option<&str>, grit_dir: &Path, json: bool) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist)")?;
 
     let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
@@ -62,6 +67,17 @@ pub async fn find(query: &str, playlist: Option<&str>, grit_dir: &Path) -> Resul
         })
         .collect();
 
+    if json {
+        return print_json(&FindOutput {
+            playlist_id: playlist_id.to_string(),
+            query: query.to_string(),
+            matches: matches
+                .into_iter()
+                .map(|(index, track)| FindMatch { index, track })
+                .collect(),
+        });
+    }
+
     if matches.is_empty() {
         println!("No tracks found matching '{}'", query);
         return Ok(());
@@ -91,10 +107,13 @@ pub async fn find(query: &str, playlist: Option<&str>, grit_dir: &Path) -> Resul
     Ok(())
 }
 
-pub async fn playlists(query: Option<&str>, grit_dir: &Path) -> Result<()> {
+pub async fn playlists(query: Option<&str>, grit_dir: &Path, json: bool) -> Result<()> {
     let playlists_dir = grit_dir.join("playlists");
 
     if !playlists_dir.exists() {
+        if json {
+            return print_json(&Vec::<PlaylistSummary>::new());
+        }
         println!("No playlists tracked yet. Use 'grit init <playlist-id>' to start tracking.");
         return Ok(());
     }
@@ -128,7 +147,7 @@ pub async fn playlists(query: Option<&str>, grit_dir: &Path) -> Result<()> {
         }
     }
 
-    if playlists_info.is_empty() {
+    if playlists_info.is_empty() && !json {
         println!("No playlists tracked yet. Use 'grit init <playlist-id>' to start tracking.");
         return Ok(());
     }
@@ -151,6 +170,14 @@ pub async fn playlists(query: Option<&str>, grit_dir: &Path) -> Result<()> {
         playlists_info
     };
 
+    if json {
+        let summaries: Vec<PlaylistSummary> = filtered
+            .iter()
+            .map(|(id, snapshot)| PlaylistSummary::new(id, snapshot))
+            .collect();
+        return print_json(&summaries);
+    }
+
     if filtered.is_empty() {
         println!("No playlists found matching '{}'", query.unwrap_or(""));
         return Ok(());
@@ -205,7 +232,7 @@ pub async fn switch(playlist_id: &str, grit_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub async fn curr(grit_dir: &Path) -> Result<()> {
+pub async fn curr(grit_dir: &Path, json: bool) -> Result<()> {
     let playlist_id = working_playlist::load(grit_dir)
         .context("No working playlist set. Use 'grit init' or 'grit switch' to set one.")?;
 
@@ -219,6 +246,10 @@ pub async fn curr(grit_dir: &Path) -> Result<()> {
 
     let snap = snapshot::load(&snapshot_path)?;
 
+    if json {
+        return print_json(&PlaylistSummary::new(&playlist_id, &snap));
+    }
+
     println!("\nCurrent working playlist:\n");
     println!("  Name:     {}", snap.name);
     println!("  ID:       {}", snap.id);
@@ -1,7 +1,16 @@
 pub mod auth;
+pub mod bundle;
+pub mod config;
+pub mod convert;
+pub mod fork;
 pub mod init;
+pub mod library;
 pub mod misc;
+pub mod new;
+pub mod output;
 pub mod play;
+pub mod remote;
 pub mod staging;
+pub mod transfer;
 mod utils;
 pub mod vcs;
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::{
+    cli::commands::{staging, transfer, utils::placeholder_id},
+    provider::{PlaylistSnapshot, ProviderKind, SourceKind},
+    state::{clear_staged, remotes, snapshot, working_playlist, JournalEntry, Operation},
+};
+
+/// Start a playlist that only exists locally until the first `grit push`
+/// creates it on `provider`.
+///
+/// `from` is a tracked playlist to copy the tracks of, or a playlist file
+/// (M3U8, XSPF, CSV, JSPF) whose entries are imported and committed.
+pub async fn run(
+    name: &str,
+    provider: ProviderKind,
+    from: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    let source = match from {
+        Some(from) if snapshot::snapshot_path(grit_dir, from).exists() => {
+            let source = snapshot::load(&snapshot::snapshot_path(grit_dir, from))?;
+            if source.provider != provider {
+                bail!(
+                    "{} is a {} playlist; use 'grit convert' to copy it to {}",
+                    from,
+                    source.provider,
+                    provider
+                );
+            }
+            Some(source)
+        }
+        Some(from) if Path::new(from).is_file() => None,
+        Some(from) => bail!("No tracked playlist or playlist file named '{}'", from),
+        None => None,
+    };
+
+    let id = placeholder_id("new", name, grit_dir);
+    let snap = PlaylistSnapshot {
+        id: id.clone(),
+        name: name.to_string(),
+        description: None,
+        tracks: source
+            .as_ref()
+            .map(|s| s.tracks.clone())
+            .unwrap_or_default(),
+        provider: provider.clone(),
+        source: SourceKind::Playlist,
+        snapshot_hash: String::new(),
+        metadata: Some(remotes::unpublished_metadata()),
+    };
+
+    snapshot::save(&snap, &snapshot::snapshot_path(grit_dir, &id))?;
+    let hash = snapshot::compute_hash(&snap)?;
+    snapshot::save_by_hash(&snap, &hash, grit_dir, &id)?;
+
+    let message = match &source {
+        Some(source) => format!("Created from {}", source.id),
+        None => "Created locally".to_string(),
+    };
+    let entry =
+        JournalEntry::new_with_message(Operation::Init, hash, snap.tracks.len(), 0, 0, message);
+    JournalEntry::record(grit_dir, &id, &entry)?;
+
+    clear_staged(grit_dir, &id)?;
+    working_playlist::save(grit_dir, &id)?;
+
+    println!("Created playlist '{}' as {}", name, id);
+
+    if let Some(file) = from.filter(|_| source.is_none()) {
+        transfer::import(file, None, Some(&id), grit_dir).await?;
+        staging::commit(&format!("Import {}", file), Some(&id), grit_dir).await?;
+    }
+
+    println!(
+        "\nIt will be created on {} by the first 'grit push'.",
+        provider
+    );
+
+    Ok(())
+}
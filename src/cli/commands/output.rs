@@ -0,0 +1,289 @@
+//! Structured output for `--json`.
+//!
+//! Every type here is part of the documented JSON schema (see README),
+//! so fields may be added but never renamed or removed.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::provider::{DiffPatch, PlaylistSnapshot, ProviderKind, RemotePlaylist, Track};
+use crate::state::JournalEntry;
+
+pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).context("Failed to serialize JSON output")?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Summary of a tracked playlist, used by `playlists` and `curr`.
+#[derive(Serialize)]
+pub struct PlaylistSummary {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub provider: ProviderKind,
+    pub tracks: usize,
+}
+
+impl PlaylistSummary {
+    pub fn new(id: &str, snapshot: &PlaylistSnapshot) -> Self {
+        Self {
+            id: id.to_string(),
+            name: snapshot.name.clone(),
+            description: snapshot.description.clone(),
+            provider: snapshot.provider.clone(),
+            tracks: snapshot.tracks.len(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct StatusOutput {
+    pub playlist_id: String,
+    pub staged: DiffPatch,
+    /// Changes needed to make the remote match the local snapshot; `null` if
+    /// the remote could not be fetched.
+    pub unpushed: Option<DiffPatch>,
+    pub remote_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct LogEntryOutput<'a> {
+    #[serde(flatten)]
+    pub entry: &'a JournalEntry,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<DiffPatch>,
+    /// Why `changes` couldn't be computed for `--stat`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct LogOutput<'a> {
+    pub playlist_id: String,
+    pub entries: Vec<LogEntryOutput<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct DiffOutput {
+    pub playlist_id: String,
+    pub staged: Option<DiffPatch>,
+    pub remote: Option<DiffPatch>,
+    pub remote_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct FindMatch<'a> {
+    pub index: usize,
+    pub track: &'a Track,
+}
+
+#[derive(Serialize)]
+pub struct FindOutput<'a> {
+    pub playlist_id: String,
+    pub query: String,
+    pub matches: Vec<FindMatch<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct WhoamiOutput {
+    pub provider: ProviderKind,
+    pub token_type: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<u64>,
+    pub expired: bool,
+    /// YouTube quota spent today, from the local ledger
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<QuotaOutput>,
+}
+
+#[derive(Serialize)]
+pub struct QuotaOutput {
+    pub day: chrono::NaiveDate,
+    pub used: u64,
+    /// 0 when there is no limit
+    pub budget: u64,
+}
+
+/// A playlist of the signed-in account, used by `remote ls`.
+#[derive(Serialize)]
+pub struct RemotePlaylistOutput {
+    #[serde(flatten)]
+    pub playlist: RemotePlaylist,
+    pub tracked: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::TrackChange;
+    use crate::state::Operation;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use serde_json::json;
+
+    fn track() -> Track {
+        Track {
+            id: "t1".to_string(),
+            name: "Song".to_string(),
+            artists: vec!["Band".to_string()],
+            duration_ms: 1000,
+            provider: ProviderKind::Spotify,
+            metadata: None,
+        }
+    }
+
+    fn patch() -> DiffPatch {
+        DiffPatch {
+            changes: vec![TrackChange::Added {
+                track: track(),
+                index: 0,
+            }],
+        }
+    }
+
+    fn patch_json() -> serde_json::Value {
+        json!({
+            "changes": [{
+                "Added": {
+                    "track": {
+                        "id": "t1",
+                        "name": "Song",
+                        "artists": ["Band"],
+                        "duration_ms": 1000,
+                        "provider": "Spotify"
+                    },
+                    "index": 0
+                }
+            }]
+        })
+    }
+
+    fn to_json<T: Serialize>(value: &T) -> serde_json::Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_log_json() {
+        let entry = JournalEntry {
+            timestamp: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+            operation: Operation::Commit,
+            snapshot_hash: "abc123".to_string(),
+            added: 1,
+            removed: 0,
+            moved: 0,
+            message: Some("Add song".to_string()),
+            author: Some("sam".to_string()),
+        };
+        let output = LogOutput {
+            playlist_id: "pl1".to_string(),
+            entries: vec![
+                LogEntryOutput {
+                    entry: &entry,
+                    changes: Some(patch()),
+                    error: None,
+                },
+                LogEntryOutput {
+                    entry: &entry,
+                    changes: None,
+                    error: Some("No snapshot found with hash 'abc123'".to_string()),
+                },
+            ],
+        };
+
+        let entry_json = json!({
+            "timestamp": "2024-05-01T12:00:00Z",
+            "operation": "Commit",
+            "snapshot_hash": "abc123",
+            "added": 1,
+            "removed": 0,
+            "moved": 0,
+            "message": "Add song",
+            "author": "sam"
+        });
+        let mut with_changes = entry_json.clone();
+        with_changes["changes"] = patch_json();
+        let mut with_error = entry_json;
+        with_error["error"] = json!("No snapshot found with hash 'abc123'");
+
+        assert_eq!(
+            to_json(&output),
+            json!({ "playlist_id": "pl1", "entries": [with_changes, with_error] })
+        );
+    }
+
+    #[test]
+    fn test_status_and_diff_json() {
+        let status = StatusOutput {
+            playlist_id: "pl1".to_string(),
+            staged: patch(),
+            unpushed: None,
+            remote_error: Some("offline".to_string()),
+        };
+        assert_eq!(
+            to_json(&status),
+            json!({
+                "playlist_id": "pl1",
+                "staged": patch_json(),
+                "unpushed": null,
+                "remote_error": "offline"
+            })
+        );
+
+        let diff = DiffOutput {
+            playlist_id: "pl1".to_string(),
+            staged: None,
+            remote: Some(DiffPatch::default()),
+            remote_error: None,
+        };
+        assert_eq!(
+            to_json(&diff),
+            json!({
+                "playlist_id": "pl1",
+                "staged": null,
+                "remote": { "changes": [] },
+                "remote_error": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_whoami_json() {
+        let mut whoami = WhoamiOutput {
+            provider: ProviderKind::Youtube,
+            token_type: "Bearer".to_string(),
+            scopes: vec!["youtube".to_string()],
+            expires_at: Some(1_700_000_000),
+            expired: false,
+            quota: Some(QuotaOutput {
+                day: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                used: 150,
+                budget: 10_000,
+            }),
+        };
+        assert_eq!(
+            to_json(&whoami),
+            json!({
+                "provider": "Youtube",
+                "token_type": "Bearer",
+                "scopes": ["youtube"],
+                "expires_at": 1_700_000_000,
+                "expired": false,
+                "quota": { "day": "2024-05-01", "used": 150, "budget": 10_000 }
+            })
+        );
+
+        whoami.provider = ProviderKind::Spotify;
+        whoami.expires_at = None;
+        whoami.quota = None;
+        assert_eq!(
+            to_json(&whoami),
+            json!({
+                "provider": "Spotify",
+                "token_type": "Bearer",
+                "scopes": ["youtube"],
+                "expires_at": null,
+                "expired": false
+            })
+        );
+    }
+}
@@ -2,9 +2,10 @@ use anyhow::{bail, Context, Result};
 use crossterm::event::{KeyCode, KeyModifiers};
 use std::path::Path;
 
+use crate::cli::commands::utils::{client_credentials, endpoints};
 use crate::playback::{fetch_audio_url, LyricsFetcher, MpvPlayer, Queue, SpotifyPlayer};
-use crate::provider::ProviderKind;
-use crate::state::{credentials, snapshot, working_playlist};
+use crate::provider::{Provider, ProviderKind, SpotifyProvider, Track};
+use crate::state::{config::Config, credentials, snapshot, working_playlist};
 use crate::tui::{App, PlayerBackend, Tui};
 
 pub async fn run(playlist: Option<&str>, shuffle: bool, grit_dir: &Path) -> Result<()> {
@@ -27,13 +28,49 @@ pub async fn run(playlist: Option<&str>, shuffle: bool, grit_dir: &Path) -> Resu
         .unwrap_or(0)
         .min(snap.tracks.len().saturating_sub(1));
 
-    match snap.provider {
-        ProviderKind::Spotify => {
-            play_spotify(&snap, shuffle, grit_dir, &snapshot_path, start_index).await
+    let config = Config::load(grit_dir, Some(playlist_id))?;
+    let backend = config
+        .get_str("player.backend")
+        .unwrap_or_else(|| "auto".to_string());
+
+    match (backend.as_str(), &snap.provider) {
+        ("auto" | "spotify", ProviderKind::Spotify) => {
+            play_spotify(
+                &snap,
+                shuffle,
+                grit_dir,
+                &config,
+                &snapshot_path,
+                start_index,
+            )
+            .await
         }
-        ProviderKind::Youtube => {
-            play_mpv(&snap, shuffle, grit_dir, &snapshot_path, start_index).await
+        (
+            "auto" | "mpv",
+            ProviderKind::Youtube
+            | ProviderKind::Local
+            | ProviderKind::Subsonic
+            | ProviderKind::Plugin(_),
+        ) => {
+            play_mpv(
+                &snap,
+                shuffle,
+                grit_dir,
+                &config,
+                &snapshot_path,
+                start_index,
+            )
+            .await
         }
+        ("spotify" | "mpv", provider) => bail!(
+            "player.backend '{}' cannot play {} playlists (set it to 'auto')",
+            backend,
+            provider
+        ),
+        _ => bail!(
+            "Unknown player.backend '{}' (expected auto, spotify or mpv)",
+            backend
+        ),
     }
 }
 
@@ -41,17 +78,21 @@ async fn play_spotify(
     snap: &crate::provider::PlaylistSnapshot,
     shuffle: bool,
     grit_dir: &Path,
+    config: &Config,
     snapshot_path: &Path,
     start_index: usize,
 ) -> Result<()> {
-    let token = credentials::load(grit_dir, ProviderKind::Spotify)?
+    let token = credentials::load(grit_dir, &ProviderKind::Spotify)?
         .context("No Spotify credentials. Run 'grit auth spotify' first.")?;
 
-    let client_id = std::env::var("SPOTIFY_CLIENT_ID").context("SPOTIFY_CLIENT_ID not set")?;
-    let client_secret =
-        std::env::var("SPOTIFY_CLIENT_SECRET").context("SPOTIFY_CLIENT_SECRET not set")?;
+    let (client_id, client_secret) = client_credentials(&ProviderKind::Spotify, config)?;
 
-    let mut player = SpotifyPlayer::new(token, client_id, client_secret, grit_dir);
+    let mut player =
+        SpotifyPlayer::new(token, client_id, client_secret, grit_dir).with_endpoints(endpoints(
+            &ProviderKind::Spotify,
+            config,
+            SpotifyProvider::default_endpoints(),
+        ));
     player.select_device().await?;
 
     let uris: Vec<String> = snap
@@ -79,7 +120,7 @@ async fn play_spotify(
         .and_then(|m| m.modified())
         .ok();
 
-    let mut lyrics_fetcher = LyricsFetcher::new();
+    let mut lyrics_fetcher = lyrics_fetcher(config);
 
     loop {
         if let Some(lyrics) = lyrics_fetcher.try_recv() {
@@ -221,11 +262,7 @@ async fn play_spotify(
             }
             match key.code {
                 KeyCode::Char('q') => break,
-                KeyCode::Char('/') => {
-                    if !app.show_lyrics {
-                        app.start_search();
-                    }
-                }
+                KeyCode::Char('/') if !app.show_lyrics => app.start_search(),
                 KeyCode::Char('g') => app.start_seeking(),
                 KeyCode::Char(' ') => {
                     app.is_paused = !app.is_paused;
@@ -363,12 +400,13 @@ async fn play_mpv(
     snap: &crate::provider::PlaylistSnapshot,
     shuffle: bool,
     grit_dir: &Path,
+    config: &Config,
     snapshot_path: &Path,
     start_index: usize,
 ) -> Result<()> {
     use crate::cli::commands::utils::create_provider;
 
-    let provider = create_provider(snap.provider, grit_dir)?;
+    let provider = create_provider(&snap.provider, grit_dir)?;
     let mut queue = Queue::new(snap.tracks.clone());
 
     if shuffle {
@@ -395,11 +433,10 @@ async fn play_mpv(
     let mut tui = Tui::new()?;
     tui.draw(&app)?;
 
-    let mut lyrics_fetcher = LyricsFetcher::new();
+    let mut lyrics_fetcher = lyrics_fetcher(config);
 
     if let Some(track) = queue.current_track().cloned() {
-        let yt_url = provider.playable_url(&track).await?;
-        match fetch_audio_url(&yt_url).await {
+        match audio_url(provider.as_ref(), &track).await {
             Ok(audio_url) => {
                 if let Err(e) = player.load(&audio_url).await {
                     app.set_error(format!("Failed to load: {}", e));
@@ -466,16 +503,13 @@ async fn play_mpv(
                                 lyrics_fetcher.reset();
                                 queue.jump_to(idx);
                                 tui.draw(&app)?;
-                                match provider.playable_url(&track).await {
-                                    Ok(yt_url) => match fetch_audio_url(&yt_url).await {
-                                        Ok(audio_url) => {
-                                            while player.try_recv_event().is_some() {}
-                                            if let Err(e) = player.load(&audio_url).await {
-                                                app.set_error(e.to_string());
-                                            }
+                                match audio_url(provider.as_ref(), &track).await {
+                                    Ok(audio_url) => {
+                                        while player.try_recv_event().is_some() {}
+                                        if let Err(e) = player.load(&audio_url).await {
+                                            app.set_error(e.to_string());
                                         }
-                                        Err(e) => app.set_error(e.to_string()),
-                                    },
+                                    }
                                     Err(e) => app.set_error(e.to_string()),
                                 }
                                 app.loading = false;
@@ -530,11 +564,7 @@ async fn play_mpv(
             }
             match key.code {
                 KeyCode::Char('q') => break,
-                KeyCode::Char('/') => {
-                    if !app.show_lyrics {
-                        app.start_search();
-                    }
-                }
+                KeyCode::Char('/') if !app.show_lyrics => app.start_search(),
                 KeyCode::Char('g') => app.start_seeking(),
                 KeyCode::Char(' ') => {
                     app.is_paused = !app.is_paused;
@@ -571,16 +601,13 @@ async fn play_mpv(
                         app.reset_lyrics_scroll();
                         lyrics_fetcher.reset();
                         tui.draw(&app)?;
-                        match provider.playable_url(&track).await {
-                            Ok(yt_url) => match fetch_audio_url(&yt_url).await {
-                                Ok(audio_url) => {
-                                    while player.try_recv_event().is_some() {}
-                                    if let Err(e) = player.load(&audio_url).await {
-                                        app.set_error(e.to_string());
-                                    }
+                        match audio_url(provider.as_ref(), &track).await {
+                            Ok(audio_url) => {
+                                while player.try_recv_event().is_some() {}
+                                if let Err(e) = player.load(&audio_url).await {
+                                    app.set_error(e.to_string());
                                 }
-                                Err(e) => app.set_error(e.to_string()),
-                            },
+                            }
                             Err(e) => app.set_error(e.to_string()),
                         }
                         app.loading = false;
@@ -600,16 +627,13 @@ async fn play_mpv(
                         app.reset_lyrics_scroll();
                         lyrics_fetcher.reset();
                         tui.draw(&app)?;
-                        match provider.playable_url(&track).await {
-                            Ok(yt_url) => match fetch_audio_url(&yt_url).await {
-                                Ok(audio_url) => {
-                                    while player.try_recv_event().is_some() {}
-                                    if let Err(e) = player.load(&audio_url).await {
-                                        app.set_error(e.to_string());
-                                    }
+                        match audio_url(provider.as_ref(), &track).await {
+                            Ok(audio_url) => {
+                                while player.try_recv_event().is_some() {}
+                                if let Err(e) = player.load(&audio_url).await {
+                                    app.set_error(e.to_string());
                                 }
-                                Err(e) => app.set_error(e.to_string()),
-                            },
+                            }
                             Err(e) => app.set_error(e.to_string()),
                         }
                         app.loading = false;
@@ -682,16 +706,13 @@ async fn play_mpv(
                             lyrics_fetcher.reset();
                             queue.jump_to(idx);
                             tui.draw(&app)?;
-                            match provider.playable_url(&track).await {
-                                Ok(yt_url) => match fetch_audio_url(&yt_url).await {
-                                    Ok(audio_url) => {
-                                        while player.try_recv_event().is_some() {}
-                                        if let Err(e) = player.load(&audio_url).await {
-                                            app.set_error(e.to_string());
-                                        }
+                            match audio_url(provider.as_ref(), &track).await {
+                                Ok(audio_url) => {
+                                    while player.try_recv_event().is_some() {}
+                                    if let Err(e) = player.load(&audio_url).await {
+                                        app.set_error(e.to_string());
                                     }
-                                    Err(e) => app.set_error(e.to_string()),
-                                },
+                                }
                                 Err(e) => app.set_error(e.to_string()),
                             }
                             app.loading = false;
@@ -741,18 +762,14 @@ async fn play_mpv(
                     lyrics_fetcher.reset();
                     tui.draw(&app)?;
 
-                    if let Ok(yt_url) = provider.playable_url(&track).await {
-                        match fetch_audio_url(&yt_url).await {
-                            Ok(audio_url) => {
-                                while player.try_recv_event().is_some() {}
-                                if let Err(e) = player.load(&audio_url).await {
-                                    app.set_error(e.to_string());
-                                }
+                    match audio_url(provider.as_ref(), &track).await {
+                        Ok(audio_url) => {
+                            while player.try_recv_event().is_some() {}
+                            if let Err(e) = player.load(&audio_url).await {
+                                app.set_error(e.to_string());
                             }
-                            Err(e) => app.set_error(e.to_string()),
                         }
-                    } else {
-                        app.set_error("Failed to get playable URL".to_string());
+                        Err(e) => app.set_error(e.to_string()),
                     }
                     app.loading = false;
                     skip_position = 5;
@@ -767,3 +784,22 @@ async fn play_mpv(
     let _ = working_playlist::save_last_track(grit_dir, app.current_index);
     Ok(())
 }
+
+/// Lyrics come from lrclib.net unless `lyrics.url` points elsewhere.
+fn lyrics_fetcher(config: &Config) -> LyricsFetcher {
+    match config.get_str("lyrics.url") {
+        Some(url) => LyricsFetcher::new().with_base_url(url),
+        None => LyricsFetcher::new(),
+    }
+}
+
+/// What mpv should load for `track`: local files play directly, anything
+/// else is resolved to a stream URL with yt-dlp.
+async fn audio_url(provider: &dyn Provider, track: &Track) -> Result<String> {
+    let url = provider.playable_url(track).await?;
+    // Only YouTube gives page URLs that need resolving to an audio stream
+    if track.provider != ProviderKind::Youtube {
+        return Ok(url);
+    }
+    fetch_audio_url(&url).await
+}
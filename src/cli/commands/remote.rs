@@ -0,0 +1,406 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    cli::commands::{
+        init::{detect_provider, resolve_id},
+        output::{print_json, RemotePlaylistOutput},
+        utils::create_provider,
+    },
+    matching::{self, Candidate, Confidence, MatchCache},
+    provider::{PlaylistSnapshot, Provider, ProviderKind, RemotePlaylist, SourceKind, Track},
+    state::{
+        remotes::{self, Remote, ORIGIN},
+        snapshot,
+    },
+};
+
+pub async fn add(
+    name: &str,
+    url: &str,
+    provider: Option<ProviderKind>,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+
+    let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
+    if !snapshot_path.exists() {
+        bail!("Playlist not initialized. Run 'grit init' first.");
+    }
+
+    if name == ORIGIN {
+        bail!(
+            "'{}' is reserved for the playlist grit was initialized from",
+            ORIGIN
+        );
+    }
+
+    let mut existing = remotes::load(grit_dir, playlist_id)?;
+    if existing.iter().any(|r| r.name == name) {
+        bail!("Remote '{}' already exists", name);
+    }
+
+    let provider_kind = provider
+        .or_else(|| detect_provider(url))
+        .context("Could not detect the provider from the URL (use --provider)")?;
+    let remote_id = resolve_id(&provider_kind, url, grit_dir)?;
+
+    let provider = create_provider(&provider_kind, grit_dir)?;
+
+    println!("Verifying remote playlist {}...", remote_id);
+    let remote_snapshot = provider.fetch(&remote_id).await?;
+
+    existing.push(Remote {
+        name: name.to_string(),
+        provider: provider_kind.clone(),
+        playlist_id: remote_id.clone(),
+    });
+    remotes::save(grit_dir, playlist_id, &existing)?;
+
+    println!("\nAdded remote '{}'", name);
+    println!("  Provider: {}", provider_kind);
+    println!("  Playlist: {} ({})", remote_snapshot.name, remote_id);
+    println!(
+        "\nUse 'grit push {}' or 'grit push --all' to sync it.",
+        name
+    );
+
+    Ok(())
+}
+
+pub async fn remove(name: &str, playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+
+    let mut existing = remotes::load(grit_dir, playlist_id)?;
+    let before = existing.len();
+    existing.retain(|r| r.name != name);
+
+    if existing.len() == before {
+        bail!("No remote named '{}'", name);
+    }
+
+    remotes::save(grit_dir, playlist_id, &existing)?;
+    println!("Removed remote '{}'", name);
+
+    Ok(())
+}
+
+pub async fn show(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+
+    let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
+    if !snapshot_path.exists() {
+        bail!("Playlist not initialized. Run 'grit init' first.");
+    }
+
+    let snap = snapshot::load(&snapshot_path)?;
+
+    println!("{}\t{}\t{}", ORIGIN, snap.provider, snap.id);
+    for remote in remotes::load(grit_dir, playlist_id)? {
+        println!(
+            "{}\t{}\t{}",
+            remote.name, remote.provider, remote.playlist_id
+        );
+    }
+
+    Ok(())
+}
+
+/// List the playlists of the signed-in account on `provider`, marking the
+/// ones that are already tracked.
+pub async fn ls(
+    provider: ProviderKind,
+    owned: bool,
+    filter: Option<&str>,
+    grit_dir: &Path,
+    json: bool,
+) -> Result<()> {
+    let provider = create_provider(&provider, grit_dir)?;
+    let playlists: Vec<RemotePlaylistOutput> = account_playlists(provider.as_ref(), owned, filter)
+        .await?
+        .into_iter()
+        .map(|playlist| RemotePlaylistOutput {
+            tracked: snapshot::snapshot_path(grit_dir, &playlist.id).exists(),
+            playlist,
+        })
+        .collect();
+
+    if json {
+        return print_json(&playlists);
+    }
+
+    if playlists.is_empty() {
+        println!("No playlists found.");
+        return Ok(());
+    }
+
+    for p in &playlists {
+        let tracks = p
+            .playlist
+            .tracks
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        println!(
+            "{} {}\t{:>5}\t{}\t{}",
+            if p.tracked { "*" } else { " " },
+            p.playlist.id,
+            tracks,
+            p.playlist.owner.as_deref().unwrap_or("-"),
+            p.playlist.name
+        );
+    }
+    println!("\n* tracked; 'grit clone --all' tracks the rest");
+
+    Ok(())
+}
+
+/// Playlists of the signed-in account, keeping only the ones it can change
+/// if `owned` and those whose name contains `filter` (ignoring case).
+pub async fn account_playlists(
+    provider: &dyn Provider,
+    owned: bool,
+    filter: Option<&str>,
+) -> Result<Vec<RemotePlaylist>> {
+    let filter = filter.map(str::to_lowercase);
+    Ok(provider
+        .list_playlists()
+        .await?
+        .into_iter()
+        .filter(|p| !owned || p.owned)
+        .filter(|p| {
+            filter
+                .as_deref()
+                .is_none_or(|f| p.name.to_lowercase().contains(f))
+        })
+        .collect())
+}
+
+/// Build the snapshot a mirror remote should have: the local track list with
+/// each track replaced by its counterpart on the mirror's provider.
+///
+/// Tracks without a recorded counterpart are matched on the mirror's
+/// provider, and the result is stored in the local track's metadata so later
+/// pushes map it the same way. Returns the desired snapshot and whether
+/// `local` was updated with new mappings.
+///
+/// A dry run neither searches nor asks: only sure matches already in the
+/// match cache are used, and nothing is written.
+pub async fn mirror_snapshot(
+    local: &mut PlaylistSnapshot,
+    remote: &Remote,
+    provider: &dyn Provider,
+    grit_dir: &Path,
+    dry_run: bool,
+) -> Result<(PlaylistSnapshot, bool)> {
+    let mut cache = MatchCache::load(grit_dir)?;
+    let mut tracks = Vec::new();
+    let mut updated = false;
+    let mut unsearched = 0;
+
+    for track in local.tracks.iter_mut() {
+        if let Some(mirror) = remotes::mirrored_track(track, &remote.provider) {
+            tracks.push(mirror);
+            continue;
+        }
+
+        let chosen = if dry_run {
+            let Some(cached) = cache.get(track, &remote.provider) else {
+                println!(
+                    "  Would search {} for {} - {}",
+                    remote.provider,
+                    track.name,
+                    track.artists.join(", ")
+                );
+                unsearched += 1;
+                continue;
+            };
+            cached
+                .first()
+                .filter(|best| best.confidence() == Confidence::High)
+                .map(|best| best.track.clone())
+        } else {
+            let candidates =
+                matching::find_matches(track, &remote.provider, provider, &mut cache).await?;
+            pick_match(track, &candidates)?
+        };
+
+        match chosen {
+            Some(found) => {
+                println!(
+                    "  Mapped: {} - {} -> {} ({})",
+                    track.name,
+                    track.artists.join(", "),
+                    found.name,
+                    found.id
+                );
+                remotes::record_mirror(track, &found)?;
+                tracks.push(found);
+                updated = true;
+            }
+            None => {
+                println!(
+                    "  Skipping {} - {} (no match on {})",
+                    track.name,
+                    track.artists.join(", "),
+                    remote.provider
+                );
+            }
+        }
+    }
+
+    if dry_run {
+        if unsearched > 0 {
+            println!(
+                "  {} track(s) would be searched for; the changes below leave them out",
+                unsearched
+            );
+        }
+    } else {
+        cache.save()?;
+    }
+
+    let desired = PlaylistSnapshot {
+        id: remote.playlist_id.clone(),
+        name: local.name.clone(),
+        description: local.description.clone(),
+        tracks,
+        provider: remote.provider.clone(),
+        source: SourceKind::Playlist,
+        snapshot_hash: String::new(),
+        metadata: None,
+    };
+
+    Ok((desired, updated))
+}
+
+/// Choose the counterpart of `track` from ranked `candidates`.
+///
+/// A high-confidence best candidate is taken as is. Low-confidence
+/// candidates are listed and the user picks one or skips the track; when
+/// stdin isn't a terminal (a script or cron job) the track is skipped.
+pub fn pick_match(track: &Track, candidates: &[Candidate]) -> Result<Option<Track>> {
+    let plausible: Vec<&Candidate> = candidates
+        .iter()
+        .filter(|c| c.confidence() != Confidence::None)
+        .collect();
+
+    match plausible.first() {
+        None => return Ok(None),
+        Some(best) if best.confidence() == Confidence::High => return Ok(Some(best.track.clone())),
+        Some(_) => {}
+    }
+
+    if !io::stdin().is_terminal() {
+        println!(
+            "  Unsure about {} - {}; run in a terminal to choose a match",
+            track.name,
+            track.artists.join(", ")
+        );
+        return Ok(None);
+    }
+
+    println!(
+        "\n  Unsure about {} - {}:",
+        track.name,
+        track.artists.join(", ")
+    );
+    for (i, candidate) in plausible.iter().enumerate() {
+        println!(
+            "    {}. {} - {} [{}] ({:.0}%)",
+            i + 1,
+            candidate.track.name,
+            candidate.track.artists.join(", "),
+            format_duration(candidate.track.duration_ms),
+            candidate.score * 100.0
+        );
+    }
+
+    loop {
+        print!("  Use [1-{}] or 's' to skip: ", plausible.len());
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+        let input = input.trim();
+
+        if input.eq_ignore_ascii_case("s") || input.is_empty() {
+            return Ok(None);
+        }
+        match input.parse::<usize>() {
+            Ok(n) if (1..=plausible.len()).contains(&n) => {
+                return Ok(Some(plausible[n - 1].track.clone()))
+            }
+            _ => println!("  Invalid choice"),
+        }
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::FakeProvider;
+    use tempfile::TempDir;
+
+    fn track(id: &str, name: &str, artist: &str, provider: ProviderKind) -> Track {
+        Track {
+            id: id.to_string(),
+            name: name.to_string(),
+            artists: vec![artist.to_string()],
+            duration_ms: 200_000,
+            provider,
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mirror_snapshot_maps_tracks() {
+        let dir = TempDir::new().unwrap();
+        let youtube = FakeProvider::new(ProviderKind::Youtube).with_catalog(vec![track(
+            "yt1",
+            "Artist - Song (Official Video)",
+            "ArtistVEVO",
+            ProviderKind::Youtube,
+        )]);
+
+        let mut local = PlaylistSnapshot {
+            tracks: vec![
+                track("sp1", "Song", "Artist", ProviderKind::Spotify),
+                track("sp2", "Unreleased", "Nobody", ProviderKind::Spotify),
+            ],
+            ..PlaylistSnapshot::fixture(ProviderKind::Spotify, &[])
+        };
+        let remote = Remote {
+            name: "yt".to_string(),
+            provider: ProviderKind::Youtube,
+            playlist_id: "ytpl".to_string(),
+        };
+
+        let (desired, updated) = mirror_snapshot(&mut local, &remote, &youtube, dir.path(), false)
+            .await
+            .unwrap();
+
+        assert!(updated);
+        assert_eq!(desired.id, "ytpl");
+        assert_eq!(desired.tracks.len(), 1);
+        assert_eq!(desired.tracks[0].id, "yt1");
+        assert_eq!(
+            remotes::mirrored_track(&local.tracks[0], &ProviderKind::Youtube)
+                .unwrap()
+                .id,
+            "yt1"
+        );
+        assert!(remotes::mirrored_track(&local.tracks[1], &ProviderKind::Youtube).is_none());
+    }
+}
@@ -3,14 +3,19 @@ use std::io::{self, Write};
 use std::path::Path;
 
 use crate::{
-    cli::commands::utils::create_provider,
-    provider::{ProviderKind, TrackChange},
+    cli::commands::{
+        output::{print_json, StatusOutput},
+        utils::{create_provider, require_editable},
+    },
+    provider::{ProviderKind, SourceKind, TrackChange},
     state::{
-        apply_patch, clear_staged, load_staged, snapshot, stage_change, JournalEntry, Operation,
+        apply_patch, clear_staged, config::Config, load_staged, snapshot, stage_change,
+        JournalEntry, Operation,
     },
+    utils::hooks::{self, Hook},
 };
 
-pub async fn status(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+pub async fn status(playlist: Option<&str>, grit_dir: &Path, json: bool) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist)")?;
 
     let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
@@ -21,6 +26,26 @@ pub async fn status(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
     let local_snapshot = snapshot::load(&snapshot_path)?;
     let staged_patch = load_staged(grit_dir, playlist_id)?;
 
+    if json {
+        let provider = create_provider(&local_snapshot.provider, grit_dir)?;
+        let (unpushed, remote_error) = match provider
+            .fetch_source(local_snapshot.source, playlist_id)
+            .await
+        {
+            std::result::Result::Ok(remote_snapshot) => (
+                Some(crate::state::diff(&remote_snapshot, &local_snapshot)),
+                None,
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        return print_json(&StatusOutput {
+            playlist_id: playlist_id.to_string(),
+            staged: staged_patch,
+            unpushed,
+            remote_error,
+        });
+    }
+
     // Display staged changes
     println!("\n[Staged Changes]");
     if staged_patch.changes.is_empty() {
@@ -70,9 +95,12 @@ pub async fn status(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
 
     // Compare local vs remote
     println!("\n[Local vs Remote]");
-    let provider = create_provider(local_snapshot.provider, grit_dir)?;
+    let provider = create_provider(&local_snapshot.provider, grit_dir)?;
 
-    match provider.fetch(playlist_id).await {
+    match provider
+        .fetch_source(local_snapshot.source, playlist_id)
+        .await
+    {
         std::result::Result::Ok(remote_snapshot) => {
             use crate::state::diff;
             let local_vs_remote = diff(&remote_snapshot, &local_snapshot);
@@ -119,12 +147,21 @@ pub async fn search(
     grit_dir: &Path,
     add_mode: bool,
     playlist: Option<&str>,
+    json: bool,
 ) -> Result<()> {
+    if json && add_mode {
+        bail!("--add is interactive and cannot be combined with --json");
+    }
+
     let provider_kind = provider.context("Provider required for search (use --provider)")?;
-    let provider_instance = create_provider(provider_kind, grit_dir)?;
+    let provider_instance = create_provider(&provider_kind, grit_dir)?;
 
     let tracks = provider_instance.search_by_query(query).await?;
 
+    if json {
+        return print_json(&tracks);
+    }
+
     if tracks.is_empty() {
         println!("No tracks found for '{}'", query);
         return Ok(());
@@ -147,11 +184,14 @@ pub async fn search(
 
     println!("\nSearch results for '{}':\n", query);
 
-    const PAGE_SIZE: usize = 5;
+    let page_size = Config::load(grit_dir, None)?
+        .get_u64("search.page_size")?
+        .unwrap_or(5)
+        .max(1) as usize;
     let mut start = 0;
 
     loop {
-        let end = (start + PAGE_SIZE).min(tracks.len());
+        let end = (start + page_size).min(tracks.len());
         let page_tracks = &tracks[start..end];
 
         for (i, track) in page_tracks.iter().enumerate() {
@@ -279,7 +319,8 @@ pub async fn add(track_id: &str, playlist: Option<&str>, grit_dir: &Path) -> Res
     }
 
     let snapshot = snapshot::load(&snapshot_path)?;
-    let provider = create_provider(snapshot.provider, grit_dir)?;
+    require_editable(&snapshot)?;
+    let provider = create_provider(&snapshot.provider, grit_dir)?;
 
     let track = provider.fetch_track(track_id).await?;
 
@@ -292,7 +333,12 @@ pub async fn add(track_id: &str, playlist: Option<&str>, grit_dir: &Path) -> Res
         );
     }
 
-    let index = snapshot.tracks.len();
+    // Newly liked songs go on top
+    let index = if snapshot.source == SourceKind::Liked {
+        0
+    } else {
+        snapshot.tracks.len()
+    };
 
     let change = TrackChange::Added {
         track: track.clone(),
@@ -322,6 +368,7 @@ pub async fn remove(track_id: &str, playlist: Option<&str>, grit_dir: &Path) ->
     }
 
     let snapshot = snapshot::load(&snapshot_path)?;
+    require_editable(&snapshot)?;
 
     let (index, track) = snapshot
         .tracks
@@ -363,6 +410,10 @@ pub async fn move_track(
     }
 
     let snapshot = snapshot::load(&snapshot_path)?;
+    require_editable(&snapshot)?;
+    if !snapshot.source.can_reorder() {
+        bail!("Liked songs are ordered by when they were liked and can't be moved");
+    }
 
     let (from_index, track) = snapshot
         .tracks
@@ -435,6 +486,15 @@ pub async fn commit(message: &str, playlist: Option<&str>, grit_dir: &Path) -> R
         return Ok(());
     }
 
+    hooks::run(grit_dir, Hook::PreCommit, playlist_id, &patch, None)?;
+    hooks::run(
+        grit_dir,
+        Hook::CommitMsg,
+        playlist_id,
+        &patch,
+        Some(message),
+    )?;
+
     let mut snapshot_copy = snapshot::load(&snapshot_path)?;
 
     let mut added = 0;
@@ -458,7 +518,6 @@ pub async fn commit(message: &str, playlist: Option<&str>, grit_dir: &Path) -> R
 
     snapshot::save(&snapshot_copy, &snapshot_path)?;
 
-    let journal_path = JournalEntry::journal_path(grit_dir, playlist_id);
     let entry = JournalEntry::new_with_message(
         Operation::Commit,
         hash.clone(),
@@ -467,7 +526,7 @@ pub async fn commit(message: &str, playlist: Option<&str>, grit_dir: &Path) -> R
         moved,
         message.to_string(),
     );
-    JournalEntry::append(&journal_path, &entry)?;
+    JournalEntry::record(grit_dir, playlist_id, &entry)?;
 
     clear_staged(grit_dir, playlist_id)?;
 
@@ -0,0 +1,423 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    cli::commands::{remote::pick_match, utils::create_provider},
+    formats::{csv::Column, Entry, Format, PlaylistFile},
+    matching::{self, Confidence},
+    provider::{PlaylistSnapshot, Provider, ProviderKind, Track},
+    state::{diff_occurrences, journal, load_staged, quota, save_staged, snapshot},
+};
+
+/// Write a playlist (or one of its past revisions) in another player's format.
+pub async fn export(
+    playlist: Option<&str>,
+    format: Option<Format>,
+    rev: Option<&str>,
+    output: Option<&str>,
+    columns: &[Column],
+    grit_dir: &Path,
+) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+
+    let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
+    if !snapshot_path.exists() {
+        bail!("Playlist not initialized. Run 'grit init' first.");
+    }
+
+    let snap = match rev {
+        Some(hash) => snapshot::load_by_hash(hash, grit_dir, playlist_id)?,
+        None => snapshot::load(&snapshot_path)?,
+    };
+
+    let format = format
+        .or_else(|| output.and_then(|o| Format::from_path(Path::new(o))))
+        .unwrap_or(Format::M3u8);
+
+    // Spotify and YouTube links are built from the ID, so those need no login
+    let provider = match snap.provider {
+        ProviderKind::Spotify | ProviderKind::Youtube => None,
+        _ => Some(create_provider(&snap.provider, grit_dir)?),
+    };
+    let mut document = to_playlist_file(&snap, provider.as_deref()).await;
+
+    let origins = journal::track_origins(grit_dir, playlist_id)?;
+    for entry in &mut document.entries {
+        if let Some(origin) = entry.id.as_ref().and_then(|id| origins.get(id)) {
+            entry.added_by = origin.author.clone();
+            entry.added_at = Some(origin.timestamp.to_rfc3339());
+        }
+    }
+
+    let rendered = format.render(&document, columns)?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, rendered).with_context(|| format!("Failed to write {}", path))?;
+            eprintln!("Exported {} tracks to {}", snap.tracks.len(), path);
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+async fn to_playlist_file(
+    snap: &PlaylistSnapshot,
+    provider: Option<&dyn Provider>,
+) -> PlaylistFile {
+    let mut entries = Vec::with_capacity(snap.tracks.len());
+
+    for track in &snap.tracks {
+        let location = match (track_link(track), provider) {
+            (Some(link), _) => link,
+            (None, Some(provider)) => match provider.playable_url(track).await {
+                Ok(url) => url,
+                Err(e) => {
+                    eprintln!("Warning: {} ({}), using its ID", e, track.name);
+                    track.id.clone()
+                }
+            },
+            (None, None) => track.id.clone(),
+        };
+        entries.push(Entry::from_track(track, Some(location)));
+    }
+
+    PlaylistFile {
+        title: Some(snap.name.clone()),
+        entries,
+    }
+}
+
+/// Stage the changes that turn a tracked playlist into the contents of a
+/// playlist file.
+pub async fn import(
+    file: &str,
+    format: Option<Format>,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+
+    let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
+    if !snapshot_path.exists() {
+        bail!("Playlist not initialized. Run 'grit init' first.");
+    }
+
+    let staged = load_staged(grit_dir, playlist_id)?;
+    if !staged.changes.is_empty() {
+        bail!(
+            "You have {} uncommitted staged change(s). Commit or reset before importing.",
+            staged.changes.len()
+        );
+    }
+
+    let path = Path::new(file);
+    let format = format
+        .or_else(|| Format::from_path(path))
+        .context("Unknown playlist format (use --format)")?;
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", file))?;
+    let document = format.parse(&content)?;
+
+    let current = snapshot::load(&snapshot_path)?;
+    let provider = create_provider(&current.provider, grit_dir)?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    if current.provider == ProviderKind::Youtube {
+        let cost = resolve_cost(&document.entries, &current, base_dir);
+        quota::check_budget(grit_dir, "This import", cost, false)?;
+    }
+
+    println!(
+        "Resolving {} entries on {}...",
+        document.entries.len(),
+        current.provider
+    );
+
+    let mut desired = current.clone();
+    desired.tracks.clear();
+    let mut unresolved = Vec::new();
+
+    for entry in &document.entries {
+        match resolve_entry(entry, &current, provider.as_ref(), base_dir).await? {
+            Some(track) => desired.tracks.push(track),
+            None => unresolved.push(describe(entry)),
+        }
+    }
+
+    // Counted per copy, so tracks the file lists twice stay twice
+    let patch = diff_occurrences(&current, &desired);
+    save_staged(grit_dir, playlist_id, &patch)?;
+
+    println!("\nStaged {} change(s) from {}", patch.changes.len(), file);
+    if !unresolved.is_empty() {
+        println!("\nCould not resolve {} entries:", unresolved.len());
+        for entry in &unresolved {
+            println!("  {}", entry);
+        }
+    }
+    println!("\nReview with 'grit status', then 'grit commit -m <message>'.");
+
+    Ok(())
+}
+
+/// YouTube quota `resolve_entry` spends on `entries`: a read for each ID not
+/// in the playlist yet and a search for each entry found by its title.
+fn resolve_cost(entries: &[Entry], current: &PlaylistSnapshot, base_dir: &Path) -> u64 {
+    entries
+        .iter()
+        .map(|entry| match entry_id(entry, current, base_dir) {
+            Some(id) if current.tracks.iter().any(|t| t.id == id) => 0,
+            Some(_) => quota::READ,
+            None if entry.title.is_some() => quota::SEARCH,
+            None => 0,
+        })
+        .sum()
+}
+
+/// The ID an entry gives for a track on the playlist's provider, directly or
+/// through its location.
+fn entry_id(entry: &Entry, current: &PlaylistSnapshot, base_dir: &Path) -> Option<String> {
+    entry.id.clone().or_else(|| {
+        entry
+            .location
+            .as_deref()
+            .and_then(|location| track_id_from_location(location, &current.provider, base_dir))
+    })
+}
+
+/// Find the provider track an entry refers to: by its ID or location when
+/// that is a link to (or file on) the provider, otherwise by searching its
+/// title.
+pub async fn resolve_entry(
+    entry: &Entry,
+    current: &PlaylistSnapshot,
+    provider: &dyn Provider,
+    base_dir: &Path,
+) -> Result<Option<Track>> {
+    if let Some(id) = entry_id(entry, current, base_dir) {
+        if let Some(track) = current.tracks.iter().find(|t| t.id == id) {
+            return Ok(Some(track.clone()));
+        }
+        match provider.fetch_track(&id).await {
+            Ok(track) => return Ok(Some(track)),
+            Err(e) => eprintln!("  Warning: {} ({})", e, id),
+        }
+    }
+
+    let Some(title) = &entry.title else {
+        return Ok(None);
+    };
+
+    // Score search results against what the file says about the entry
+    let wanted = Track {
+        id: String::new(),
+        name: title.clone(),
+        artists: entry.artist.iter().cloned().collect(),
+        duration_ms: entry.duration_ms.unwrap_or(0),
+        provider: current.provider.clone(),
+        metadata: None,
+    };
+    let query = format!("{} {}", entry.artist.as_deref().unwrap_or_default(), title);
+    let candidates = matching::rank(&wanted, provider.search_by_query(query.trim()).await?);
+
+    match candidates.first().map(|c| c.confidence()) {
+        None | Some(Confidence::None) => Ok(None),
+        _ => pick_match(&wanted, &candidates),
+    }
+}
+
+/// A link to a Spotify or YouTube track, the reverse of
+/// `track_id_from_location`. Other providers are asked for a playable URL.
+fn track_link(track: &Track) -> Option<String> {
+    match track.provider {
+        ProviderKind::Spotify => Some(format!("spotify:track:{}", track.id)),
+        ProviderKind::Youtube => Some(format!("https://www.youtube.com/watch?v={}", track.id)),
+        _ => None,
+    }
+}
+
+/// The track ID a location points to on `provider`, if it is one of its
+/// links (or, for the local provider, a file path).
+pub fn track_id_from_location(
+    location: &str,
+    provider: &ProviderKind,
+    base_dir: &Path,
+) -> Option<String> {
+    match provider {
+        ProviderKind::Spotify => {
+            if let Some(id) = location.strip_prefix("spotify:track:") {
+                return Some(id.to_string());
+            }
+            let id = location.split("open.spotify.com/track/").nth(1)?;
+            Some(id.split(['?', '#']).next()?.to_string())
+        }
+        ProviderKind::Youtube => {
+            if let Some(rest) = location.split("youtu.be/").nth(1) {
+                return Some(rest.split(['?', '&', '#']).next()?.to_string());
+            }
+            if !location.contains("youtube.com/") {
+                return None;
+            }
+            let query = location.split_once('?')?.1;
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("v="))
+                .map(str::to_string)
+        }
+        ProviderKind::Local => {
+            let is_other_link = location.contains("://") || location.starts_with("spotify:");
+            if is_other_link && !location.starts_with("file://") {
+                return None;
+            }
+            let path = match location.strip_prefix("file://") {
+                Some(encoded) => urlencoding::decode(encoded).ok()?.into_owned(),
+                None => location.to_string(),
+            };
+            let path = Path::new(&path);
+            let path = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                base_dir.join(path)
+            };
+            Some(path.to_string_lossy().into_owned())
+        }
+        // Subsonic and plugin tracks are found by ID (CSV, JSPF) or by searching
+        ProviderKind::Subsonic | ProviderKind::Plugin(_) => None,
+    }
+}
+
+fn describe(entry: &Entry) -> String {
+    match (&entry.artist, &entry.title, &entry.location) {
+        (Some(artist), Some(title), _) => format!("{} - {}", artist, title),
+        (None, Some(title), _) => title.clone(),
+        (_, None, Some(location)) => location.clone(),
+        _ => "(empty entry)".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{config, JournalEntry, Operation};
+    use tempfile::TempDir;
+
+    fn track(id: &str, provider: ProviderKind) -> Track {
+        Track {
+            id: id.to_string(),
+            name: id.to_string(),
+            artists: vec!["Band".to_string()],
+            duration_ms: 1000,
+            provider,
+            metadata: None,
+        }
+    }
+
+    fn track_playlist(grit_dir: &Path, provider: ProviderKind, ids: &[&str]) {
+        let snap = PlaylistSnapshot {
+            tracks: ids.iter().map(|id| track(id, provider.clone())).collect(),
+            ..PlaylistSnapshot::fixture(provider, &[])
+        };
+        snapshot::save(&snap, &snapshot::snapshot_path(grit_dir, "pl1")).unwrap();
+        let hash = snapshot::compute_hash(&snap).unwrap();
+        let entry = JournalEntry::new(Operation::Init, hash, ids.len(), 0, 0);
+        JournalEntry::record(grit_dir, "pl1", &entry).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_needs_no_credentials() {
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path().join(".grit");
+        track_playlist(&grit_dir, ProviderKind::Spotify, &["abc"]);
+
+        let out = temp.path().join("mix.m3u8");
+        export(
+            Some("pl1"),
+            None,
+            None,
+            Some(out.to_str().unwrap()),
+            &[],
+            &grit_dir,
+        )
+        .await
+        .unwrap();
+
+        assert!(fs::read_to_string(&out)
+            .unwrap()
+            .contains("spotify:track:abc"));
+    }
+
+    #[tokio::test]
+    async fn test_import_keeps_repeated_tracks() {
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path().join(".grit");
+        let music = temp.path().join("music");
+        fs::create_dir_all(&music).unwrap();
+        for name in ["a.mp3", "b.mp3"] {
+            fs::write(music.join(name), b"").unwrap();
+        }
+        config::set(
+            &config::repo_path(&grit_dir),
+            "local.root",
+            music.to_str().unwrap(),
+        )
+        .unwrap();
+        track_playlist(&grit_dir, ProviderKind::Local, &["a.mp3"]);
+
+        let file = music.join("new.m3u8");
+        fs::write(&file, "#EXTM3U\na.mp3\nb.mp3\na.mp3\n").unwrap();
+        import(file.to_str().unwrap(), None, Some("pl1"), &grit_dir)
+            .await
+            .unwrap();
+
+        let mut snap = snapshot::load(&snapshot::snapshot_path(&grit_dir, "pl1")).unwrap();
+        crate::state::apply_patch(&mut snap, &load_staged(&grit_dir, "pl1").unwrap()).unwrap();
+        let ids: Vec<&str> = snap.tracks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["a.mp3", "b.mp3", "a.mp3"]);
+    }
+
+    #[test]
+    fn test_track_id_from_location() {
+        let base = Path::new("/music");
+        assert_eq!(
+            track_id_from_location("spotify:track:abc", &ProviderKind::Spotify, base).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            track_id_from_location(
+                "https://open.spotify.com/track/abc?si=x",
+                &ProviderKind::Spotify,
+                base
+            )
+            .as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            track_id_from_location(
+                "https://www.youtube.com/watch?list=PL1&v=vid",
+                &ProviderKind::Youtube,
+                base
+            )
+            .as_deref(),
+            Some("vid")
+        );
+        assert_eq!(
+            track_id_from_location("https://youtu.be/vid?t=3", &ProviderKind::Youtube, base)
+                .as_deref(),
+            Some("vid")
+        );
+        assert!(
+            track_id_from_location("spotify:track:abc", &ProviderKind::Youtube, base).is_none()
+        );
+        assert_eq!(
+            track_id_from_location("file:///a/My%20Song.mp3", &ProviderKind::Local, base)
+                .as_deref(),
+            Some("/a/My Song.mp3")
+        );
+        assert_eq!(
+            track_id_from_location("sub/b.flac", &ProviderKind::Local, base).as_deref(),
+            Some("/music/sub/b.flac")
+        );
+    }
+}
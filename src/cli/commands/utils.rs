@@ -1,33 +1,169 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 
 use crate::{
-    provider::{Provider, ProviderKind, SpotifyProvider, YoutubeProvider},
-    state::credentials,
+    formats::slugify,
+    provider::{
+        Endpoints, LocalProvider, PlaylistSnapshot, PluginProvider, Provider, ProviderKind,
+        SpotifyProvider, SubsonicProvider, YoutubeProvider,
+    },
+    state::{config::Config, credentials, quota, snapshot},
 };
 
-pub fn create_provider(provider_kind: ProviderKind, grit_dir: &Path) -> Result<Box<dyn Provider>> {
-    let token = credentials::load(grit_dir, provider_kind)?
-        .context("No credentials found. Please run 'grit auth <provider>' first.")?;
+pub fn create_provider(provider_kind: &ProviderKind, grit_dir: &Path) -> Result<Box<dyn Provider>> {
+    let config = Config::load(grit_dir, None)?;
+    let search_limit = config.get_u64("search.limit")?.unwrap_or(10) as usize;
 
-    let provider: Box<dyn Provider> = match provider_kind {
-        ProviderKind::Spotify => {
-            let client_id =
-                std::env::var("SPOTIFY_CLIENT_ID").context("SPOTIFY_CLIENT_ID not set")?;
-            let client_secret =
-                std::env::var("SPOTIFY_CLIENT_SECRET").context("SPOTIFY_CLIENT_SECRET not set")?;
-
-            Box::new(SpotifyProvider::new(client_id, client_secret).with_token(&token, grit_dir))
+    match provider_kind {
+        ProviderKind::Local => {
+            return Ok(Box::new(
+                LocalProvider::new(local_root(&config)?).with_search_limit(search_limit),
+            ))
+        }
+        ProviderKind::Plugin(name) => return Ok(Box::new(PluginProvider::find(name, grit_dir)?)),
+        ProviderKind::Subsonic => {
+            let (url, username) = subsonic_account(&config)?;
+            let password = credentials::load(grit_dir, provider_kind)?
+                .context("No credentials found. Please run 'grit auth subsonic' first.")?
+                .access_token;
+            return Ok(Box::new(
+                SubsonicProvider::new(&url, &username, &password).with_search_limit(search_limit),
+            ));
         }
-        ProviderKind::Youtube => {
-            let client_id =
-                std::env::var("YOUTUBE_CLIENT_ID").context("YOUTUBE_CLIENT_ID not set")?;
-            let client_secret =
-                std::env::var("YOUTUBE_CLIENT_SECRET").context("YOUTUBE_CLIENT_SECRET not set")?;
+        _ => {}
+    }
 
-            Box::new(YoutubeProvider::new(client_id, client_secret).with_token(&token, grit_dir))
+    let token = credentials::load(grit_dir, provider_kind)?.with_context(|| {
+        format!(
+            "No credentials found. Please run 'grit auth {}' first.",
+            provider_kind
+        )
+    })?;
+    let (client_id, client_secret) = client_credentials(provider_kind, &config)?;
+
+    let provider: Box<dyn Provider> = match provider_kind {
+        ProviderKind::Spotify => Box::new(
+            SpotifyProvider::new(client_id, client_secret)
+                .with_endpoints(endpoints(
+                    provider_kind,
+                    &config,
+                    SpotifyProvider::default_endpoints(),
+                ))
+                .with_token(&token, grit_dir)
+                .with_search_limit(search_limit),
+        ),
+        ProviderKind::Youtube => Box::new(
+            YoutubeProvider::new(client_id, client_secret)
+                .with_endpoints(endpoints(
+                    provider_kind,
+                    &config,
+                    YoutubeProvider::default_endpoints(),
+                ))
+                .with_token(&token, grit_dir)
+                .with_search_limit(search_limit)
+                .with_quota_budget(quota::budget(&config)?),
+        ),
+        ProviderKind::Local | ProviderKind::Subsonic | ProviderKind::Plugin(_) => {
+            unreachable!("handled above")
         }
     };
     Ok(provider)
 }
+
+/// Music directory of the local provider (`local.root`), with `~` expanded.
+pub fn local_root(config: &Config) -> Result<PathBuf> {
+    let root = config
+        .get_str("local.root")
+        .context("local.root not set (run 'grit config set local.root ~/Music')")?;
+
+    match root.strip_prefix("~/") {
+        Some(rest) => {
+            let home = std::env::var("HOME").context("HOME not set")?;
+            Ok(PathBuf::from(home).join(rest))
+        }
+        None => Ok(PathBuf::from(root)),
+    }
+}
+
+/// An unused `<prefix>-<slug>` ID for a playlist that isn't on its
+/// provider yet.
+pub fn placeholder_id(prefix: &str, name: &str, grit_dir: &Path) -> String {
+    let slug = slugify(name);
+    let base = format!(
+        "{}-{}",
+        prefix,
+        if slug.is_empty() { "playlist" } else { &slug }
+    );
+
+    let mut id = base.clone();
+    let mut n = 2;
+    while snapshot::snapshot_path(grit_dir, &id).exists() {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// Fail unless changes to the snapshot's source can be pushed back to it.
+pub fn require_editable(snapshot: &PlaylistSnapshot) -> Result<()> {
+    if !snapshot.source.is_editable() {
+        bail!(
+            "'{}' is a read-only {} source; use 'grit fork' to make an editable copy",
+            snapshot.id,
+            snapshot.source
+        );
+    }
+    Ok(())
+}
+
+/// `defaults` with any of `<provider>.api_url`, `<provider>.auth_url` and
+/// `<provider>.token_url` from the config applied.
+pub fn endpoints(provider_kind: &ProviderKind, config: &Config, defaults: Endpoints) -> Endpoints {
+    let lookup = |name: &str| config.get_str(&format!("{}.{}", provider_kind, name));
+
+    Endpoints {
+        api: lookup("api_url").unwrap_or(defaults.api),
+        auth: lookup("auth_url").unwrap_or(defaults.auth),
+        token: lookup("token_url").unwrap_or(defaults.token),
+    }
+}
+
+/// Server URL and account name of the Subsonic provider
+/// (`subsonic.url`, `subsonic.username`).
+pub fn subsonic_account(config: &Config) -> Result<(String, String)> {
+    let url = config.get_str("subsonic.url").context(
+        "subsonic.url not set (run 'grit config set subsonic.url https://music.example.com')",
+    )?;
+    let username = config
+        .get_str("subsonic.username")
+        .context("subsonic.username not set (run 'grit config set subsonic.username <name>')")?;
+    Ok((url, username))
+}
+
+/// OAuth client ID and secret for a provider.
+///
+/// Environment variables (`SPOTIFY_CLIENT_ID`, ...) take precedence over
+/// `<provider>.client_id` / `<provider>.client_secret` in the config.
+pub fn client_credentials(
+    provider_kind: &ProviderKind,
+    config: &Config,
+) -> Result<(String, String)> {
+    let prefix = provider_kind.to_string();
+    let env_prefix = prefix.to_uppercase();
+
+    let lookup = |name: &str| {
+        let env_var = format!("{}_{}", env_prefix, name.to_uppercase());
+        std::env::var(&env_var)
+            .ok()
+            .or_else(|| config.get_str(&format!("{}.{}", prefix, name)))
+            .with_context(|| {
+                format!(
+                    "{} not set (export it or run 'grit config set {}.{} <value>')",
+                    env_var, prefix, name
+                )
+            })
+    };
+
+    Ok((lookup("client_id")?, lookup("client_secret")?))
+}
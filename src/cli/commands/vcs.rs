@@ -3,18 +3,46 @@ use std::path::Path;
 use anyhow::{bail, Context, Result};
 
 use crate::{
-    cli::commands::utils::create_provider,
-    state::{diff, load_staged, snapshot, JournalEntry, Operation},
+    cli::commands::output::{print_json, DiffOutput, LogEntryOutput, LogOutput},
+    cli::commands::remote::mirror_snapshot,
+    cli::commands::utils::{create_provider, require_editable},
+    matching::{self, MatchCache},
+    provider::{
+        DiffPatch, PlaylistSnapshot, Provider, ProviderKind, SourceKind, Track, TrackChange,
+        YoutubeProvider,
+    },
+    state::{
+        diff,
+        journal::LogFilter,
+        load_staged,
+        push_plan::PushPlan,
+        quota,
+        remotes::{self, Remote, ORIGIN},
+        snapshot, working_playlist, JournalEntry, Operation,
+    },
+    utils::hooks::{self, Hook},
 };
 
-pub async fn push(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+pub async fn push(
+    playlist: Option<&str>,
+    grit_dir: &Path,
+    remote: Option<&str>,
+    all: bool,
+    dry_run: bool,
+) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist)")?;
-
     let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
     if !snapshot_path.exists() {
         bail!("Playlist not initialized. Run 'grit init' first.");
     }
 
+    if let Some(plan) = PushPlan::load(grit_dir, playlist_id)? {
+        bail!(
+            "A push to {} was interrupted. Run 'grit push --continue' to finish it or 'grit push --abort' to roll it back.",
+            plan.remote
+        );
+    }
+
     let staged = load_staged(grit_dir, playlist_id)?;
     if !staged.changes.is_empty() {
         bail!(
@@ -23,24 +51,174 @@ pub async fn push(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
         );
     }
 
-    let local_snapshot = snapshot::load(&snapshot_path)?;
-    let provider = create_provider(local_snapshot.provider, grit_dir)?;
+    let mut local_snapshot = snapshot::load(&snapshot_path)?;
+    let mirrors = remotes::load(grit_dir, playlist_id)?;
+
+    // None stands for the origin playlist the snapshot was initialized from
+    let targets: Vec<Option<&Remote>> = match remote {
+        _ if all => std::iter::once(None)
+            .filter(|_| local_snapshot.source.is_editable())
+            .chain(mirrors.iter().map(Some))
+            .collect(),
+        None | Some(ORIGIN) => vec![None],
+        Some(name) => vec![Some(mirrors.iter().find(|r| r.name == name).with_context(
+            || format!("No remote named '{}' (see 'grit remote show')", name),
+        )?)],
+    };
+
+    // Publishing a playlist changes its ID
+    let mut playlist_id = playlist_id.to_string();
+    let mut snapshot_path = snapshot_path;
+
+    for target in targets {
+        match target {
+            None => {
+                require_editable(&local_snapshot)?;
+                let provider = create_provider(&local_snapshot.provider, grit_dir)?;
+                if !remotes::has_origin(&local_snapshot) && dry_run {
+                    println!(
+                        "Would create {} playlist '{}' with {} tracks and move {} to its ID",
+                        local_snapshot.provider,
+                        local_snapshot.name,
+                        local_snapshot.tracks.len(),
+                        playlist_id
+                    );
+                    continue;
+                }
+                if !remotes::has_origin(&local_snapshot) {
+                    playlist_id = publish(grit_dir, &mut local_snapshot, provider.as_ref()).await?;
+                    snapshot_path = snapshot::snapshot_path(grit_dir, &playlist_id);
+                }
+                push_to(
+                    grit_dir,
+                    &playlist_id,
+                    ORIGIN,
+                    &playlist_id,
+                    provider.as_ref(),
+                    &local_snapshot,
+                    dry_run,
+                )
+                .await?;
+            }
+            Some(mirror) => {
+                println!(
+                    "\n[{}] Mapping tracks to {}...",
+                    mirror.name, mirror.provider
+                );
+                if mirror.provider == ProviderKind::Youtube {
+                    check_mapping_quota(grit_dir, &local_snapshot, mirror, dry_run)?;
+                }
+                let provider = create_provider(&mirror.provider, grit_dir)?;
+                let (desired, updated) = mirror_snapshot(
+                    &mut local_snapshot,
+                    mirror,
+                    provider.as_ref(),
+                    grit_dir,
+                    dry_run,
+                )
+                .await?;
+
+                if updated && dry_run {
+                    println!("Would save the new mappings to {:?}", snapshot_path);
+                } else if updated {
+                    // Keep the new mappings so later pushes produce the same tracks
+                    snapshot::save(&local_snapshot, &snapshot_path)?;
+                    let hash = snapshot::compute_hash(&local_snapshot)?;
+                    snapshot::save_by_hash(&local_snapshot, &hash, grit_dir, &playlist_id)?;
+                }
 
-    println!("Verifying write permissions...");
-    let can_modify = provider.can_modify_playlist(playlist_id).await?;
-    if !can_modify {
+                push_to(
+                    grit_dir,
+                    &playlist_id,
+                    &mirror.name,
+                    &mirror.playlist_id,
+                    provider.as_ref(),
+                    &desired,
+                    dry_run,
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the playlist on its provider for a snapshot that isn't there yet
+/// (made with `grit new` or imported without an ID), then move the local
+/// state from the placeholder ID to the new playlist's ID and return it.
+async fn publish(
+    grit_dir: &Path,
+    local_snapshot: &mut PlaylistSnapshot,
+    provider: &dyn Provider,
+) -> Result<String> {
+    if !provider.capabilities().create {
         bail!(
-            "You don't have write access to this playlist. Only the owner or collaborators can push changes."
+            "{} playlists can't be created by grit. Mirror this one to an existing playlist with 'grit remote add'.",
+            local_snapshot.provider
         );
     }
 
+    let placeholder = local_snapshot.id.clone();
+    println!(
+        "Creating {} playlist '{}'...",
+        local_snapshot.provider, local_snapshot.name
+    );
+    let remote_id = provider
+        .create_playlist(&local_snapshot.name, local_snapshot.description.as_deref())
+        .await?;
+
+    snapshot::rename_playlist(grit_dir, &placeholder, &remote_id)?;
+    remotes::mark_published(local_snapshot, &remote_id);
+    let snapshot_path = snapshot::snapshot_path(grit_dir, &remote_id);
+    snapshot::save(local_snapshot, &snapshot_path)?;
+    let hash = snapshot::compute_hash(local_snapshot)?;
+    snapshot::save_by_hash(local_snapshot, &hash, grit_dir, &remote_id)?;
+
+    if working_playlist::load(grit_dir).ok().as_deref() == Some(placeholder.as_str()) {
+        working_playlist::save(grit_dir, &remote_id)?;
+    }
+
+    println!("  {} is now {}", placeholder, remote_id);
+    Ok(remote_id)
+}
+
+/// Make the remote playlist `remote_id` match `desired` and record the push
+/// in the journal of the tracked playlist `playlist_id`.
+async fn push_to(
+    grit_dir: &Path,
+    playlist_id: &str,
+    remote_name: &str,
+    remote_id: &str,
+    provider: &dyn Provider,
+    desired: &PlaylistSnapshot,
+    dry_run: bool,
+) -> Result<()> {
+    if !provider.capabilities().write {
+        bail!("{} playlists are read-only in grit", desired.provider);
+    }
+
+    // Liked songs always belong to the authenticated account
+    if desired.source == SourceKind::Playlist {
+        println!("Verifying write permissions...");
+        let can_modify = provider.can_modify_playlist(remote_id).await?;
+        if !can_modify {
+            bail!(
+                "You don't have write access to this playlist. Only the owner or collaborators can push changes."
+            );
+        }
+    }
+
     println!("Fetching remote playlist state...");
-    let remote_snapshot = provider.fetch(playlist_id).await?;
+    let remote_snapshot = provider.fetch_source(desired.source, remote_id).await?;
 
-    let patch = diff(&remote_snapshot, &local_snapshot);
+    let patch = diff(&remote_snapshot, desired);
 
     if patch.changes.is_empty() {
-        println!("\nNo changes to push. Local and remote are in sync.");
+        println!(
+            "\nNo changes to push. Local and {} are in sync.",
+            remote_name
+        );
         return Ok(());
     }
 
@@ -50,33 +228,294 @@ pub async fn push(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
 
     for change in &patch.changes {
         match change {
-            crate::provider::TrackChange::Added { .. } => added += 1,
-            crate::provider::TrackChange::Removed { .. } => removed += 1,
-            crate::provider::TrackChange::Moved { .. } => moved += 1,
+            TrackChange::Added { .. } => added += 1,
+            TrackChange::Removed { .. } => removed += 1,
+            TrackChange::Moved { .. } => moved += 1,
         }
     }
 
+    let local_snapshot = snapshot::load(&snapshot::snapshot_path(grit_dir, playlist_id))?;
+    let mut plan = PushPlan::new(
+        remote_name,
+        &snapshot::compute_hash(&local_snapshot)?,
+        &remote_snapshot,
+        desired,
+        (added, removed, moved),
+    );
+
+    if desired.provider == ProviderKind::Youtube {
+        check_youtube_quota(grit_dir, &plan, dry_run)?;
+    }
+
+    if dry_run {
+        println!(
+            "\nWould push to {}: +{} -{} ~{}",
+            remote_name, added, removed, moved
+        );
+        print_changes(&patch);
+        println!("\nSteps:");
+        for (i, step) in plan.steps.iter().enumerate() {
+            println!("  {}. {}", i + 1, step.description);
+        }
+        // Only web API providers can be replayed safely: in a dry run (see
+        // main) their HTTP client prints the changing requests instead of
+        // sending them
+        if matches!(
+            desired.provider,
+            ProviderKind::Spotify | ProviderKind::Youtube | ProviderKind::Subsonic
+        ) {
+            println!("\nRequests that would change {}:", remote_name);
+            provider
+                .apply_known(desired.source, remote_id, &remote_snapshot, &patch, desired)
+                .await?;
+        }
+        println!("\nDry run; {} was not changed.", remote_name);
+        return Ok(());
+    }
+
+    hooks::run(grit_dir, Hook::PrePush, playlist_id, &patch, None)?;
+
     println!(
-        "\nPushing changes to remote: +{} -{} ~{}",
-        added, removed, moved
+        "\nPushing changes to {}: +{} -{} ~{}",
+        remote_name, added, removed, moved
     );
 
-    // Apply patch to remote to match local snapshot
-    provider.apply(playlist_id, &patch, &local_snapshot).await?;
+    // Record the plan before the first request so an interruption can be resumed
+    plan.save(grit_dir, playlist_id)?;
 
-    // Record in journal
-    let hash = snapshot::compute_hash(&local_snapshot)?;
-    let journal_path = JournalEntry::journal_path(grit_dir, playlist_id);
-    let entry = JournalEntry::new(Operation::Push, hash, added, removed, moved);
-    JournalEntry::append(&journal_path, &entry)?;
+    run_plan(grit_dir, playlist_id, &mut plan, provider, &remote_snapshot).await?;
 
-    println!("\nSuccessfully pushed to remote!");
+    println!("\nSuccessfully pushed to {}!", remote_name);
     println!("  {} changes applied", patch.changes.len());
 
     Ok(())
 }
 
-pub async fn log(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+/// Estimate the YouTube quota `plan` spends, including the fetch that checks
+/// the result, and stop before a push would take today's usage over
+/// `youtube.quota_budget`. A dry run only warns.
+fn check_youtube_quota(grit_dir: &Path, plan: &PushPlan, dry_run: bool) -> Result<()> {
+    let source = plan.before.source;
+    let steps: u64 = (0..plan.steps.len())
+        .map(|i| {
+            let (current, next) = (plan.target(i), plan.target(i + 1));
+            YoutubeProvider::apply_cost(
+                source,
+                &current.tracks,
+                &diff(&current, &next),
+                &next.tracks,
+            )
+        })
+        .sum();
+    let cost =
+        steps + YoutubeProvider::fetch_cost(source, plan.target(plan.steps.len()).tracks.len());
+
+    quota::check_budget(grit_dir, "This push", cost, dry_run)
+}
+
+/// Check what mapping the unmapped tracks of `local` to a YouTube mirror
+/// spends (a search each, unless cached, and an insert each) against the
+/// budget, before the first search.
+fn check_mapping_quota(
+    grit_dir: &Path,
+    local: &PlaylistSnapshot,
+    mirror: &Remote,
+    dry_run: bool,
+) -> Result<()> {
+    let unmapped: Vec<&Track> = local
+        .tracks
+        .iter()
+        .filter(|track| remotes::mirrored_track(track, &mirror.provider).is_none())
+        .collect();
+    if unmapped.is_empty() {
+        return Ok(());
+    }
+
+    let cache = MatchCache::load(grit_dir)?;
+    let searches = matching::searches_needed(unmapped.iter().copied(), &mirror.provider, &cache);
+    let cost = searches as u64 * quota::SEARCH + unmapped.len() as u64 * quota::WRITE;
+    quota::check_budget(
+        grit_dir,
+        &format!("Mapping {} track(s) to {}", unmapped.len(), mirror.name),
+        cost,
+        dry_run,
+    )
+}
+
+/// Apply the remaining steps of `plan` to a remote currently at `remote`,
+/// checkpointing after each one, then check the result and record the push.
+async fn run_plan(
+    grit_dir: &Path,
+    playlist_id: &str,
+    plan: &mut PushPlan,
+    provider: &dyn Provider,
+    remote: &PlaylistSnapshot,
+) -> Result<()> {
+    let source = plan.before.source;
+    let remote_id = plan.before.id.clone();
+
+    let mut current = remote.clone();
+    for index in plan.completed..plan.steps.len() {
+        let target = plan.target(index + 1);
+        println!(
+            "  [{}/{}] {}",
+            index + 1,
+            plan.steps.len(),
+            plan.steps[index].description
+        );
+
+        let patch = diff(&current, &target);
+        provider
+            .apply_known(source, &remote_id, &current, &patch, &target)
+            .await?;
+
+        plan.completed = index + 1;
+        plan.save(grit_dir, playlist_id)?;
+        current = target;
+    }
+
+    verify(plan, provider).await?;
+    PushPlan::remove(grit_dir, playlist_id)?;
+
+    let entry = if plan.remote == ORIGIN {
+        JournalEntry::new(
+            Operation::Push,
+            plan.local_hash.clone(),
+            plan.added,
+            plan.removed,
+            plan.moved,
+        )
+    } else {
+        JournalEntry::new_with_message(
+            Operation::Push,
+            plan.local_hash.clone(),
+            plan.added,
+            plan.removed,
+            plan.moved,
+            format!("Push to {}", plan.remote),
+        )
+    };
+    JournalEntry::record(grit_dir, playlist_id, &entry)
+}
+
+/// Fail unless the remote ended up as the plan intended.
+async fn verify(plan: &PushPlan, provider: &dyn Provider) -> Result<()> {
+    let remote = provider
+        .fetch_source(plan.before.source, &plan.before.id)
+        .await?;
+    if !plan.matches(plan.steps.len(), &remote) {
+        bail!(
+            "{} doesn't match the pushed playlist; it may have changed during the push. \
+             Run 'grit push --continue' to apply the plan again or 'grit push --abort' to roll it back.",
+            plan.remote
+        );
+    }
+    Ok(())
+}
+
+/// Finish an interrupted push from the step the remote has reached.
+pub async fn push_continue(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+    let mut plan =
+        PushPlan::load(grit_dir, playlist_id)?.context("No interrupted push to continue")?;
+    let provider = create_provider(&plan.before.provider, grit_dir)?;
+
+    resume(grit_dir, playlist_id, &mut plan, provider.as_ref()).await?;
+    println!("\nSuccessfully pushed to {}!", plan.remote);
+
+    Ok(())
+}
+
+async fn resume(
+    grit_dir: &Path,
+    playlist_id: &str,
+    plan: &mut PushPlan,
+    provider: &dyn Provider,
+) -> Result<()> {
+    println!("Fetching remote playlist state...");
+    let remote = provider
+        .fetch_source(plan.before.source, &plan.before.id)
+        .await?;
+
+    plan.completed = plan.steps_applied(&remote);
+    println!(
+        "Continuing push to {} at step {}/{}",
+        plan.remote,
+        (plan.completed + 1).min(plan.steps.len()),
+        plan.steps.len()
+    );
+
+    run_plan(grit_dir, playlist_id, plan, provider, &remote).await
+}
+
+/// Roll the remote of an interrupted push back to its state before the push.
+pub async fn push_abort(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+    let playlist_id = playlist.context("Playlist required (use --playlist)")?;
+    let plan = PushPlan::load(grit_dir, playlist_id)?.context("No interrupted push to abort")?;
+    let provider = create_provider(&plan.before.provider, grit_dir)?;
+
+    abort(grit_dir, playlist_id, &plan, provider.as_ref()).await?;
+    println!(
+        "\nRolled {} back to its state before the push.",
+        plan.remote
+    );
+
+    Ok(())
+}
+
+async fn abort(
+    grit_dir: &Path,
+    playlist_id: &str,
+    plan: &PushPlan,
+    provider: &dyn Provider,
+) -> Result<()> {
+    println!("Fetching remote playlist state...");
+    let remote = provider
+        .fetch_source(plan.before.source, &plan.before.id)
+        .await?;
+
+    if !plan.matches(0, &remote) {
+        let patch = diff(&remote, &plan.before);
+        provider
+            .apply_known(
+                plan.before.source,
+                &plan.before.id,
+                &remote,
+                &patch,
+                &plan.before,
+            )
+            .await?;
+
+        let remote = provider
+            .fetch_source(plan.before.source, &plan.before.id)
+            .await?;
+        if !plan.matches(0, &remote) {
+            bail!(
+                "{} couldn't be restored; the push plan is kept",
+                plan.remote
+            );
+        }
+    }
+
+    PushPlan::remove(grit_dir, playlist_id)
+}
+
+/// Display options for `grit log`.
+#[derive(Debug, Default)]
+pub struct LogOptions {
+    pub filter: LogFilter,
+    pub oneline: bool,
+    pub stat: bool,
+    pub max_count: Option<usize>,
+    pub format: Option<String>,
+}
+
+pub async fn log(
+    playlist: Option<&str>,
+    grit_dir: &Path,
+    options: &LogOptions,
+    json: bool,
+) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist")?;
 
     let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
@@ -84,47 +523,208 @@ pub async fn log(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
         bail!("Playlist not initialized. Run 'grit init' first.");
     }
 
-    let journal_path = JournalEntry::journal_path(grit_dir, playlist_id);
-    let entries = JournalEntry::read_all(&journal_path)?;
+    let entries = JournalEntry::history(grit_dir, playlist_id)?;
 
-    if entries.is_empty() {
+    if entries.is_empty() && !json {
         println!("No history yet.");
         return Ok(());
     }
 
-    println!("\nCommit History:\n");
-
-    for entry in entries.iter().rev() {
-        let hash_short = &entry.snapshot_hash[..8.min(entry.snapshot_hash.len())];
-        let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M:%S");
+    // Newest first; keep the chronological index so --stat can find the parent entry
+    let selected: Vec<(usize, &JournalEntry)> = entries
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, entry)| options.filter.matches(entry))
+        .take(options.max_count.unwrap_or(usize::MAX))
+        .collect();
+
+    if json {
+        let entries = selected
+            .iter()
+            .map(|&(idx, entry)| {
+                let stat = options.stat.then(|| {
+                    let parent = idx.checked_sub(1).map(|i| &entries[i]);
+                    log_stat(grit_dir, playlist_id, parent, entry)
+                });
+                let (changes, error) = match stat {
+                    Some(Ok(patch)) => (Some(patch), None),
+                    Some(Err(e)) => (None, Some(format!("{:#}", e))),
+                    None => (None, None),
+                };
+                LogEntryOutput {
+                    entry,
+                    changes,
+                    error,
+                }
+            })
+            .collect();
+        return print_json(&LogOutput {
+            playlist_id: playlist_id.to_string(),
+            entries,
+        });
+    }
 
-        let operation_str = match entry.operation {
-            Operation::Init => "init",
-            Operation::Pull => "pull",
-            Operation::Push => "push",
-            Operation::Apply => "apply",
-            Operation::Commit => "commit",
-        };
+    if selected.is_empty() {
+        println!("No entries match the given filters.");
+        return Ok(());
+    }
 
-        let changes = format!("+{} -{} ~{}", entry.added, entry.removed, entry.moved);
+    let plain = !options.oneline && options.format.is_none();
+    if plain {
+        println!("\nCommit History:\n");
+    }
 
-        if let Some(msg) = &entry.message {
+    for (idx, entry) in selected {
+        if let Some(template) = &options.format {
+            println!("{}", render_log_format(template, entry));
+        } else if options.oneline {
+            let hash_short = &entry.snapshot_hash[..8.min(entry.snapshot_hash.len())];
             println!(
-                "[{}] {} | {} | {}",
-                hash_short, timestamp, operation_str, msg
+                "{} {} {}",
+                hash_short,
+                entry.operation,
+                entry.message.as_deref().unwrap_or("")
             );
         } else {
-            println!("[{}] {} | {}", hash_short, timestamp, operation_str);
+            print_log_entry(entry);
+        }
+
+        if options.stat {
+            let parent = idx.checked_sub(1).map(|i| &entries[i]);
+            print_log_stat(log_stat(grit_dir, playlist_id, parent, entry));
         }
 
-        println!("  {}", changes);
-        println!();
+        if plain || options.stat {
+            println!();
+        }
     }
 
     Ok(())
 }
 
-pub async fn pull(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+fn print_log_entry(entry: &JournalEntry) {
+    let hash_short = &entry.snapshot_hash[..8.min(entry.snapshot_hash.len())];
+    let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M:%S");
+    let changes = format!("+{} -{} ~{}", entry.added, entry.removed, entry.moved);
+
+    if let Some(msg) = &entry.message {
+        println!(
+            "[{}] {} | {} | {}",
+            hash_short, timestamp, entry.operation, msg
+        );
+    } else {
+        println!("[{}] {} | {}", hash_short, timestamp, entry.operation);
+    }
+
+    if let Some(author) = &entry.author {
+        println!("  Author: {}", author);
+    }
+    println!("  {}", changes);
+}
+
+/// Compute the tracks changed by `entry` from the stored snapshots of the
+/// entry and its parent.
+fn log_stat(
+    grit_dir: &Path,
+    playlist_id: &str,
+    parent: Option<&JournalEntry>,
+    entry: &JournalEntry,
+) -> Result<DiffPatch> {
+    let current = snapshot::load_by_hash(&entry.snapshot_hash, grit_dir, playlist_id)?;
+
+    let previous = match parent {
+        Some(parent) => snapshot::load_by_hash(&parent.snapshot_hash, grit_dir, playlist_id)?,
+        None => PlaylistSnapshot {
+            tracks: Vec::new(),
+            ..current.clone()
+        },
+    };
+
+    Ok(diff(&previous, &current))
+}
+
+fn print_log_stat(stat: Result<DiffPatch>) {
+    match stat {
+        Ok(patch) => print_changes(&patch),
+        Err(e) => println!("  ({}, no stat available)", e),
+    }
+}
+
+fn print_changes(patch: &DiffPatch) {
+    for change in &patch.changes {
+        match change {
+            TrackChange::Added { track, .. } => {
+                println!("  + {} - {}", track.name, track.artists.join(", "))
+            }
+            TrackChange::Removed { track, .. } => {
+                println!("  - {} - {}", track.name, track.artists.join(", "))
+            }
+            TrackChange::Moved { track, from, to } => println!(
+                "  ~ {} - {} ({} -> {})",
+                track.name,
+                track.artists.join(", "),
+                from,
+                to
+            ),
+        }
+    }
+}
+
+/// List the files a command would write to record the snapshot `hash` as
+/// an `operation` entry, for `--dry-run`.
+fn print_local_writes(grit_dir: &Path, playlist_id: &str, hash: &str, operation: Operation) {
+    println!("\nDry run; would write:");
+    println!("  {:?}", snapshot::snapshot_path(grit_dir, playlist_id));
+    println!(
+        "  {:?}",
+        snapshot::snapshots_dir(grit_dir, playlist_id).join(format!("{}.yaml", hash))
+    );
+    println!(
+        "  {:?} ({} entry)",
+        JournalEntry::journal_path(grit_dir, playlist_id),
+        operation
+    );
+}
+
+/// Expand a `--format` template for a journal entry.
+fn render_log_format(template: &str, entry: &JournalEntry) -> String {
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            out.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('h') => out.push_str(&entry.snapshot_hash[..8.min(entry.snapshot_hash.len())]),
+            Some('H') => out.push_str(&entry.snapshot_hash),
+            Some('d') => out.push_str(&entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()),
+            Some('o') => out.push_str(&entry.operation.to_string()),
+            Some('s') => out.push_str(entry.message.as_deref().unwrap_or("")),
+            Some('a') if chars.peek() == Some(&'n') => {
+                chars.next();
+                out.push_str(entry.author.as_deref().unwrap_or(""));
+            }
+            Some('a') => out.push_str(&entry.added.to_string()),
+            Some('r') => out.push_str(&entry.removed.to_string()),
+            Some('m') => out.push_str(&entry.moved.to_string()),
+            Some('n') => out.push('\n'),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+
+    out
+}
+
+pub async fn pull(playlist: Option<&str>, grit_dir: &Path, dry_run: bool) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist)")?;
 
     let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
@@ -141,10 +741,14 @@ pub async fn pull(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
     }
 
     let local_snapshot = snapshot::load(&snapshot_path)?;
-    let provider = create_provider(local_snapshot.provider, grit_dir)?;
+    remotes::require_origin(&local_snapshot)?;
+    let provider = create_provider(&local_snapshot.provider, grit_dir)?;
 
     println!("Fetching remote playlist state...");
-    let remote_snapshot = provider.fetch(playlist_id).await?;
+    let mut remote_snapshot = provider
+        .fetch_source(local_snapshot.source, playlist_id)
+        .await?;
+    remotes::preserve_local_metadata(&local_snapshot.tracks, &mut remote_snapshot.tracks);
 
     let local_hash = snapshot::compute_hash(&local_snapshot)?;
     let remote_hash = snapshot::compute_hash(&remote_snapshot)?;
@@ -168,6 +772,16 @@ pub async fn pull(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
         }
     }
 
+    if dry_run {
+        println!(
+            "\nWould pull changes from remote: +{} -{} ~{}",
+            added, removed, moved
+        );
+        print_changes(&patch);
+        print_local_writes(grit_dir, playlist_id, &remote_hash, Operation::Pull);
+        return Ok(());
+    }
+
     println!(
         "\nPulling changes from remote: +{} -{} ~{}",
         added, removed, moved
@@ -175,15 +789,17 @@ pub async fn pull(playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
 
     // Update local snapshot to match remote
     snapshot::save(&remote_snapshot, &snapshot_path)?;
+    snapshot::save_by_hash(&remote_snapshot, &remote_hash, grit_dir, playlist_id)?;
 
     // Record in journal
-    let journal_path = JournalEntry::journal_path(grit_dir, playlist_id);
     let entry = JournalEntry::new(Operation::Pull, remote_hash, added, removed, moved);
-    JournalEntry::append(&journal_path, &entry)?;
+    JournalEntry::record(grit_dir, playlist_id, &entry)?;
 
     println!("\nSuccessfully pulled from remote!");
     println!("  {} changes applied", patch.changes.len());
 
+    hooks::run(grit_dir, Hook::PostPull, playlist_id, &patch, None)?;
+
     Ok(())
 }
 
@@ -192,6 +808,7 @@ pub async fn diff_cmd(
     grit_dir: &Path,
     staged: bool,
     remote: bool,
+    json: bool,
 ) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist)")?;
 
@@ -205,6 +822,32 @@ pub async fn diff_cmd(
     // Default to showing staged changes if no flag is specified
     let show_staged = staged || !remote;
 
+    if json {
+        let staged_patch = if show_staged {
+            Some(load_staged(grit_dir, playlist_id)?)
+        } else {
+            None
+        };
+        let (remote_patch, remote_error) = if remote {
+            let provider = create_provider(&local_snapshot.provider, grit_dir)?;
+            match provider
+                .fetch_source(local_snapshot.source, playlist_id)
+                .await
+            {
+                Ok(remote_snapshot) => (Some(diff(&remote_snapshot, &local_snapshot)), None),
+                Err(e) => (None, Some(e.to_string())),
+            }
+        } else {
+            (None, None)
+        };
+        return print_json(&DiffOutput {
+            playlist_id: playlist_id.to_string(),
+            staged: staged_patch,
+            remote: remote_patch,
+            remote_error,
+        });
+    }
+
     if show_staged {
         println!("\n[Staged Changes]\n");
 
@@ -249,9 +892,12 @@ pub async fn diff_cmd(
     if remote {
         println!("\n[Local vs Remote]\n");
 
-        let provider = create_provider(local_snapshot.provider, grit_dir)?;
+        let provider = create_provider(&local_snapshot.provider, grit_dir)?;
 
-        match provider.fetch(playlist_id).await {
+        match provider
+            .fetch_source(local_snapshot.source, playlist_id)
+            .await
+        {
             std::result::Result::Ok(remote_snapshot) => {
                 use crate::state::diff as compute_diff;
                 let patch = compute_diff(&remote_snapshot, &local_snapshot);
@@ -300,7 +946,12 @@ pub async fn diff_cmd(
     Ok(())
 }
 
-pub async fn revert(hash: Option<&str>, playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+pub async fn revert(
+    hash: Option<&str>,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+    dry_run: bool,
+) -> Result<()> {
     let playlist_id = playlist.context("Playlist required (use --playlist)")?;
 
     let snapshot_path = snapshot::snapshot_path(grit_dir, playlist_id);
@@ -322,8 +973,7 @@ pub async fn revert(hash: Option<&str>, playlist: Option<&str>, grit_dir: &Path)
         h.to_string()
     } else {
         // No hash provided - revert to previous commit
-        let journal_path = JournalEntry::journal_path(grit_dir, playlist_id);
-        let entries = JournalEntry::read_all(&journal_path)?;
+        let entries = JournalEntry::history(grit_dir, playlist_id)?;
 
         if entries.len() < 2 {
             bail!("Not enough commits to revert. Need at least 2 commits in history.");
@@ -334,16 +984,29 @@ pub async fn revert(hash: Option<&str>, playlist: Option<&str>, grit_dir: &Path)
     };
 
     // Load the target snapshot by hash
-    let target_snapshot = snapshot::load_by_hash(&target_hash, grit_dir, playlist_id)
+    let mut target_snapshot = snapshot::load_by_hash(&target_hash, grit_dir, playlist_id)
         .with_context(|| format!("Failed to load snapshot with hash '{}'", target_hash))?;
 
+    // Snapshots from before the playlist was published still have its placeholder ID
+    let current = snapshot::load(&snapshot_path)?;
+    if remotes::has_origin(&current) && !remotes::has_origin(&target_snapshot) {
+        remotes::mark_published(&mut target_snapshot, &current.id);
+    }
+
     let full_hash = snapshot::compute_hash(&target_snapshot)?;
 
+    if dry_run {
+        println!("Would revert to [{}]", target_hash);
+        print_changes(&diff(&current, &target_snapshot));
+        print_local_writes(grit_dir, playlist_id, &full_hash, Operation::Commit);
+        return Ok(());
+    }
+
     // Save as current snapshot
     snapshot::save(&target_snapshot, &snapshot_path)?;
+    snapshot::save_by_hash(&target_snapshot, &full_hash, grit_dir, playlist_id)?;
 
     // Record in journal
-    let journal_path = JournalEntry::journal_path(grit_dir, playlist_id);
     let entry = JournalEntry::new_with_message(
         Operation::Commit,
         full_hash.clone(),
@@ -352,7 +1015,7 @@ pub async fn revert(hash: Option<&str>, playlist: Option<&str>, grit_dir: &Path)
         0,
         format!("Revert to {}", target_hash),
     );
-    JournalEntry::append(&journal_path, &entry)?;
+    JournalEntry::record(grit_dir, playlist_id, &entry)?;
 
     println!("\nReverted to commit [{}]", full_hash);
     println!("Playlist: {}", target_snapshot.name);
@@ -362,7 +1025,12 @@ pub async fn revert(hash: Option<&str>, playlist: Option<&str>, grit_dir: &Path)
     Ok(())
 }
 
-pub async fn apply(file_path: &str, playlist: Option<&str>, grit_dir: &Path) -> Result<()> {
+pub async fn apply(
+    file_path: &str,
+    playlist: Option<&str>,
+    grit_dir: &Path,
+    dry_run: bool,
+) -> Result<()> {
     // Load the snapshot from YAML file
     let file_content = std::fs::read_to_string(file_path)
         .with_context(|| format!("Failed to read file: {}", file_path))?;
@@ -382,6 +1050,7 @@ pub async fn apply(file_path: &str, playlist: Option<&str>, grit_dir: &Path) ->
 
     // Load current snapshot to check provider compatibility
     let current_snapshot = snapshot::load(&snapshot_path)?;
+    require_editable(&current_snapshot)?;
     if current_snapshot.provider != snapshot.provider {
         bail!(
             "Provider mismatch: playlist is {:?} but file contains {:?} snapshot",
@@ -401,11 +1070,18 @@ pub async fn apply(file_path: &str, playlist: Option<&str>, grit_dir: &Path) ->
 
     // Compute hash and save snapshot
     let hash = snapshot::compute_hash(&snapshot)?;
+
+    if dry_run {
+        println!("Would apply {}", file_path);
+        print_changes(&diff(&current_snapshot, &snapshot));
+        print_local_writes(grit_dir, playlist_id, &hash, Operation::Apply);
+        return Ok(());
+    }
+
     snapshot::save(&snapshot, &snapshot_path)?;
     snapshot::save_by_hash(&snapshot, &hash, grit_dir, playlist_id)?;
 
     // Record in journal
-    let journal_path = JournalEntry::journal_path(grit_dir, playlist_id);
     let entry = JournalEntry::new_with_message(
         Operation::Apply,
         hash.clone(),
@@ -414,7 +1090,7 @@ pub async fn apply(file_path: &str, playlist: Option<&str>, grit_dir: &Path) ->
         0,
         format!("Applied from {}", file_path),
     );
-    JournalEntry::append(&journal_path, &entry)?;
+    JournalEntry::record(grit_dir, playlist_id, &entry)?;
 
     println!("\nApplied playlist state from file!");
     println!("  Playlist: {}", snapshot.name);
@@ -424,3 +1100,328 @@ pub async fn apply(file_path: &str, playlist: Option<&str>, grit_dir: &Path) ->
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::FakeProvider;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_push_to_applies_and_records() {
+        let dir = TempDir::new().unwrap();
+        let local = PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b", "c"]);
+        snapshot::save(&local, &snapshot::snapshot_path(dir.path(), "pl1")).unwrap();
+
+        let remote = FakeProvider::new(ProviderKind::Spotify).with_playlist(
+            PlaylistSnapshot::fixture(ProviderKind::Spotify, &["b", "a"]),
+        );
+        push_to(dir.path(), "pl1", ORIGIN, "pl1", &remote, &local, false)
+            .await
+            .unwrap();
+
+        let pushed: Vec<String> = remote
+            .playlist("pl1")
+            .unwrap()
+            .tracks
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(pushed, vec!["a", "b", "c"]);
+
+        let history = JournalEntry::history(dir.path(), "pl1").unwrap();
+        let entry = history.last().unwrap();
+        assert_eq!(entry.operation, Operation::Push);
+        assert_eq!(entry.added, 1);
+    }
+
+    fn remote_ids(remote: &FakeProvider) -> Vec<String> {
+        remote
+            .playlist("pl1")
+            .unwrap()
+            .tracks
+            .into_iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    /// A push from ["x", "a"] to ["b", "a"] interrupted after its first
+    /// step (removing "x") reached the remote but not the plan file.
+    fn interrupted_push(grit_dir: &Path) -> (PushPlan, FakeProvider) {
+        let plan = PushPlan::new(
+            ORIGIN,
+            "hash",
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["x", "a"]),
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["b", "a"]),
+            (1, 1, 1),
+        );
+        snapshot::save(
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["b", "a"]),
+            &snapshot::snapshot_path(grit_dir, "pl1"),
+        )
+        .unwrap();
+        plan.save(grit_dir, "pl1").unwrap();
+
+        let remote = FakeProvider::new(ProviderKind::Spotify)
+            .with_playlist(PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a"]));
+        (plan, remote)
+    }
+
+    #[tokio::test]
+    async fn test_push_continue_resumes_after_applied_steps() {
+        let dir = TempDir::new().unwrap();
+        let (mut plan, remote) = interrupted_push(dir.path());
+
+        let err = push(Some("pl1"), dir.path(), None, false, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("interrupted"));
+
+        resume(dir.path(), "pl1", &mut plan, &remote).await.unwrap();
+
+        assert_eq!(remote_ids(&remote), vec!["b", "a"]);
+        assert!(PushPlan::load(dir.path(), "pl1").unwrap().is_none());
+        let history = JournalEntry::history(dir.path(), "pl1").unwrap();
+        assert_eq!(history.last().unwrap().operation, Operation::Push);
+    }
+
+    #[tokio::test]
+    async fn test_push_abort_restores_remote() {
+        let dir = TempDir::new().unwrap();
+        let (plan, remote) = interrupted_push(dir.path());
+
+        abort(dir.path(), "pl1", &plan, &remote).await.unwrap();
+
+        assert_eq!(remote_ids(&remote), vec!["x", "a"]);
+        assert!(PushPlan::load(dir.path(), "pl1").unwrap().is_none());
+        assert!(JournalEntry::history(dir.path(), "pl1").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_moves_placeholder_to_created_playlist() {
+        let dir = TempDir::new().unwrap();
+        let mut local = PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b"]);
+        local.id = "new-mix".to_string();
+        local.metadata = Some(remotes::unpublished_metadata());
+        snapshot::save(&local, &snapshot::snapshot_path(dir.path(), "new-mix")).unwrap();
+        let entry = JournalEntry::new(Operation::Init, "h".to_string(), 2, 0, 0);
+        JournalEntry::record(dir.path(), "new-mix", &entry).unwrap();
+        working_playlist::save(dir.path(), "new-mix").unwrap();
+
+        let remote = FakeProvider::new(ProviderKind::Spotify);
+        let id = publish(dir.path(), &mut local, &remote).await.unwrap();
+        push_to(dir.path(), &id, ORIGIN, &id, &remote, &local, false)
+            .await
+            .unwrap();
+
+        assert!(!snapshot::snapshot_path(dir.path(), "new-mix").exists());
+        let stored = snapshot::load(&snapshot::snapshot_path(dir.path(), &id)).unwrap();
+        assert_eq!(stored.id, id);
+        assert!(remotes::has_origin(&stored));
+        assert_eq!(working_playlist::load(dir.path()).unwrap(), id);
+        assert_eq!(remote.playlist(&id).unwrap().tracks.len(), 2);
+
+        let history = JournalEntry::history(dir.path(), &id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].operation, Operation::Push);
+    }
+
+    #[test]
+    fn test_youtube_quota_budget_stops_push() {
+        let dir = TempDir::new().unwrap();
+        let plan = PushPlan::new(
+            ORIGIN,
+            "hash",
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a"]),
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b"]),
+            (1, 0, 0),
+        );
+        assert!(check_youtube_quota(dir.path(), &plan, false).is_ok());
+
+        // One insert, then the checking fetch: 50 + 3
+        let mut ledger = quota::QuotaLedger::default();
+        ledger.add(quota::DEFAULT_BUDGET - 50);
+        ledger.save(dir.path()).unwrap();
+        let err = check_youtube_quota(dir.path(), &plan, false).unwrap_err();
+        assert!(err.to_string().contains("needs about 53"));
+        assert!(check_youtube_quota(dir.path(), &plan, true).is_ok());
+    }
+
+    #[test]
+    fn test_mapping_quota_counts_uncached_searches() {
+        let dir = TempDir::new().unwrap();
+        let mirror = Remote {
+            name: "yt".to_string(),
+            provider: ProviderKind::Youtube,
+            playlist_id: "ytpl".to_string(),
+        };
+        let local = PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b"]);
+
+        let mut ledger = quota::QuotaLedger::default();
+        ledger.add(quota::DEFAULT_BUDGET - 250);
+        ledger.save(dir.path()).unwrap();
+
+        // Two searches and two inserts
+        let err = check_mapping_quota(dir.path(), &local, &mirror, false).unwrap_err();
+        assert!(err.to_string().contains("needs about 300"));
+        assert!(check_mapping_quota(dir.path(), &local, &mirror, true).is_ok());
+
+        // A cached search costs nothing
+        let mut cache = MatchCache::load(dir.path()).unwrap();
+        cache.insert(&local.tracks[0], &ProviderKind::Youtube, Vec::new());
+        cache.save().unwrap();
+        assert!(check_mapping_quota(dir.path(), &local, &mirror, false).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_push_dry_run_sends_no_subsonic_changes() {
+        use crate::provider::SubsonicProvider;
+        use crate::utils::http::{HttpClient, Settings};
+        use crate::utils::test_server::{Request, TestServer};
+
+        let mut server = TestServer::bind().await;
+        server.serve(|request: &Request| {
+            let body = match request.path.as_str() {
+                "/rest/getPlaylist" => serde_json::json!({
+                    "status": "ok",
+                    "playlist": {
+                        "id": "pl1",
+                        "name": "Mix",
+                        "owner": "alice",
+                        "entry": [{"id": "a", "title": "Song a", "artist": "Artist", "duration": 200}]
+                    }
+                }),
+                _ => serde_json::json!({"status": "ok"}),
+            };
+            (200, serde_json::json!({ "subsonic-response": body }).to_string())
+        });
+
+        let dir = TempDir::new().unwrap();
+        let mut local = PlaylistSnapshot::fixture(ProviderKind::Spotify, &["b", "a"]);
+        local.provider = ProviderKind::Subsonic;
+        snapshot::save(&local, &snapshot::snapshot_path(dir.path(), "pl1")).unwrap();
+
+        let remote = SubsonicProvider::new(&server.url, "alice", "secret").with_http(
+            HttpClient::with_settings(Settings {
+                dry_run: true,
+                ..Settings::default()
+            }),
+        );
+        push_to(dir.path(), "pl1", ORIGIN, "pl1", &remote, &local, true)
+            .await
+            .unwrap();
+
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert!(paths.contains(&"/rest/getPlaylist".to_string()));
+        assert!(!paths.contains(&"/rest/createPlaylist".to_string()));
+        assert!(PushPlan::load(dir.path(), "pl1").unwrap().is_none());
+        assert!(JournalEntry::history(dir.path(), "pl1").unwrap().is_empty());
+    }
+
+    /// Every file under `dir` with its contents.
+    fn tree(dir: &Path) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        let mut files = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir).unwrap().flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    files.push((path.clone(), std::fs::read(&path).unwrap()));
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_mirror_dry_run_writes_nothing() {
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path().join(".grit");
+        let music = temp.path().join("music");
+        std::fs::create_dir_all(&music).unwrap();
+        std::fs::write(music.join("Artist - Song a.mp3"), b"").unwrap();
+        std::fs::write(music.join("mix.m3u8"), "#EXTM3U\n").unwrap();
+
+        crate::state::config::set(
+            &crate::state::config::repo_path(&grit_dir),
+            "local.root",
+            music.to_str().unwrap(),
+        )
+        .unwrap();
+        snapshot::save(
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a"]),
+            &snapshot::snapshot_path(&grit_dir, "pl1"),
+        )
+        .unwrap();
+        remotes::save(
+            &grit_dir,
+            "pl1",
+            &[Remote {
+                name: "disk".to_string(),
+                provider: ProviderKind::Local,
+                playlist_id: "mix".to_string(),
+            }],
+        )
+        .unwrap();
+
+        let before = tree(&grit_dir);
+        push(Some("pl1"), &grit_dir, Some("disk"), false, true)
+            .await
+            .unwrap();
+        assert_eq!(tree(&grit_dir), before);
+        assert_eq!(
+            std::fs::read_to_string(music.join("mix.m3u8")).unwrap(),
+            "#EXTM3U\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_dry_run_writes_nothing() {
+        let dir = TempDir::new().unwrap();
+        let snapshot_path = snapshot::snapshot_path(dir.path(), "pl1");
+        snapshot::save(
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b"]),
+            &snapshot_path,
+        )
+        .unwrap();
+        let file = dir.path().join("mix.yaml");
+        snapshot::save(
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["b", "c"]),
+            &file,
+        )
+        .unwrap();
+        let file = file.to_str().unwrap();
+
+        apply(file, Some("pl1"), dir.path(), true).await.unwrap();
+        let current = snapshot::load(&snapshot_path).unwrap();
+        assert_eq!(current.tracks.len(), 2);
+        assert_eq!(current.tracks[0].id, "a");
+        assert!(JournalEntry::history(dir.path(), "pl1").unwrap().is_empty());
+
+        apply(file, Some("pl1"), dir.path(), false).await.unwrap();
+        let current = snapshot::load(&snapshot_path).unwrap();
+        assert_eq!(current.tracks[0].id, "b");
+    }
+
+    #[test]
+    fn test_render_log_format() {
+        let mut entry = JournalEntry::new_with_message(
+            Operation::Commit,
+            "0123456789ab".to_string(),
+            2,
+            1,
+            0,
+            "add chill vibes".to_string(),
+        );
+        entry.author = Some("alice".to_string());
+
+        let rendered = render_log_format("%h %o by %an: %s (+%a -%r ~%m) 100%%", &entry);
+        assert_eq!(
+            rendered,
+            "01234567 commit by alice: add chill vibes (+2 -1 ~0) 100%"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Spreadsheet-friendly CSV (RFC 4180) with a header row.
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+use super::{Entry, PlaylistFile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Position,
+    Id,
+    Name,
+    Artists,
+    Duration,
+    Album,
+    AddedBy,
+    AddedAt,
+    Location,
+}
+
+impl Column {
+    pub const DEFAULT: [Column; 8] = [
+        Column::Position,
+        Column::Id,
+        Column::Name,
+        Column::Artists,
+        Column::Duration,
+        Column::Album,
+        Column::AddedBy,
+        Column::AddedAt,
+    ];
+
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Position => "position",
+            Column::Id => "id",
+            Column::Name => "name",
+            Column::Artists => "artists",
+            Column::Duration => "duration",
+            Column::Album => "album",
+            Column::AddedBy => "added_by",
+            Column::AddedAt => "added_at",
+            Column::Location => "location",
+        }
+    }
+
+    /// Recognize a header cell, including common spreadsheet spellings.
+    fn from_header(header: &str) -> Option<Self> {
+        let normalized = header.trim().to_lowercase().replace([' ', '-'], "_");
+        match normalized.as_str() {
+            "position" | "#" | "pos" => Some(Column::Position),
+            "id" | "track_id" => Some(Column::Id),
+            "name" | "title" | "track" | "track_name" => Some(Column::Name),
+            "artists" | "artist" | "artist_name" | "artist_name(s)" => Some(Column::Artists),
+            "duration" | "duration_ms" | "track_duration_(ms)" | "length" | "time" => {
+                Some(Column::Duration)
+            }
+            "album" | "album_name" => Some(Column::Album),
+            "added_by" => Some(Column::AddedBy),
+            "added_at" | "date_added" => Some(Column::AddedAt),
+            "location" | "url" | "uri" | "track_uri" => Some(Column::Location),
+            _ => None,
+        }
+    }
+}
+
+pub fn render(playlist: &PlaylistFile, columns: &[Column]) -> String {
+    let columns = if columns.is_empty() {
+        &Column::DEFAULT[..]
+    } else {
+        columns
+    };
+
+    let mut out = String::new();
+    let header: Vec<&str> = columns.iter().map(|c| c.header()).collect();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+
+    for (position, entry) in playlist.entries.iter().enumerate() {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| {
+                let value = match column {
+                    Column::Position => Some((position + 1).to_string()),
+                    Column::Id => entry.id.clone(),
+                    Column::Name => entry.title.clone(),
+                    Column::Artists => entry.artist.clone(),
+                    Column::Duration => entry.duration_ms.map(format_duration),
+                    Column::Album => entry.album.clone(),
+                    Column::AddedBy => entry.added_by.clone(),
+                    Column::AddedAt => entry.added_at.clone(),
+                    Column::Location => entry.location.clone(),
+                };
+                quote(value.as_deref().unwrap_or_default())
+            })
+            .collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+
+    out
+}
+
+/// Parse CSV with a header row. Unknown columns are ignored; rows are
+/// ordered by the position column when there is one.
+pub fn parse(content: &str) -> Result<PlaylistFile> {
+    let mut rows = parse_rows(content)?.into_iter();
+
+    let Some(header) = rows.next() else {
+        return Ok(PlaylistFile::default());
+    };
+    let columns: Vec<Option<Column>> = header.iter().map(|h| Column::from_header(h)).collect();
+
+    if !columns
+        .iter()
+        .any(|c| matches!(c, Some(Column::Id | Column::Name | Column::Location)))
+    {
+        bail!("CSV needs an id, name or location column");
+    }
+
+    let mut positioned = Vec::new();
+    for (row_index, row) in rows.enumerate() {
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+
+        let mut entry = Entry::default();
+        let mut position = None;
+
+        for (column, cell) in columns.iter().zip(&row) {
+            let value = cell.trim();
+            if value.is_empty() {
+                continue;
+            }
+            let text = Some(value.to_string());
+            match column {
+                Some(Column::Position) => position = value.parse::<usize>().ok(),
+                Some(Column::Id) => entry.id = text,
+                Some(Column::Name) => entry.title = text,
+                Some(Column::Artists) => entry.artist = text,
+                Some(Column::Duration) => entry.duration_ms = parse_duration(value),
+                Some(Column::Album) => entry.album = text,
+                Some(Column::AddedBy) => entry.added_by = text,
+                Some(Column::AddedAt) => entry.added_at = text,
+                Some(Column::Location) => entry.location = text,
+                None => {}
+            }
+        }
+
+        positioned.push((position.unwrap_or(usize::MAX), row_index, entry));
+    }
+
+    positioned.sort_by_key(|(position, row, _)| (*position, *row));
+
+    Ok(PlaylistFile {
+        title: None,
+        entries: positioned.into_iter().map(|(_, _, entry)| entry).collect(),
+    })
+}
+
+fn quote(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Split CSV into rows of cells, handling quoted fields.
+pub fn parse_rows(content: &str) -> Result<Vec<Vec<String>>> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    cell.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => cell.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut cell)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => cell.push(c),
+        }
+    }
+
+    if in_quotes {
+        bail!("Unterminated quoted field in CSV");
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// "m:ss" or "h:mm:ss", or a plain number of milliseconds.
+fn parse_duration(value: &str) -> Option<u64> {
+    if !value.contains(':') {
+        return value.parse().ok();
+    }
+
+    value
+        .split(':')
+        .try_fold(0u64, |acc, part| {
+            Some(acc * 60 + part.trim().parse::<u64>().ok()?)
+        })
+        .map(|secs| secs * 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let playlist = PlaylistFile {
+            title: None,
+            entries: vec![
+                Entry {
+                    id: Some("abc".to_string()),
+                    title: Some("Hello, \"World\"".to_string()),
+                    artist: Some("A; B".to_string()),
+                    duration_ms: Some(185_000),
+                    added_by: Some("ana".to_string()),
+                    ..Entry::default()
+                },
+                Entry {
+                    id: Some("def".to_string()),
+                    title: Some("Line\nbreak".to_string()),
+                    ..Entry::default()
+                },
+            ],
+        };
+
+        let rendered = render(&playlist, &Column::DEFAULT);
+        assert!(
+            rendered.starts_with("position,id,name,artists,duration,album,added_by,added_at\r\n")
+        );
+        assert!(rendered.contains("1,abc,\"Hello, \"\"World\"\"\",A; B,3:05,,ana,\r\n"));
+        assert_eq!(parse(&rendered).unwrap(), playlist);
+    }
+
+    #[test]
+    fn test_parse_reorders_by_position_and_ignores_unknown_columns() {
+        let csv = "Title,Artist,Rating,#\nSecond,B,5,2\nFirst,A,3,1\n";
+        let parsed = parse(csv).unwrap();
+        let titles: Vec<_> = parsed
+            .entries
+            .iter()
+            .map(|e| e.title.as_deref().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["First", "Second"]);
+        assert_eq!(parsed.entries[0].artist.as_deref(), Some("A"));
+    }
+
+    #[test]
+    fn test_requires_identifying_column() {
+        assert!(parse("artist,duration\nA,3:00\n").is_err());
+    }
+}
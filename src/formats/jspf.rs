@@ -0,0 +1,209 @@
+//! JSPF, the JSON form of XSPF used by ListenBrainz.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{Entry, PlaylistFile};
+
+/// Extension key ListenBrainz uses for per-track details.
+const TRACK_EXTENSION: &str = "https://musicbrainz.org/doc/jspf#track";
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    playlist: Playlist,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Playlist {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default)]
+    track: Vec<JspfTrack>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct JspfTrack {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    album: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+    #[serde(default, skip_serializing_if = "OneOrMany::is_empty")]
+    identifier: OneOrMany,
+    #[serde(default, skip_serializing_if = "OneOrMany::is_empty")]
+    location: OneOrMany,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    extension: serde_json::Map<String, serde_json::Value>,
+}
+
+/// JSPF allows a single string or a list for `identifier` and `location`.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(untagged)]
+enum OneOrMany {
+    #[default]
+    None,
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn is_empty(&self) -> bool {
+        self.first().is_none()
+    }
+
+    fn first(&self) -> Option<&str> {
+        match self {
+            OneOrMany::None => None,
+            OneOrMany::One(value) => Some(value),
+            OneOrMany::Many(values) => values.first().map(String::as_str),
+        }
+    }
+}
+
+pub fn render(playlist: &PlaylistFile) -> Result<String> {
+    let tracks = playlist
+        .entries
+        .iter()
+        .map(|entry| {
+            let mut details = serde_json::Map::new();
+            if let Some(added_by) = &entry.added_by {
+                details.insert("added_by".to_string(), added_by.clone().into());
+            }
+            if let Some(added_at) = &entry.added_at {
+                details.insert("added_at".to_string(), added_at.clone().into());
+            }
+            if let Some(id) = &entry.id {
+                details.insert(
+                    "additional_metadata".to_string(),
+                    serde_json::json!({ "grit_id": id }),
+                );
+            }
+
+            let mut extension = serde_json::Map::new();
+            if !details.is_empty() {
+                extension.insert(TRACK_EXTENSION.to_string(), details.into());
+            }
+
+            JspfTrack {
+                title: entry.title.clone(),
+                creator: entry.artist.clone(),
+                album: entry.album.clone(),
+                duration: entry.duration_ms,
+                identifier: entry
+                    .location
+                    .clone()
+                    .map_or(OneOrMany::None, |l| OneOrMany::Many(vec![l])),
+                location: OneOrMany::None,
+                extension,
+            }
+        })
+        .collect();
+
+    let document = Document {
+        playlist: Playlist {
+            title: playlist.title.clone(),
+            track: tracks,
+        },
+    };
+    serde_json::to_string_pretty(&document).context("Failed to serialize JSPF")
+}
+
+pub fn parse(content: &str) -> Result<PlaylistFile> {
+    let document: Document = serde_json::from_str(content).context("Failed to parse JSPF")?;
+
+    let entries = document
+        .playlist
+        .track
+        .into_iter()
+        .map(|track| {
+            let details = track.extension.get(TRACK_EXTENSION);
+            let detail = |key: &str| {
+                details
+                    .and_then(|d| d.get(key))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+
+            Entry {
+                id: details
+                    .and_then(|d| d.pointer("/additional_metadata/grit_id"))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                location: track
+                    .location
+                    .first()
+                    .or(track.identifier.first())
+                    .map(str::to_string),
+                title: track.title,
+                artist: track.creator,
+                album: track.album,
+                duration_ms: track.duration,
+                added_by: detail("added_by"),
+                added_at: detail("added_at"),
+            }
+        })
+        .collect();
+
+    Ok(PlaylistFile {
+        title: document.playlist.title,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let playlist = PlaylistFile {
+            title: Some("Mix".to_string()),
+            entries: vec![Entry {
+                id: Some("abc".to_string()),
+                location: Some("spotify:track:abc".to_string()),
+                title: Some("Song".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                duration_ms: Some(200_000),
+                added_by: Some("ana".to_string()),
+                added_at: Some("2024-05-01T10:00:00+00:00".to_string()),
+            }],
+        };
+
+        let rendered = render(&playlist).unwrap();
+        assert_eq!(parse(&rendered).unwrap(), playlist);
+    }
+
+    #[test]
+    fn test_parse_listenbrainz_export() {
+        let json = r#"{
+            "playlist": {
+                "title": "Weekly Jams",
+                "track": [{
+                    "title": "Blue Monday",
+                    "creator": "New Order",
+                    "identifier": "https://musicbrainz.org/recording/6b3d1f4e",
+                    "extension": {
+                        "https://musicbrainz.org/doc/jspf#track": {
+                            "added_by": "listenbrainz",
+                            "added_at": "2024-01-01T00:00:00+00:00"
+                        }
+                    }
+                }]
+            }
+        }"#;
+
+        let parsed = parse(json).unwrap();
+        assert_eq!(parsed.title.as_deref(), Some("Weekly Jams"));
+        let entry = &parsed.entries[0];
+        assert_eq!(
+            entry.location.as_deref(),
+            Some("https://musicbrainz.org/recording/6b3d1f4e")
+        );
+        assert_eq!(entry.added_by.as_deref(), Some("listenbrainz"));
+        assert!(entry.id.is_none());
+    }
+}
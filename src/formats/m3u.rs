@@ -0,0 +1,116 @@
+use super::{Entry, PlaylistFile};
+
+/// Parse an extended M3U playlist. `#EXTINF` lines supply the duration and
+/// "Artist - Title" of the entry that follows them.
+pub fn parse(content: &str) -> PlaylistFile {
+    let mut playlist = PlaylistFile::default();
+    let mut pending = Entry::default();
+
+    for line in content.lines() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, display) = info.split_once(',').unwrap_or((info, ""));
+            // Attributes may follow the duration: #EXTINF:123 tvg-id="x",Title
+            pending.duration_ms = duration
+                .split_whitespace()
+                .next()
+                .and_then(|d| d.parse::<i64>().ok())
+                .and_then(|d| u64::try_from(d).ok())
+                .map(|secs| secs * 1000);
+
+            let display = display.trim();
+            match display.split_once(" - ") {
+                Some((artist, title)) => {
+                    pending.artist = Some(artist.trim().to_string());
+                    pending.title = Some(title.trim().to_string());
+                }
+                None if !display.is_empty() => pending.title = Some(display.to_string()),
+                None => {}
+            }
+        } else if let Some(title) = line.strip_prefix("#PLAYLIST:") {
+            playlist.title = Some(title.trim().to_string());
+        } else if !line.starts_with('#') {
+            let mut entry = std::mem::take(&mut pending);
+            entry.location = Some(line.to_string());
+            playlist.entries.push(entry);
+        }
+    }
+
+    playlist
+}
+
+/// Render an extended M3U playlist. Entries without a location are skipped,
+/// since M3U has no way to express them.
+pub fn render(playlist: &PlaylistFile) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    if let Some(title) = &playlist.title {
+        out.push_str(&format!("#PLAYLIST:{}\n", title));
+    }
+
+    for entry in &playlist.entries {
+        let Some(location) = &entry.location else {
+            continue;
+        };
+
+        let display = match (&entry.artist, &entry.title) {
+            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
+            (None, Some(title)) => title.clone(),
+            (Some(artist), None) => artist.clone(),
+            (None, None) => String::new(),
+        };
+        let duration = entry
+            .duration_ms
+            .map(|ms| (ms / 1000).to_string())
+            .unwrap_or_else(|| "-1".to_string());
+
+        out.push_str(&format!("#EXTINF:{},{}\n{}\n", duration, display, location));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let playlist = PlaylistFile {
+            title: Some("Mix".to_string()),
+            entries: vec![
+                Entry {
+                    location: Some("https://www.youtube.com/watch?v=abc".to_string()),
+                    title: Some("Song".to_string()),
+                    artist: Some("Artist".to_string()),
+                    duration_ms: Some(200_000),
+                    ..Entry::default()
+                },
+                Entry {
+                    location: Some("music/untagged.mp3".to_string()),
+                    ..Entry::default()
+                },
+            ],
+        };
+
+        let rendered = render(&playlist);
+        assert!(rendered.contains("#EXTINF:200,Artist - Song\n"));
+        assert!(rendered.contains("#EXTINF:-1,\nmusic/untagged.mp3\n"));
+        assert_eq!(parse(&rendered), playlist);
+    }
+
+    #[test]
+    fn test_parse_plain_m3u() {
+        let parsed = parse("# comment\n/music/a.mp3\r\n\n/music/b.flac\n");
+        let locations: Vec<_> = parsed
+            .entries
+            .iter()
+            .map(|e| e.location.as_deref().unwrap())
+            .collect();
+        assert_eq!(locations, vec!["/music/a.mp3", "/music/b.flac"]);
+        assert!(parsed.entries[0].title.is_none());
+    }
+}
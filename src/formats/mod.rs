@@ -0,0 +1,125 @@
+//! Playlist file formats understood by other players.
+
+pub mod csv;
+pub mod jspf;
+pub mod m3u;
+pub mod spotify_export;
+pub mod takeout;
+pub mod xspf;
+
+use std::path::Path;
+
+use anyhow::Result;
+use clap::ValueEnum;
+
+use crate::provider::Track;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    #[value(alias = "m3u")]
+    M3u8,
+    Xspf,
+    Csv,
+    #[value(alias = "json")]
+    Jspf,
+}
+
+impl Format {
+    /// Guess the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "m3u" | "m3u8" => Some(Format::M3u8),
+            "xspf" => Some(Format::Xspf),
+            "csv" => Some(Format::Csv),
+            "jspf" | "json" => Some(Format::Jspf),
+            _ => None,
+        }
+    }
+
+    pub fn parse(self, content: &str) -> Result<PlaylistFile> {
+        match self {
+            Format::M3u8 => Ok(m3u::parse(content)),
+            Format::Xspf => xspf::parse(content),
+            Format::Csv => csv::parse(content),
+            Format::Jspf => jspf::parse(content),
+        }
+    }
+
+    /// Render `playlist`. `columns` only applies to CSV.
+    pub fn render(self, playlist: &PlaylistFile, columns: &[csv::Column]) -> Result<String> {
+        match self {
+            Format::M3u8 => Ok(m3u::render(playlist)),
+            Format::Xspf => Ok(xspf::render(playlist)),
+            Format::Csv => Ok(csv::render(playlist, columns)),
+            Format::Jspf => jspf::render(playlist),
+        }
+    }
+}
+
+/// A playlist as other players see it: a title and a list of entries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistFile {
+    pub title: Option<String>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entry {
+    /// Track ID on the playlist's provider
+    pub id: Option<String>,
+    /// File path or URL
+    pub location: Option<String>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub added_by: Option<String>,
+    /// RFC 3339 timestamp
+    pub added_at: Option<String>,
+}
+
+impl Entry {
+    pub fn from_track(track: &Track, location: Option<String>) -> Self {
+        let album = track
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("album"))
+            .and_then(|a| a.as_str())
+            .map(str::to_string);
+
+        Self {
+            id: Some(track.id.clone()),
+            location,
+            title: Some(track.name.clone()),
+            artist: Some(track.artists.join(", ")).filter(|a| !a.is_empty()),
+            album,
+            duration_ms: Some(track.duration_ms).filter(|d| *d > 0),
+            ..Self::default()
+        }
+    }
+}
+
+/// A playlist read from a provider's account data export.
+#[derive(Debug, Clone)]
+pub struct ExportedPlaylist {
+    /// Provider playlist ID, when the export has it
+    pub id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub tracks: Vec<Track>,
+    /// Items that aren't provider tracks (podcast episodes, local files)
+    pub skipped: usize,
+}
+
+/// Lowercase words joined by dashes, for IDs and file names.
+pub fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
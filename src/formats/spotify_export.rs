@@ -0,0 +1,137 @@
+//! `Playlist1.json` from Spotify's "Download your data" account export.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::ExportedPlaylist;
+use crate::provider::{ProviderKind, Track};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    #[serde(default)]
+    playlists: Vec<Playlist>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Playlist {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    items: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    #[serde(default)]
+    track: Option<ItemTrack>,
+    #[serde(default)]
+    added_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemTrack {
+    track_name: String,
+    #[serde(default)]
+    artist_name: Option<String>,
+    #[serde(default)]
+    album_name: Option<String>,
+    track_uri: String,
+}
+
+/// Parse an account export. The export has track URIs, names and albums
+/// but no durations and no playlist IDs.
+pub fn parse(content: &str) -> Result<Vec<ExportedPlaylist>> {
+    let document: Document =
+        serde_json::from_str(content).context("Failed to parse Spotify account export")?;
+
+    Ok(document
+        .playlists
+        .into_iter()
+        .map(|playlist| {
+            let total = playlist.items.len();
+            let tracks: Vec<Track> = playlist
+                .items
+                .into_iter()
+                .filter_map(|item| {
+                    let track = item.track?;
+                    let id = track.track_uri.strip_prefix("spotify:track:")?.to_string();
+
+                    let mut metadata = serde_json::json!({ "incomplete": true });
+                    if let Some(album) = track.album_name {
+                        metadata["album"] = album.into();
+                    }
+                    if let Some(added) = item.added_date {
+                        metadata["added_at"] = added.into();
+                    }
+
+                    Some(Track {
+                        id,
+                        name: track.track_name,
+                        artists: track.artist_name.into_iter().collect(),
+                        duration_ms: 0,
+                        provider: ProviderKind::Spotify,
+                        metadata: Some(metadata),
+                    })
+                })
+                .collect();
+
+            ExportedPlaylist {
+                id: None,
+                name: playlist.name,
+                description: playlist.description.filter(|d| !d.is_empty()),
+                skipped: total - tracks.len(),
+                tracks,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_export() {
+        let json = r#"{
+          "playlists" : [ {
+            "name" : "Chill",
+            "lastModifiedDate" : "2023-01-02",
+            "items" : [ {
+              "track" : {
+                "trackName" : "Teardrop",
+                "artistName" : "Massive Attack",
+                "albumName" : "Mezzanine",
+                "trackUri" : "spotify:track:67Hna13dNDkZvBpTXRIaOJ"
+              },
+              "episode" : null,
+              "localTrack" : null,
+              "addedDate" : "2023-01-01"
+            }, {
+              "track" : null,
+              "episode" : { "episodeName" : "Ep 1" },
+              "localTrack" : null,
+              "addedDate" : "2023-01-01"
+            } ],
+            "description" : null,
+            "numberOfFollowers" : 0
+          } ]
+        }"#;
+
+        let playlists = parse(json).unwrap();
+        assert_eq!(playlists.len(), 1);
+        let chill = &playlists[0];
+        assert_eq!(chill.name, "Chill");
+        assert!(chill.id.is_none());
+        assert_eq!(chill.skipped, 1);
+        assert_eq!(chill.tracks[0].id, "67Hna13dNDkZvBpTXRIaOJ");
+        assert_eq!(chill.tracks[0].artists, vec!["Massive Attack"]);
+        let metadata = chill.tracks[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata["album"], "Mezzanine");
+        assert_eq!(metadata["incomplete"], true);
+    }
+}
@@ -0,0 +1,199 @@
+//! YouTube playlist CSVs from Google Takeout.
+//!
+//! Older exports have one `<title>.csv` per playlist holding both the
+//! playlist details and its videos. Newer ones have a `playlists.csv`
+//! index plus a `<title>-videos.csv` per playlist.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use super::{csv::parse_rows, ExportedPlaylist};
+use crate::provider::{ProviderKind, Track};
+
+/// Read every playlist CSV in `dir`. Files that aren't Takeout playlist
+/// CSVs are ignored.
+pub fn parse_dir(dir: &Path) -> Result<Vec<ExportedPlaylist>> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {:?}", dir))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+        })
+        .collect();
+    files.sort();
+
+    let read = |path: &Path| -> Result<Vec<Vec<String>>> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        parse_rows(&content)
+    };
+
+    let index_path = dir.join("playlists.csv");
+    let index = if index_path.exists() {
+        parse_index(&read(&index_path)?)
+    } else {
+        HashMap::new()
+    };
+
+    let mut playlists = Vec::new();
+    for path in files.iter().filter(|p| **p != index_path) {
+        let rows = read(path)?;
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        if let Some(playlist) = parse_playlist(&rows, &stem, &index) {
+            playlists.push(playlist);
+        }
+    }
+
+    Ok(playlists)
+}
+
+/// Titles to playlist IDs, from `playlists.csv`.
+fn parse_index(rows: &[Vec<String>]) -> HashMap<String, String> {
+    let Some((header, rows)) = rows.split_first() else {
+        return HashMap::new();
+    };
+    let id_col = column(header, &["playlist id"]);
+    let title_col = column(header, &["playlist title (original)", "title"]);
+
+    let (Some(id_col), Some(title_col)) = (id_col, title_col) else {
+        return HashMap::new();
+    };
+    rows.iter()
+        .filter_map(|row| {
+            let id = row.get(id_col)?.trim();
+            let title = row.get(title_col)?.trim();
+            (!id.is_empty()).then(|| (title.to_string(), id.to_string()))
+        })
+        .collect()
+}
+
+fn parse_playlist(
+    rows: &[Vec<String>],
+    stem: &str,
+    index: &HashMap<String, String>,
+) -> Option<ExportedPlaylist> {
+    let mut rows = rows
+        .iter()
+        .filter(|row| row.iter().any(|c| !c.trim().is_empty()));
+    let first = rows.next()?;
+
+    let (id, name, description, header) = if column(first, &["video id"]).is_some() {
+        // Newer format: only videos, named after the playlist
+        let title = stem.strip_suffix("-videos").unwrap_or(stem).to_string();
+        (index.get(&title).cloned(), title, None, first)
+    } else {
+        // Older format: a playlist details block, then the videos
+        let id_col = column(first, &["playlist id"])?;
+        let details = rows.next()?;
+        let cell = |names: &[&str]| {
+            column(first, names)
+                .and_then(|i| details.get(i))
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+        };
+        let id = details.get(id_col).map(|c| c.trim().to_string());
+        let name = cell(&["title"]).unwrap_or_else(|| stem.to_string());
+        (id, name, cell(&["description"]), rows.next()?)
+    };
+
+    let video_col = column(header, &["video id"])?;
+    let added_col = column(header, &["playlist video creation timestamp", "time added"]);
+
+    let tracks = rows
+        .filter_map(|row| {
+            let video_id = row.get(video_col)?.trim();
+            if video_id.is_empty() {
+                return None;
+            }
+
+            let mut metadata = serde_json::json!({ "incomplete": true });
+            if let Some(added) = added_col.and_then(|i| row.get(i)) {
+                metadata["added_at"] = added.trim().into();
+            }
+
+            // Takeout only has video IDs; names come from 'grit enrich'
+            Some(Track {
+                id: video_id.to_string(),
+                name: video_id.to_string(),
+                artists: Vec::new(),
+                duration_ms: 0,
+                provider: ProviderKind::Youtube,
+                metadata: Some(metadata),
+            })
+        })
+        .collect();
+
+    Some(ExportedPlaylist {
+        id,
+        name,
+        description,
+        tracks,
+        skipped: 0,
+    })
+}
+
+fn column(header: &[String], names: &[&str]) -> Option<usize> {
+    header
+        .iter()
+        .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_old_and_new_formats() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+
+        fs::write(
+            dir.join("Road Trip.csv"),
+            "Playlist Id,Channel Id,Time Created,Time Updated,Title,Description,Visibility\n\
+             PLold,UC1,2020-01-01 00:00:00 UTC,,Road Trip,Songs for driving,Private\n\
+             \n\
+             Video Id,Time Added\n\
+             vid1   ,2020-01-02 00:00:00 UTC\n\
+             vid2,2020-01-03 00:00:00 UTC\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("playlists.csv"),
+            "Playlist ID,Add new videos to top,Playlist Title (Original),Playlist Visibility\n\
+             PLnew,False,Focus,Private\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("Focus-videos.csv"),
+            "Video ID,Playlist Video Creation Timestamp\nvid3,2023-05-01T10:00:00+00:00\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("subscriptions.csv"),
+            "Channel Id,Channel Url\nUC1,x\n",
+        )
+        .unwrap();
+
+        let playlists = parse_dir(dir).unwrap();
+        assert_eq!(playlists.len(), 2);
+
+        let focus = &playlists[0];
+        assert_eq!(focus.id.as_deref(), Some("PLnew"));
+        assert_eq!(focus.name, "Focus");
+        assert_eq!(focus.tracks[0].id, "vid3");
+
+        let road_trip = &playlists[1];
+        assert_eq!(road_trip.id.as_deref(), Some("PLold"));
+        assert_eq!(road_trip.description.as_deref(), Some("Songs for driving"));
+        let ids: Vec<_> = road_trip.tracks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["vid1", "vid2"]);
+    }
+}
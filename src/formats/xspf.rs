@@ -0,0 +1,210 @@
+//! XSPF ("spiff"), the XML Shareable Playlist Format.
+
+use anyhow::{bail, Result};
+
+use super::{Entry, PlaylistFile};
+
+pub fn render(playlist: &PlaylistFile) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n",
+    );
+    if let Some(title) = &playlist.title {
+        out.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    }
+
+    out.push_str("  <trackList>\n");
+    for entry in &playlist.entries {
+        out.push_str("    <track>\n");
+        if let Some(location) = &entry.location {
+            out.push_str(&format!(
+                "      <location>{}</location>\n",
+                escape(location)
+            ));
+        }
+        if let Some(title) = &entry.title {
+            out.push_str(&format!("      <title>{}</title>\n", escape(title)));
+        }
+        if let Some(artist) = &entry.artist {
+            out.push_str(&format!("      <creator>{}</creator>\n", escape(artist)));
+        }
+        if let Some(album) = &entry.album {
+            out.push_str(&format!("      <album>{}</album>\n", escape(album)));
+        }
+        if let Some(duration) = entry.duration_ms {
+            out.push_str(&format!("      <duration>{}</duration>\n", duration));
+        }
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+
+    out
+}
+
+/// Parse the parts of XSPF grit uses. This is not a general XML parser:
+/// it reads the playlist title and each track's location, title, creator,
+/// album and duration.
+pub fn parse(content: &str) -> Result<PlaylistFile> {
+    let Some(list_start) = content.find("<trackList") else {
+        bail!("Not an XSPF playlist (no <trackList>)");
+    };
+
+    let title = element_text(&content[..list_start], "title");
+
+    let mut entries = Vec::new();
+    let mut rest = &content[list_start..];
+    while let Some(start) = find_open_tag(rest, "track") {
+        let Some(end) = rest[start..].find("</track>") else {
+            bail!("Unterminated <track> element");
+        };
+        let block = &rest[start..start + end];
+
+        entries.push(Entry {
+            // Only the first location is used; XSPF allows alternatives
+            location: element_text(block, "location"),
+            title: element_text(block, "title"),
+            artist: element_text(block, "creator"),
+            album: element_text(block, "album"),
+            duration_ms: element_text(block, "duration").and_then(|d| d.parse().ok()),
+            ..Entry::default()
+        });
+
+        rest = &rest[start + end + "</track>".len()..];
+    }
+
+    Ok(PlaylistFile { title, entries })
+}
+
+/// Position of `<name>` or `<name ...>` (but not `<nameOther>`).
+fn find_open_tag(text: &str, name: &str) -> Option<usize> {
+    let needle = format!("<{}", name);
+    let mut offset = 0;
+    while let Some(pos) = text[offset..].find(&needle) {
+        let at = offset + pos;
+        match text[at + needle.len()..].chars().next() {
+            Some('>') | Some(' ') | Some('\t') | Some('\n') | Some('\r') => return Some(at),
+            _ => offset = at + needle.len(),
+        }
+    }
+    None
+}
+
+fn element_text(text: &str, name: &str) -> Option<String> {
+    let start = find_open_tag(text, name)?;
+    let content_start = start + text[start..].find('>')? + 1;
+    let content_len = text[content_start..].find(&format!("</{}>", name))?;
+    let value = unescape(text[content_start..content_start + content_len].trim());
+    (!value.is_empty()).then_some(value)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape(text: &str) -> String {
+    if let Some(cdata) = text
+        .strip_prefix("<![CDATA[")
+        .and_then(|t| t.strip_suffix("]]>"))
+    {
+        return cdata.to_string();
+    }
+    unescape_entities(text)
+}
+
+fn unescape_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let Some(semi) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_with_escaping() {
+        let playlist = PlaylistFile {
+            title: Some("Rock & Roll <3".to_string()),
+            entries: vec![
+                Entry {
+                    location: Some("spotify:track:abc".to_string()),
+                    title: Some("Don't Stop".to_string()),
+                    artist: Some("Fleetwood Mac".to_string()),
+                    album: Some("Rumours".to_string()),
+                    duration_ms: Some(193_000),
+                    ..Entry::default()
+                },
+                Entry {
+                    title: Some("No location".to_string()),
+                    ..Entry::default()
+                },
+            ],
+        };
+
+        let rendered = render(&playlist);
+        assert!(rendered.contains("<title>Rock &amp; Roll &lt;3</title>"));
+        assert_eq!(parse(&rendered).unwrap(), playlist);
+    }
+
+    #[test]
+    fn test_parse_foreign_xspf() {
+        let xml = r#"<?xml version="1.0"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <trackList>
+    <track><location>file:///m/a.mp3</location><title>Caf&#233;</title><trackNum>1</trackNum></track>
+    <track>
+      <location><![CDATA[http://x/?a=1&b=2]]></location>
+      <duration>1000</duration>
+    </track>
+  </trackList>
+</playlist>"#;
+
+        let parsed = parse(xml).unwrap();
+        assert!(parsed.title.is_none());
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.entries[0].title.as_deref(), Some("Café"));
+        assert_eq!(
+            parsed.entries[1].location.as_deref(),
+            Some("http://x/?a=1&b=2")
+        );
+        assert_eq!(parsed.entries[1].duration_ms, Some(1000));
+    }
+}
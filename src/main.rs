@@ -1,4 +1,6 @@
 mod cli;
+mod formats;
+mod matching;
 mod playback;
 mod provider;
 mod state;
@@ -6,9 +8,10 @@ mod tui;
 mod utils;
 
 use anyhow::Context;
-use clap::Parser;
-use cli::{Cli, Commands};
+use clap::{CommandFactory, Parser};
+use cli::{BundleAction, Cli, Commands, ConfigAction, RemoteAction};
 use provider::ProviderKind;
+use state::config::Config;
 use std::path::{Path, PathBuf};
 
 #[tokio::main]
@@ -16,9 +19,48 @@ async fn main() -> anyhow::Result<()> {
     // Load .env file if present (ignores if missing)
     let _ = dotenvy::dotenv();
 
-    let cli = Cli::parse();
     let grit_dir = PathBuf::from(".grit");
 
+    // Aliases come from whatever config can be read, so a broken setting
+    // doesn't stop --help or `grit config` from working
+    let aliases = Config::load(&grit_dir, None).unwrap_or_default();
+    let cli = Cli::parse_from(expand_alias(std::env::args().collect(), &aliases)?);
+
+    let config = match cli.command {
+        Commands::Config { .. } => Config::default(),
+        _ => Config::load(&grit_dir, None)?,
+    };
+
+    if let Some(name) = config.get_str("user.name") {
+        state::journal::set_configured_author(name);
+    }
+    let http = utils::http::Settings::default();
+    utils::http::configure(utils::http::Settings {
+        max_retries: config
+            .get_u64("http.max_retries")?
+            .map(u32::try_from)
+            .transpose()
+            .context("Config key 'http.max_retries' is too large")?
+            .unwrap_or(http.max_retries),
+        max_requests: config
+            .get_u64("http.max_requests")?
+            .map(usize::try_from)
+            .transpose()
+            .context("Config key 'http.max_requests' is too large")?
+            .unwrap_or(http.max_requests),
+        verbose: cli.verbose,
+        // Providers run their real apply; requests that change something are printed
+        dry_run: matches!(cli.command, Commands::Push { dry_run: true, .. }),
+        ..http
+    });
+    let default_provider = config
+        .get_str("core.provider")
+        .map(|p| {
+            p.parse::<ProviderKind>()
+                .map_err(|_| anyhow::anyhow!("Invalid core.provider '{}' in config", p))
+        })
+        .transpose()?;
+
     match cli.command {
         Commands::Auth { provider } => {
             cli::commands::auth::run(provider, &grit_dir).await?;
@@ -27,9 +69,21 @@ async fn main() -> anyhow::Result<()> {
             let provider = provider
                 .or(cli.provider)
                 .or_else(|| cli::commands::init::detect_provider(&playlist))
+                .or(default_provider)
                 .unwrap_or(ProviderKind::Spotify);
             cli::commands::init::run(provider, &playlist, &grit_dir).await?;
         }
+        Commands::New {
+            name,
+            provider,
+            from,
+        } => {
+            let provider = provider
+                .or(cli.provider)
+                .or(default_provider)
+                .unwrap_or(ProviderKind::Spotify);
+            cli::commands::new::run(&name, provider, from.as_deref(), &grit_dir).await?;
+        }
         Commands::Search { query, add } => {
             let playlist = if add {
                 Some(resolve_playlist(None, cli.playlist.clone(), &grit_dir)?)
@@ -38,10 +92,11 @@ async fn main() -> anyhow::Result<()> {
             };
             cli::commands::staging::search(
                 &query,
-                cli.provider,
+                cli.provider.or(default_provider),
                 &grit_dir,
                 add,
                 playlist.as_deref(),
+                cli.json,
             )
             .await?;
         }
@@ -63,7 +118,7 @@ async fn main() -> anyhow::Result<()> {
         }
         Commands::Status { playlist } => {
             let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
-            cli::commands::staging::status(Some(&playlist), &grit_dir).await?;
+            cli::commands::staging::status(Some(&playlist), &grit_dir, cli.json).await?;
         }
         Commands::Reset { playlist } => {
             let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
@@ -71,54 +126,225 @@ async fn main() -> anyhow::Result<()> {
         }
         Commands::List { playlist } => {
             let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
-            cli::commands::misc::list(Some(&playlist), &grit_dir).await?;
+            cli::commands::misc::list(Some(&playlist), &grit_dir, cli.json).await?;
         }
         Commands::Find { query, playlist } => {
             let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
-            cli::commands::misc::find(&query, Some(&playlist), &grit_dir).await?;
+            cli::commands::misc::find(&query, Some(&playlist), &grit_dir, cli.json).await?;
         }
         Commands::Logout { provider } => {
             cli::commands::auth::logout(provider, &grit_dir).await?;
         }
         Commands::Whoami { provider } => {
-            cli::commands::auth::whoami(provider, &grit_dir).await?;
+            cli::commands::auth::whoami(provider, &grit_dir, cli.json).await?;
         }
         Commands::Commit { message } => {
             let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
             cli::commands::staging::commit(&message, Some(&playlist), &grit_dir).await?;
         }
-        Commands::Push { playlist } => {
+        Commands::Push {
+            remote,
+            all,
+            resume,
+            abort,
+            dry_run,
+            playlist,
+        } => {
+            let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
+            if resume {
+                cli::commands::vcs::push_continue(Some(&playlist), &grit_dir).await?;
+            } else if abort {
+                cli::commands::vcs::push_abort(Some(&playlist), &grit_dir).await?;
+            } else {
+                cli::commands::vcs::push(
+                    Some(&playlist),
+                    &grit_dir,
+                    remote.as_deref(),
+                    all,
+                    dry_run,
+                )
+                .await?;
+            }
+        }
+        Commands::ImportExport { path } => {
+            cli::commands::library::import_export(&path, &grit_dir).await?;
+        }
+        Commands::Enrich { playlist } => {
+            let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
+            cli::commands::library::enrich(Some(&playlist), &grit_dir).await?;
+        }
+        Commands::Convert {
+            playlist,
+            to,
+            into,
+            review,
+        } => {
+            let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
+            let into = into
+                .map(|id| cli::commands::init::resolve_id(&to, &id, &grit_dir))
+                .transpose()?;
+            let options = cli::commands::convert::ConvertOptions { to, into, review };
+            cli::commands::convert::run(&playlist, cli.provider, &options, &grit_dir, cli.json)
+                .await?;
+        }
+        Commands::Fork { playlist, name } => {
             let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
-            cli::commands::vcs::push(Some(&playlist), &grit_dir).await?;
+            cli::commands::fork::run(&playlist, name.as_deref(), &grit_dir).await?;
         }
-        Commands::Log => {
+        Commands::Bundle { action } => match action {
+            BundleAction::Create { file, playlist } => {
+                let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
+                cli::commands::bundle::create(&file, Some(&playlist), &grit_dir).await?;
+            }
+            BundleAction::Unbundle { file } => {
+                cli::commands::bundle::unbundle(&file, &grit_dir).await?;
+            }
+        },
+        Commands::Clone {
+            source,
+            all,
+            owned,
+            filter,
+        } => match source {
+            Some(source) if !all => cli::commands::bundle::clone(&source, &grit_dir).await?,
+            _ => {
+                let provider = cli
+                    .provider
+                    .or(default_provider)
+                    .unwrap_or(ProviderKind::Spotify);
+                cli::commands::init::clone_all(provider, owned, filter.as_deref(), &grit_dir)
+                    .await?;
+            }
+        },
+        Commands::Remote { action } => match action {
+            RemoteAction::Add { name, url } => {
+                let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
+                cli::commands::remote::add(&name, &url, cli.provider, Some(&playlist), &grit_dir)
+                    .await?;
+            }
+            RemoteAction::Remove { name } => {
+                let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
+                cli::commands::remote::remove(&name, Some(&playlist), &grit_dir).await?;
+            }
+            RemoteAction::Show => {
+                let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
+                cli::commands::remote::show(Some(&playlist), &grit_dir).await?;
+            }
+            RemoteAction::Ls { owned, filter } => {
+                let provider = cli
+                    .provider
+                    .or(default_provider)
+                    .unwrap_or(ProviderKind::Spotify);
+                cli::commands::remote::ls(provider, owned, filter.as_deref(), &grit_dir, cli.json)
+                    .await?;
+            }
+        },
+        Commands::Log {
+            oneline,
+            stat,
+            since,
+            until,
+            author,
+            grep,
+            operations,
+            max_count,
+            format,
+        } => {
             let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
-            cli::commands::vcs::log(Some(&playlist), &grit_dir).await?;
+            let filter = state::journal::LogFilter {
+                since: since
+                    .as_deref()
+                    .map(state::journal::parse_date)
+                    .transpose()?,
+                until: until
+                    .as_deref()
+                    .map(state::journal::parse_until)
+                    .transpose()?,
+                author,
+                grep,
+                operations,
+            };
+            let options = cli::commands::vcs::LogOptions {
+                filter,
+                oneline,
+                stat,
+                max_count,
+                format,
+            };
+            cli::commands::vcs::log(Some(&playlist), &grit_dir, &options, cli.json).await?;
         }
-        Commands::Pull => {
+        Commands::Pull { dry_run } => {
             let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
-            cli::commands::vcs::pull(Some(&playlist), &grit_dir).await?;
+            cli::commands::vcs::pull(Some(&playlist), &grit_dir, dry_run).await?;
         }
         Commands::Diff { staged, remote } => {
             let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
-            cli::commands::vcs::diff_cmd(Some(&playlist), &grit_dir, staged, remote).await?;
+            cli::commands::vcs::diff_cmd(Some(&playlist), &grit_dir, staged, remote, cli.json)
+                .await?;
         }
         Commands::Playlists { query } => {
-            cli::commands::misc::playlists(query.as_deref(), &grit_dir).await?;
+            cli::commands::misc::playlists(query.as_deref(), &grit_dir, cli.json).await?;
         }
         Commands::Switch { playlist } => {
             cli::commands::misc::switch(&playlist, &grit_dir).await?;
         }
         Commands::Curr => {
-            cli::commands::misc::curr(&grit_dir).await?;
+            cli::commands::misc::curr(&grit_dir, cli.json).await?;
         }
-        Commands::Revert { hash, playlist } => {
+        Commands::Config { action } => {
+            let playlist = resolve_playlist(None, cli.playlist, &grit_dir).ok();
+            match action {
+                ConfigAction::Get { key, show_secret } => {
+                    cli::commands::config::get(&key, show_secret, playlist.as_deref(), &grit_dir)
+                        .await?;
+                }
+                ConfigAction::Set { key, value, scope } => {
+                    cli::commands::config::set(&key, &value, scope, playlist.as_deref(), &grit_dir)
+                        .await?;
+                }
+                ConfigAction::Unset { key, scope } => {
+                    cli::commands::config::unset(&key, scope, playlist.as_deref(), &grit_dir)
+                        .await?;
+                }
+                ConfigAction::List => {
+                    cli::commands::config::list(playlist.as_deref(), &grit_dir).await?;
+                }
+            }
+        }
+        Commands::Revert {
+            hash,
+            playlist,
+            dry_run,
+        } => {
             let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
-            cli::commands::vcs::revert(hash.as_deref(), Some(&playlist), &grit_dir).await?;
+            cli::commands::vcs::revert(hash.as_deref(), Some(&playlist), &grit_dir, dry_run)
+                .await?;
         }
-        Commands::Apply { file } => {
+        Commands::Apply { file, dry_run } => {
             let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
-            cli::commands::vcs::apply(&file, Some(&playlist), &grit_dir).await?;
+            cli::commands::vcs::apply(&file, Some(&playlist), &grit_dir, dry_run).await?;
+        }
+        Commands::Export {
+            playlist,
+            format,
+            rev,
+            output,
+            columns,
+        } => {
+            let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
+            cli::commands::transfer::export(
+                Some(&playlist),
+                format,
+                rev.as_deref(),
+                output.as_deref(),
+                &columns,
+                &grit_dir,
+            )
+            .await?;
+        }
+        Commands::Import { file, format } => {
+            let playlist = resolve_playlist(None, cli.playlist, &grit_dir)?;
+            cli::commands::transfer::import(&file, format, Some(&playlist), &grit_dir).await?;
         }
         Commands::Play { playlist, shuffle } => {
             let playlist = resolve_playlist(playlist, cli.playlist, &grit_dir)?;
@@ -141,3 +367,83 @@ fn resolve_playlist(
         .or_else(|| crate::state::working_playlist::load(grit_dir).ok())
         .context("Playlist required (use --playlist, 'grit switch <id>', or run 'grit init' to set working playlist)")
 }
+
+/// Replace a command alias (`alias.<name>` in the config) with its expansion.
+/// Built-in commands always win over aliases, like in git.
+fn expand_alias(args: Vec<String>, config: &Config) -> anyhow::Result<Vec<String>> {
+    // Global options that take a value, so their value isn't mistaken for the command
+    const VALUE_OPTIONS: [&str; 4] = ["-l", "--playlist", "-p", "--provider"];
+
+    let mut position = 1;
+    while position < args.len() {
+        let arg = &args[position];
+        if VALUE_OPTIONS.contains(&arg.as_str()) {
+            position += 2;
+        } else if arg.starts_with('-') {
+            position += 1;
+        } else {
+            break;
+        }
+    }
+
+    let Some(name) = args.get(position) else {
+        return Ok(args);
+    };
+
+    let command = Cli::command();
+    let is_builtin = command
+        .get_subcommands()
+        .any(|sub| sub.get_name() == name || sub.get_all_aliases().any(|alias| alias == name));
+    if is_builtin {
+        return Ok(args);
+    }
+
+    let Some(expansion) = config.alias(name) else {
+        return Ok(args);
+    };
+
+    let replacement = split_command_line(&expansion)
+        .with_context(|| format!("Invalid alias.{} '{}'", name, expansion))?;
+
+    let mut expanded = args[..position].to_vec();
+    expanded.extend(replacement);
+    expanded.extend_from_slice(&args[position + 1..]);
+    Ok(expanded)
+}
+
+/// Split an alias into words, honouring single and double quotes.
+fn split_command_line(input: &str) -> anyhow::Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for ch in input.chars() {
+        match (quote, ch) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        anyhow::bail!("Unterminated quote");
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
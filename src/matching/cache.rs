@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use super::Candidate;
+use crate::provider::{ProviderKind, Track};
+
+/// Ranked candidates from earlier searches, stored in `.grit/cache/matches.json`
+/// and keyed by source track and target provider.
+#[derive(Debug, Default)]
+pub struct MatchCache {
+    path: PathBuf,
+    entries: HashMap<String, Vec<Candidate>>,
+    dirty: bool,
+}
+
+impl MatchCache {
+    pub fn path(grit_dir: &Path) -> PathBuf {
+        grit_dir.join("cache").join("matches.json")
+    }
+
+    pub fn load(grit_dir: &Path) -> Result<Self> {
+        let path = Self::path(grit_dir);
+        let entries = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read match cache {:?}", path))?;
+            // A corrupt cache only costs a few extra searches
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path,
+            entries,
+            dirty: false,
+        })
+    }
+
+    pub fn get(&self, track: &Track, target: &ProviderKind) -> Option<&[Candidate]> {
+        self.entries.get(&key(track, target)).map(Vec::as_slice)
+    }
+
+    pub fn insert(&mut self, track: &Track, target: &ProviderKind, candidates: Vec<Candidate>) {
+        self.entries.insert(key(track, target), candidates);
+        self.dirty = true;
+    }
+
+    /// Write the cache back if anything was added.
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        let json =
+            serde_json::to_string(&self.entries).context("Failed to serialize match cache")?;
+        fs::write(&self.path, json)
+            .with_context(|| format!("Failed to write match cache {:?}", self.path))?;
+
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+fn key(track: &Track, target: &ProviderKind) -> String {
+    format!("{}:{}->{}", track.provider, track.id, target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let track = Track {
+            id: "sp1".to_string(),
+            name: "Song".to_string(),
+            artists: vec!["Artist".to_string()],
+            duration_ms: 200_000,
+            provider: ProviderKind::Spotify,
+            metadata: None,
+        };
+        let found = Track {
+            id: "yt1".to_string(),
+            provider: ProviderKind::Youtube,
+            ..track.clone()
+        };
+
+        let mut cache = MatchCache::load(temp.path()).unwrap();
+        assert!(cache.get(&track, &ProviderKind::Youtube).is_none());
+        cache.insert(
+            &track,
+            &ProviderKind::Youtube,
+            vec![Candidate {
+                track: found,
+                score: 0.9,
+            }],
+        );
+        cache.save().unwrap();
+
+        let reloaded = MatchCache::load(temp.path()).unwrap();
+        let cached = reloaded.get(&track, &ProviderKind::Youtube).unwrap();
+        assert_eq!(cached[0].track.id, "yt1");
+        assert!(reloaded.get(&track, &ProviderKind::Spotify).is_none());
+    }
+}
@@ -0,0 +1,330 @@
+pub mod cache;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    playback::lyrics::clean_yt_title,
+    provider::{Provider, ProviderKind, Track},
+};
+
+pub use cache::MatchCache;
+
+/// Candidates scoring at least this are used without asking.
+pub const ACCEPT_THRESHOLD: f64 = 0.85;
+
+/// Candidates scoring below this are not considered matches at all.
+pub const REJECT_THRESHOLD: f64 = 0.5;
+
+/// Number of ranked candidates kept per track.
+const MAX_CANDIDATES: usize = 5;
+
+/// A possible counterpart of a track on another provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    pub track: Track,
+    pub score: f64,
+}
+
+impl Candidate {
+    pub fn confidence(&self) -> Confidence {
+        Confidence::of(self.score)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    /// Safe to use automatically.
+    High,
+    /// Plausible, but needs manual confirmation.
+    Low,
+    /// Not a match.
+    None,
+}
+
+impl Confidence {
+    pub fn of(score: f64) -> Self {
+        if score >= ACCEPT_THRESHOLD {
+            Confidence::High
+        } else if score >= REJECT_THRESHOLD {
+            Confidence::Low
+        } else {
+            Confidence::None
+        }
+    }
+}
+
+/// Ranked candidates for `track` on `provider`, best first.
+///
+/// Results are cached per source track and target provider, so converting
+/// the same playlist again doesn't search again.
+pub async fn find_matches(
+    track: &Track,
+    target: &ProviderKind,
+    provider: &dyn Provider,
+    cache: &mut MatchCache,
+) -> Result<Vec<Candidate>> {
+    if let Some(cached) = cache.get(track, target) {
+        return Ok(cached.to_vec());
+    }
+
+    let query = format!("{} {}", track.artists.join(" "), track.name);
+    let results = provider.search_by_query(&query).await?;
+
+    let candidates = rank(track, results);
+    cache.insert(track, target, candidates.clone());
+
+    Ok(candidates)
+}
+
+/// How many of `tracks` `find_matches` would have to search for; the rest
+/// are answered from the cache.
+pub fn searches_needed<'a>(
+    tracks: impl IntoIterator<Item = &'a Track>,
+    target: &ProviderKind,
+    cache: &MatchCache,
+) -> usize {
+    tracks
+        .into_iter()
+        .filter(|track| cache.get(track, target).is_none())
+        .count()
+}
+
+/// Score `results` against `track` and keep the best few, best first.
+pub fn rank(track: &Track, results: Vec<Track>) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = results
+        .into_iter()
+        .map(|candidate| Candidate {
+            score: score(track, &candidate),
+            track: candidate,
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(MAX_CANDIDATES);
+    candidates
+}
+
+/// How likely `candidate` is the same recording as `track`, from 0.0 to 1.0.
+///
+/// Matching ISRCs are conclusive. Otherwise the score weighs title
+/// similarity, artist similarity and how close the durations are.
+pub fn score(track: &Track, candidate: &Track) -> f64 {
+    if let (Some(a), Some(b)) = (isrc(track), isrc(candidate)) {
+        if a.eq_ignore_ascii_case(&b) {
+            return 1.0;
+        }
+    }
+
+    let (title_a, artist_a) = title_and_artists(track);
+    let (title_b, artist_b) = title_and_artists(candidate);
+
+    let title = similarity(&title_a, &title_b);
+    // Video titles often carry the artist ("Artist - Title"), so also
+    // accept the artist showing up anywhere in the other track's text
+    let artist = similarity(&artist_a, &artist_b)
+        .max(containment(&artist_a, &format!("{} {}", artist_b, title_b)));
+    let duration = duration_score(track.duration_ms, candidate.duration_ms);
+
+    0.55 * title + 0.3 * artist + 0.15 * duration
+}
+
+fn isrc(track: &Track) -> Option<String> {
+    track
+        .metadata
+        .as_ref()?
+        .get("isrc")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Normalized title and artist text. YouTube titles are split into artist
+/// and title where possible, and channel suffixes are dropped.
+fn title_and_artists(track: &Track) -> (String, String) {
+    let artists = track
+        .artists
+        .iter()
+        .map(|a| clean_channel_name(a))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if track.provider == ProviderKind::Youtube {
+        let (title, artist) = clean_yt_title(&track.name);
+        let artists = match artist {
+            Some(artist) => format!("{} {}", artist, artists),
+            None => artists,
+        };
+        return (normalize(&title), normalize(&artists));
+    }
+
+    (normalize(&track.name), normalize(&artists))
+}
+
+fn clean_channel_name(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let trimmed = lower
+        .trim_end_matches(" - topic")
+        .trim_end_matches("vevo")
+        .trim_end_matches("official");
+    trimmed.trim().to_string()
+}
+
+/// Lowercase, drop "feat." credits and bracketed notes such as
+/// "(Remastered 2011)", and collapse punctuation into single spaces.
+pub fn normalize(text: &str) -> String {
+    let lower = text.to_lowercase();
+
+    let mut stripped = String::with_capacity(lower.len());
+    let mut depth = 0usize;
+    for c in lower.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+
+    let cut = [" feat. ", " feat ", " ft. ", " ft ", " - remaster"]
+        .iter()
+        .filter_map(|marker| stripped.find(marker))
+        .min();
+    if let Some(cut) = cut {
+        stripped.truncate(cut);
+    }
+
+    stripped
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Similarity of two normalized strings: the better of word overlap (Dice
+/// coefficient) and character edit distance.
+fn similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let words_a: Vec<&str> = a.split_whitespace().collect();
+    let words_b: Vec<&str> = b.split_whitespace().collect();
+    let shared = words_a.iter().filter(|w| words_b.contains(w)).count();
+    let dice = 2.0 * shared as f64 / (words_a.len() + words_b.len()) as f64;
+
+    let max_len = a.chars().count().max(b.chars().count());
+    let edit = 1.0 - levenshtein(a, b) as f64 / max_len as f64;
+
+    dice.max(edit)
+}
+
+/// Fraction of the words of `needle` that appear in `haystack`.
+fn containment(needle: &str, haystack: &str) -> f64 {
+    let words: Vec<&str> = needle.split_whitespace().collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    let hay: Vec<&str> = haystack.split_whitespace().collect();
+    let found = words.iter().filter(|w| hay.contains(w)).count();
+    found as f64 / words.len() as f64
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+
+    prev[b.len()]
+}
+
+/// 1.0 within 3 seconds, falling to 0.0 at 30 seconds apart. Unknown
+/// durations are neutral.
+fn duration_score(a: u64, b: u64) -> f64 {
+    if a == 0 || b == 0 {
+        return 0.5;
+    }
+    let diff = a.abs_diff(b) as f64 / 1000.0;
+    if diff <= 3.0 {
+        1.0
+    } else {
+        (1.0 - (diff - 3.0) / 27.0).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(name: &str, artist: &str, duration_ms: u64, provider: ProviderKind) -> Track {
+        Track {
+            id: format!("{}-{}", provider, name),
+            name: name.to_string(),
+            artists: vec![artist.to_string()],
+            duration_ms,
+            provider,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("Hey Jude (Remastered 2015)"),
+            "hey jude".to_string()
+        );
+        assert_eq!(normalize("Stay feat. Justin Bieber"), "stay");
+        assert_eq!(normalize("Don't Stop Me Now"), "don t stop me now");
+    }
+
+    #[test]
+    fn test_youtube_video_matches_spotify_track() {
+        let spotify = track("Hey Jude", "The Beatles", 431_000, ProviderKind::Spotify);
+        let video = track(
+            "The Beatles - Hey Jude (Official Video)",
+            "TheBeatlesVEVO",
+            432_000,
+            ProviderKind::Youtube,
+        );
+        let cover = track(
+            "Hey Jude - piano cover",
+            "Some Pianist",
+            250_000,
+            ProviderKind::Youtube,
+        );
+
+        let ranked = rank(&spotify, vec![cover, video]);
+        assert_eq!(ranked[0].track.artists[0], "TheBeatlesVEVO");
+        assert_eq!(ranked[0].confidence(), Confidence::High);
+        assert_ne!(ranked[1].confidence(), Confidence::High);
+    }
+
+    #[test]
+    fn test_isrc_is_conclusive() {
+        let mut a = track("Song", "Artist", 200_000, ProviderKind::Spotify);
+        let mut b = track("Totally Different", "Other", 90_000, ProviderKind::Spotify);
+        a.metadata = Some(serde_json::json!({ "isrc": "USABC1234567" }));
+        b.metadata = Some(serde_json::json!({ "isrc": "usabc1234567" }));
+
+        assert_eq!(score(&a, &b), 1.0);
+    }
+
+    #[test]
+    fn test_unrelated_track_is_rejected() {
+        let a = track("Bohemian Rhapsody", "Queen", 355_000, ProviderKind::Spotify);
+        let b = track("Lose Yourself", "Eminem", 326_000, ProviderKind::Spotify);
+        assert_eq!(Confidence::of(score(&a, &b)), Confidence::None);
+    }
+}
@@ -1,8 +1,10 @@
+use crate::utils::http::HttpClient;
 use anyhow::Result;
-use reqwest::Client;
 use serde::Deserialize;
 use tokio::sync::mpsc;
 
+const LRCLIB_URL: &str = "https://lrclib.net/api";
+
 #[derive(Debug, Clone)]
 pub struct LyricLine {
     pub time_secs: f64,
@@ -83,14 +85,16 @@ fn parse_timestamp(ts: &str) -> Option<f64> {
 }
 
 pub async fn fetch_lyrics(
+    base_url: &str,
     track_name: &str,
     artist_name: &str,
     duration_secs: u64,
 ) -> Result<Lyrics> {
-    let client = Client::new();
+    let client = HttpClient::new();
 
     let url = format!(
-        "https://lrclib.net/api/get?track_name={}&artist_name={}&duration={}",
+        "{}/get?track_name={}&artist_name={}&duration={}",
+        base_url,
         urlencoding::encode(track_name),
         urlencoding::encode(artist_name),
         duration_secs
@@ -195,16 +199,21 @@ pub fn clean_yt_title(title: &str) -> (String, Option<String>) {
     }
 }
 
-pub async fn fetch_lyrics_for_yt(title: &str, duration_secs: u64) -> Result<Lyrics> {
+pub async fn fetch_lyrics_for_yt(
+    base_url: &str,
+    title: &str,
+    duration_secs: u64,
+) -> Result<Lyrics> {
     let (track, artist) = clean_yt_title(title);
     let artist_str = artist.as_deref().unwrap_or("");
-    fetch_lyrics(&track, artist_str, duration_secs).await
+    fetch_lyrics(base_url, &track, artist_str, duration_secs).await
 }
 
 pub struct LyricsFetcher {
     tx: mpsc::Sender<Lyrics>,
     rx: mpsc::Receiver<Lyrics>,
     current_track_id: Option<String>,
+    base_url: String,
 }
 
 impl LyricsFetcher {
@@ -214,9 +223,16 @@ impl LyricsFetcher {
             tx,
             rx,
             current_track_id: None,
+            base_url: LRCLIB_URL.to_string(),
         }
     }
 
+    /// Use an LRCLIB-compatible server other than lrclib.net.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
     pub fn try_recv(&mut self) -> Option<Lyrics> {
         self.rx.try_recv().ok()
     }
@@ -235,8 +251,9 @@ impl LyricsFetcher {
         let tx = self.tx.clone();
         let name = track_name.to_string();
         let artist = artist.to_string();
+        let base_url = self.base_url.clone();
         tokio::spawn(async move {
-            let lyrics = fetch_lyrics(&name, &artist, duration_secs)
+            let lyrics = fetch_lyrics(&base_url, &name, &artist, duration_secs)
                 .await
                 .unwrap_or_default();
             let _ = tx.send(lyrics).await;
@@ -250,8 +267,9 @@ impl LyricsFetcher {
         self.current_track_id = Some(track_id.to_string());
         let tx = self.tx.clone();
         let title = title.to_string();
+        let base_url = self.base_url.clone();
         tokio::spawn(async move {
-            let lyrics = fetch_lyrics_for_yt(&title, duration_secs)
+            let lyrics = fetch_lyrics_for_yt(&base_url, &title, duration_secs)
                 .await
                 .unwrap_or_default();
             let _ = tx.send(lyrics).await;
@@ -263,3 +281,43 @@ impl LyricsFetcher {
         while self.rx.try_recv().is_ok() {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::TestServer;
+
+    #[tokio::test]
+    async fn test_fetch_lyrics() {
+        let mut server = TestServer::bind().await;
+        server.serve(|request| match request.param("track_name") {
+            Some("Song") => (
+                200,
+                serde_json::json!({
+                    "syncedLyrics": "[00:01.50] First\n[01:02.00] Second",
+                    "plainLyrics": "First\nSecond"
+                })
+                .to_string(),
+            ),
+            _ => (404, "{}".to_string()),
+        });
+
+        let lyrics = fetch_lyrics(&server.url, "Song", "Artist", 200)
+            .await
+            .unwrap();
+        assert_eq!(lyrics.lines.len(), 2);
+        assert_eq!(lyrics.lines[1].time_secs, 62.0);
+        assert_eq!(lyrics.current_line_index(70.0), Some(1));
+
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/get");
+        assert_eq!(request.param("artist_name"), Some("Artist"));
+        assert_eq!(request.param("duration"), Some("200"));
+
+        let missing = fetch_lyrics(&server.url, "Other", "Artist", 200)
+            .await
+            .unwrap();
+        assert!(missing.lines.is_empty() && missing.plain.is_none());
+    }
+}
@@ -5,20 +5,19 @@ use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
-use crate::provider::{OAuthToken, ProviderKind};
+use crate::provider::{Endpoints, OAuthToken, ProviderKind, SpotifyProvider};
 use crate::state::credentials;
-
-const API_BASE: &str = "https://api.spotify.com/v1";
-const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+use crate::utils::http::HttpClient;
 
 /// Spotify Connect playback controller
 /// Controls playback on any Spotify Connect device (librespot, phone, desktop app)
 pub struct SpotifyPlayer {
-    http: reqwest::Client,
+    http: HttpClient,
     token: Mutex<OAuthToken>,
     client_id: String,
     client_secret: String,
     grit_dir: PathBuf,
+    endpoints: Endpoints,
     device_id: Option<String>,
 }
 
@@ -103,15 +102,21 @@ impl SpotifyPlayer {
         grit_dir: &Path,
     ) -> Self {
         Self {
-            http: reqwest::Client::new(),
+            http: HttpClient::new(),
             token: Mutex::new(token),
             client_id,
             client_secret,
             grit_dir: grit_dir.to_path_buf(),
+            endpoints: SpotifyProvider::default_endpoints(),
             device_id: None,
         }
     }
 
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
     /// Check if token is expired (with 5 minute buffer for safe refresh)
     fn is_token_expired(token: &OAuthToken) -> bool {
         if let Some(expires_at) = token.expires_at {
@@ -132,7 +137,7 @@ impl SpotifyPlayer {
             let new_token = self.refresh_token(&current_token).await?;
 
             // Save refreshed token
-            credentials::save(&self.grit_dir, ProviderKind::Spotify, &new_token)?;
+            credentials::save(&self.grit_dir, &ProviderKind::Spotify, &new_token)?;
 
             *self.token.lock().await = new_token.clone();
             Ok(new_token.access_token)
@@ -159,7 +164,8 @@ impl SpotifyPlayer {
 
         let response = self
             .http
-            .post(TOKEN_URL)
+            .post(&self.endpoints.token)
+            .read_only()
             .header("Authorization", format!("Basic {}", basic_auth))
             .form(&params)
             .send()
@@ -194,7 +200,7 @@ impl SpotifyPlayer {
 
         let response = self
             .http
-            .get(format!("{}/me/player/devices", API_BASE))
+            .get(format!("{}/me/player/devices", self.endpoints.api))
             .bearer_auth(&token)
             .send()
             .await?;
@@ -272,8 +278,9 @@ impl SpotifyPlayer {
             .http
             .put(format!(
                 "{}/me/player/play?device_id={}",
-                API_BASE, device_id
+                self.endpoints.api, device_id
             ))
+            .idempotent()
             .bearer_auth(&token)
             .json(&body)
             .send()
@@ -296,8 +303,9 @@ impl SpotifyPlayer {
             .http
             .put(format!(
                 "{}/me/player/pause?device_id={}",
-                API_BASE, device_id
+                self.endpoints.api, device_id
             ))
+            .idempotent()
             .bearer_auth(&token)
             .header("Content-Length", "0")
             .send()
@@ -320,8 +328,9 @@ impl SpotifyPlayer {
             .http
             .put(format!(
                 "{}/me/player/play?device_id={}",
-                API_BASE, device_id
+                self.endpoints.api, device_id
             ))
+            .idempotent()
             .bearer_auth(&token)
             .header("Content-Length", "0")
             .send()
@@ -343,7 +352,7 @@ impl SpotifyPlayer {
             .http
             .post(format!(
                 "{}/me/player/next?device_id={}",
-                API_BASE, device_id
+                self.endpoints.api, device_id
             ))
             .bearer_auth(&token)
             .header("Content-Length", "0")
@@ -366,7 +375,7 @@ impl SpotifyPlayer {
             .http
             .post(format!(
                 "{}/me/player/previous?device_id={}",
-                API_BASE, device_id
+                self.endpoints.api, device_id
             ))
             .bearer_auth(&token)
             .header("Content-Length", "0")
@@ -390,8 +399,9 @@ impl SpotifyPlayer {
             .http
             .put(format!(
                 "{}/me/player/seek?device_id={}&position_ms={}",
-                API_BASE, device_id, position_ms
+                self.endpoints.api, device_id, position_ms
             ))
+            .idempotent()
             .bearer_auth(&token)
             .header("Content-Length", "0")
             .send()
@@ -413,8 +423,9 @@ impl SpotifyPlayer {
             .http
             .put(format!(
                 "{}/me/player/shuffle?device_id={}&state={}",
-                API_BASE, device_id, state
+                self.endpoints.api, device_id, state
             ))
+            .idempotent()
             .bearer_auth(&token)
             .header("Content-Length", "0")
             .send()
@@ -442,8 +453,9 @@ impl SpotifyPlayer {
             .http
             .put(format!(
                 "{}/me/player/repeat?device_id={}&state={}",
-                API_BASE, device_id, state
+                self.endpoints.api, device_id, state
             ))
+            .idempotent()
             .bearer_auth(&token)
             .header("Content-Length", "0")
             .send()
@@ -462,7 +474,10 @@ impl SpotifyPlayer {
 
         let resp = self
             .http
-            .get(format!("{}/me/player/currently-playing", API_BASE))
+            .get(format!(
+                "{}/me/player/currently-playing",
+                self.endpoints.api
+            ))
             .bearer_auth(&token)
             .send()
             .await?;
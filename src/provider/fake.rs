@@ -0,0 +1,160 @@
+//! An in-memory provider for tests: playlists live in a map, searches run
+//! over a fixed catalog and nothing touches the network.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+
+use crate::provider::{
+    DiffPatch, OAuthToken, PlaylistSnapshot, Provider, ProviderKind, RemotePlaylist, SourceKind,
+    Track,
+};
+
+pub struct FakeProvider {
+    kind: ProviderKind,
+    playlists: Mutex<HashMap<String, PlaylistSnapshot>>,
+    catalog: Vec<Track>,
+    next_id: Mutex<usize>,
+}
+
+impl FakeProvider {
+    /// A provider posing as `kind`, as seen in the tracks it returns.
+    pub fn new(kind: ProviderKind) -> Self {
+        Self {
+            kind,
+            playlists: Mutex::default(),
+            catalog: Vec::new(),
+            next_id: Mutex::new(1),
+        }
+    }
+
+    pub fn with_playlist(self, playlist: PlaylistSnapshot) -> Self {
+        self.playlists
+            .lock()
+            .unwrap()
+            .insert(playlist.id.clone(), playlist);
+        self
+    }
+
+    /// Tracks that `search_by_query` and `fetch_track` can find.
+    pub fn with_catalog(mut self, tracks: Vec<Track>) -> Self {
+        self.catalog = tracks;
+        self
+    }
+
+    /// Current state of a playlist, e.g. to check what a push did.
+    pub fn playlist(&self, playlist_id: &str) -> Option<PlaylistSnapshot> {
+        self.playlists.lock().unwrap().get(playlist_id).cloned()
+    }
+}
+
+#[async_trait]
+impl Provider for FakeProvider {
+    async fn fetch(&self, playlist_id: &str) -> Result<PlaylistSnapshot> {
+        self.playlist(playlist_id)
+            .with_context(|| format!("Playlist not found: {}", playlist_id))
+    }
+
+    async fn apply(
+        &self,
+        playlist_id: &str,
+        _patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        let mut playlists = self.playlists.lock().unwrap();
+        let playlist = playlists
+            .get_mut(playlist_id)
+            .with_context(|| format!("Playlist not found: {}", playlist_id))?;
+        playlist.tracks = desired_state.tracks.clone();
+        Ok(())
+    }
+
+    async fn playable_url(&self, track: &Track) -> Result<String> {
+        Ok(format!("fake://{}", track.id))
+    }
+
+    async fn fetch_track(&self, track_id: &str) -> Result<Track> {
+        self.catalog
+            .iter()
+            .find(|t| t.id == track_id)
+            .cloned()
+            .with_context(|| format!("Track not found: {}", track_id))
+    }
+
+    async fn search_by_query(&self, query: &str) -> Result<Vec<Track>> {
+        let words: Vec<String> = query
+            .to_lowercase()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+
+        Ok(self
+            .catalog
+            .iter()
+            .filter(|track| {
+                let haystack = format!("{} {}", track.artists.join(" "), track.name).to_lowercase();
+                words.iter().all(|w| haystack.contains(w.as_str()))
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn oauth_url(&self, _redirect_uri: &str, _state: &str) -> String {
+        String::new()
+    }
+
+    async fn exchange_code(&self, _code: &str, _redirect_uri: &str) -> Result<OAuthToken> {
+        bail!("The fake provider doesn't use authentication")
+    }
+
+    async fn refresh_token(&self, _token: &OAuthToken) -> Result<OAuthToken> {
+        bail!("The fake provider doesn't use authentication")
+    }
+
+    async fn can_modify_playlist(&self, playlist_id: &str) -> Result<bool> {
+        Ok(self.playlists.lock().unwrap().contains_key(playlist_id))
+    }
+
+    async fn list_playlists(&self) -> Result<Vec<RemotePlaylist>> {
+        let mut playlists: Vec<RemotePlaylist> = self
+            .playlists
+            .lock()
+            .unwrap()
+            .values()
+            .map(|p| RemotePlaylist {
+                id: p.id.clone(),
+                name: p.name.clone(),
+                owner: None,
+                owned: true,
+                tracks: Some(p.tracks.len()),
+            })
+            .collect();
+        playlists.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(playlists)
+    }
+
+    async fn create_playlist(&self, name: &str, description: Option<&str>) -> Result<String> {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            format!("fake-{}", *next_id - 1)
+        };
+
+        self.playlists.lock().unwrap().insert(
+            id.clone(),
+            PlaylistSnapshot {
+                id: id.clone(),
+                name: name.to_string(),
+                description: description.map(str::to_string),
+                tracks: Vec::new(),
+                provider: self.kind.clone(),
+                source: SourceKind::Playlist,
+                snapshot_hash: String::new(),
+                metadata: None,
+            },
+        );
+        Ok(id)
+    }
+}
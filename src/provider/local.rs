@@ -0,0 +1,424 @@
+mod tags;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+
+use crate::{
+    formats::{m3u, slugify, Entry, PlaylistFile},
+    provider::{
+        DiffPatch, OAuthToken, PlaylistSnapshot, Provider, ProviderKind, SourceKind, Track,
+    },
+};
+
+const AUDIO_EXTENSIONS: [&str; 8] = ["mp3", "flac", "m4a", "ogg", "opus", "wav", "aac", "wma"];
+
+/// A directory of audio files. Playlists are `<root>/<id>.m3u8` files and
+/// track IDs are file paths relative to the root.
+pub struct LocalProvider {
+    root: PathBuf,
+    search_limit: usize,
+}
+
+impl LocalProvider {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            search_limit: 10,
+        }
+    }
+
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.search_limit = limit;
+        self
+    }
+
+    pub fn playlist_path(&self, playlist_id: &str) -> PathBuf {
+        self.root.join(format!("{}.m3u8", playlist_id))
+    }
+
+    /// Playlist ID of an M3U file given on the command line. Only `.m3u8`
+    /// files directly inside the root can be tracked, as the ID is the
+    /// file name without its extension.
+    pub fn playlist_id(&self, path: &Path) -> Result<String> {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        if extension.as_deref() != Some("m3u8") {
+            bail!(
+                "{:?} isn't an .m3u8 file; grit reads and writes UTF-8 .m3u8 playlists, so convert it first",
+                path
+            );
+        }
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .with_context(|| format!("{:?} has no file name", path))?;
+
+        // A bare file name is looked up in the root
+        let parent = path.parent().filter(|p| !p.as_os_str().is_empty());
+        if let Some(parent) = parent {
+            let inside = match (parent.canonicalize(), self.root.canonicalize()) {
+                (Ok(parent), Ok(root)) => parent == root,
+                _ => false,
+            };
+            if !inside {
+                bail!(
+                    "{:?} isn't directly inside the local music folder {:?} (local.root)",
+                    path,
+                    self.root
+                );
+            }
+        }
+
+        Ok(stem)
+    }
+
+    fn track_path(&self, track_id: &str) -> PathBuf {
+        let path = Path::new(track_id);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        }
+    }
+
+    /// Track ID for a file: its path relative to the root when inside it.
+    fn track_id(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Read a track from its tags, falling back to the file name
+    /// ("Artist - Title.mp3") and to `#EXTINF` info for missing files.
+    fn read_track(&self, track_id: &str, entry: Option<&Entry>) -> Track {
+        let path = self.track_path(track_id);
+        let tags = tags::read(&path).unwrap_or_default();
+
+        let (fallback_artist, fallback_title) = match entry {
+            Some(Entry {
+                title: Some(title),
+                artist,
+                ..
+            }) => (artist.clone(), title.clone()),
+            _ => {
+                let stem = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| track_id.to_string());
+                split_display(&stem)
+            }
+        };
+
+        let artist = tags
+            .artist
+            .or(fallback_artist)
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let duration_ms = tags
+            .duration_ms
+            .or_else(|| entry.and_then(|e| e.duration_ms))
+            .unwrap_or(0);
+
+        Track {
+            id: track_id.to_string(),
+            name: tags.title.unwrap_or(fallback_title),
+            artists: artist.split(';').map(|a| a.trim().to_string()).collect(),
+            duration_ms,
+            provider: ProviderKind::Local,
+            metadata: tags.isrc.map(|isrc| serde_json::json!({ "isrc": isrc })),
+        }
+    }
+
+    fn audio_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+
+        while let Some(dir) = dirs.pop() {
+            let entries = fs::read_dir(&dir)
+                .with_context(|| format!("Failed to read directory {:?}", dir))?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if is_audio_file(&path) {
+                    files.push(path);
+                }
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Split "Artist - Title" into its parts. Without a separator the whole
+/// text is the title.
+fn split_display(text: &str) -> (Option<String>, String) {
+    match text.split_once(" - ") {
+        Some((artist, title)) => (Some(artist.trim().to_string()), title.trim().to_string()),
+        None => (None, text.trim().to_string()),
+    }
+}
+
+fn render_m3u(snapshot: &PlaylistSnapshot) -> String {
+    let playlist = PlaylistFile {
+        title: Some(snapshot.name.clone()),
+        entries: snapshot
+            .tracks
+            .iter()
+            .map(|track| Entry {
+                // Multiple artists are kept apart with ';', as in tags
+                artist: Some(track.artists.join("; ")),
+                ..Entry::from_track(track, Some(track.id.clone()))
+            })
+            .collect(),
+    };
+    m3u::render(&playlist)
+}
+
+#[async_trait]
+impl Provider for LocalProvider {
+    async fn fetch(&self, playlist_id: &str) -> Result<PlaylistSnapshot> {
+        let path = self.playlist_path(playlist_id);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read playlist {:?}", path))?;
+
+        let playlist = m3u::parse(&content);
+
+        let tracks = playlist
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let location = entry.location.as_deref()?;
+                let id = self.track_id(&self.track_path(location));
+                Some(self.read_track(&id, Some(entry)))
+            })
+            .collect();
+
+        Ok(PlaylistSnapshot {
+            id: playlist_id.to_string(),
+            name: playlist.title.unwrap_or_else(|| playlist_id.to_string()),
+            description: None,
+            tracks,
+            provider: ProviderKind::Local,
+            source: SourceKind::Playlist,
+            snapshot_hash: String::new(),
+            metadata: None,
+        })
+    }
+
+    async fn apply(
+        &self,
+        playlist_id: &str,
+        _patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        // The playlist file is small, so rewrite it rather than patching
+        let path = self.playlist_path(playlist_id);
+        fs::write(&path, render_m3u(desired_state))
+            .with_context(|| format!("Failed to write playlist {:?}", path))
+    }
+
+    async fn playable_url(&self, track: &Track) -> Result<String> {
+        let path = self.track_path(&track.id);
+        if !path.exists() {
+            bail!("File not found: {:?}", path);
+        }
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    async fn fetch_track(&self, track_id: &str) -> Result<Track> {
+        let path = self.track_path(track_id);
+        if !path.is_file() {
+            bail!("File not found: {:?}", path);
+        }
+        Ok(self.read_track(&self.track_id(&path), None))
+    }
+
+    async fn search_by_query(&self, query: &str) -> Result<Vec<Track>> {
+        let words: Vec<String> = query
+            .to_lowercase()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+
+        let mut results = Vec::new();
+        for path in self.audio_files()? {
+            let track = self.read_track(&self.track_id(&path), None);
+            let haystack =
+                format!("{} {} {}", track.artists.join(" "), track.name, track.id).to_lowercase();
+
+            if words.iter().all(|w| haystack.contains(w.as_str())) {
+                results.push(track);
+                if results.len() >= self.search_limit.max(1) {
+                    break;
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn oauth_url(&self, _redirect_uri: &str, _state: &str) -> String {
+        String::new()
+    }
+
+    async fn exchange_code(&self, _code: &str, _redirect_uri: &str) -> Result<OAuthToken> {
+        bail!("The local provider doesn't use authentication")
+    }
+
+    async fn refresh_token(&self, _token: &OAuthToken) -> Result<OAuthToken> {
+        bail!("The local provider doesn't use authentication")
+    }
+
+    async fn can_modify_playlist(&self, playlist_id: &str) -> Result<bool> {
+        let path = self.playlist_path(playlist_id);
+        Ok(fs::metadata(&path)
+            .map(|m| !m.permissions().readonly())
+            .unwrap_or(false))
+    }
+
+    async fn create_playlist(&self, name: &str, _description: Option<&str>) -> Result<String> {
+        let slug = slugify(name);
+        let base = if slug.is_empty() {
+            "playlist".to_string()
+        } else {
+            slug
+        };
+
+        let mut id = base.clone();
+        let mut n = 2;
+        while self.playlist_path(&id).exists() {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+
+        let empty = PlaylistSnapshot {
+            id: id.clone(),
+            name: name.to_string(),
+            description: None,
+            tracks: Vec::new(),
+            provider: ProviderKind::Local,
+            source: SourceKind::Playlist,
+            snapshot_hash: String::new(),
+            metadata: None,
+        };
+        fs::create_dir_all(&self.root)
+            .with_context(|| format!("Failed to create directory {:?}", self.root))?;
+        fs::write(self.playlist_path(&id), render_m3u(&empty))
+            .with_context(|| format!("Failed to create playlist {}", id))?;
+
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_fetch_and_apply_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("Artist")).unwrap();
+        fs::write(root.join("Artist/Artist - First.mp3"), b"").unwrap();
+        fs::write(root.join("Second.flac"), b"").unwrap();
+        fs::write(
+            root.join("mix.m3u8"),
+            "#EXTM3U\n#PLAYLIST:My Mix\nArtist/Artist - First.mp3\n#EXTINF:200,Band - Gone\nmissing.mp3\n",
+        )
+        .unwrap();
+
+        let provider = LocalProvider::new(root);
+        let mut snap = provider.fetch("mix").await.unwrap();
+
+        assert_eq!(snap.name, "My Mix");
+        assert_eq!(snap.tracks.len(), 2);
+        assert_eq!(snap.tracks[0].id, "Artist/Artist - First.mp3");
+        assert_eq!(snap.tracks[0].name, "First");
+        assert_eq!(snap.tracks[0].artists, vec!["Artist"]);
+        assert_eq!(snap.tracks[1].name, "Gone");
+        assert_eq!(snap.tracks[1].duration_ms, 200_000);
+
+        let second = provider.fetch_track("Second.flac").await.unwrap();
+        snap.tracks.reverse();
+        snap.tracks.push(second);
+        provider
+            .apply("mix", &DiffPatch::default(), &snap)
+            .await
+            .unwrap();
+
+        let reloaded = provider.fetch("mix").await.unwrap();
+        let ids: Vec<&str> = reloaded.tracks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["missing.mp3", "Artist/Artist - First.mp3", "Second.flac"]
+        );
+        assert_eq!(reloaded.tracks[0].name, "Gone");
+    }
+
+    #[tokio::test]
+    async fn test_search_and_create() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("Queen - Bohemian Rhapsody.mp3"), b"").unwrap();
+        fs::write(root.join("Queen - Under Pressure.mp3"), b"").unwrap();
+        fs::write(root.join("notes.txt"), b"").unwrap();
+
+        let provider = LocalProvider::new(root);
+        let results = provider.search_by_query("queen pressure").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Under Pressure");
+
+        let id = provider.create_playlist("Road Trip!", None).await.unwrap();
+        assert_eq!(id, "road-trip");
+        assert!(provider.can_modify_playlist(&id).await.unwrap());
+        assert!(provider.fetch(&id).await.unwrap().tracks.is_empty());
+    }
+
+    #[test]
+    fn test_playlist_id_rejects_m3u_files() {
+        let temp = TempDir::new().unwrap();
+        let provider = LocalProvider::new(temp.path());
+        fs::write(temp.path().join("old.m3u"), "#EXTM3U\n").unwrap();
+
+        let err = provider
+            .playlist_id(&temp.path().join("old.m3u"))
+            .unwrap_err();
+        assert!(err.to_string().contains("isn't an .m3u8 file"));
+        assert!(provider.playlist_id(Path::new("Old.M3U")).is_err());
+    }
+
+    #[test]
+    fn test_playlist_id_rejects_files_outside_the_root() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("music");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        let provider = LocalProvider::new(&root);
+
+        assert_eq!(provider.playlist_id(&root.join("mix.m3u8")).unwrap(), "mix");
+        assert_eq!(provider.playlist_id(Path::new("mix.m3u8")).unwrap(), "mix");
+
+        let elsewhere = temp.path().join("mix.m3u8");
+        let err = provider.playlist_id(&elsewhere).unwrap_err();
+        assert!(err.to_string().contains("isn't directly inside"));
+        assert!(provider.playlist_id(&root.join("sub/mix.m3u8")).is_err());
+    }
+}
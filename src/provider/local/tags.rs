@@ -0,0 +1,495 @@
+//! Minimal tag reading for local audio files.
+//!
+//! Supports ID3v2.2-2.4 and ID3v1 (MP3) and Vorbis comments (FLAC). MP3
+//! durations come from the TLEN frame, a Xing/Info header or, for constant
+//! bitrate files, the file size. Other formats have no duration.
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Tags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub isrc: Option<String>,
+}
+
+/// Bytes scanned after the ID3 tag when looking for the first MPEG frame.
+const FRAME_SEARCH_LEN: usize = 8192;
+
+pub fn read(path: &Path) -> Result<Tags> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let file_len = file.metadata()?.len();
+
+    let mut magic = [0u8; 10];
+    let read = read_up_to(&mut file, &mut magic)?;
+
+    if read >= 4 && &magic[..4] == b"fLaC" {
+        file.seek(SeekFrom::Start(4))?;
+        return read_flac(&mut file);
+    }
+
+    let mut tags = Tags::default();
+    let mut audio_start = 0u64;
+
+    if read == 10 && &magic[..3] == b"ID3" {
+        let size = syncsafe(&magic[6..10]) as u64;
+        let has_footer = magic[5] & 0x10 != 0;
+
+        let mut body = vec![0u8; size as usize];
+        file.read_exact(&mut body)
+            .with_context(|| format!("Truncated ID3 tag in {:?}", path))?;
+        tags = parse_id3v2(magic[3], magic[5], &body);
+
+        audio_start = 10 + size + if has_footer { 10 } else { 0 };
+    }
+
+    if tags.title.is_none() || tags.artist.is_none() {
+        let v1 = read_id3v1(&mut file, file_len)?;
+        tags.title = tags.title.or(v1.title);
+        tags.artist = tags.artist.or(v1.artist);
+    }
+
+    // Other formats can hold bytes that look like an MPEG frame, so only
+    // files that are MP3 by name or by their first bytes are scanned
+    let is_mp3 = audio_start > 0
+        || (read >= 4 && magic[0] == 0xff && frame_info(&magic).is_some())
+        || path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"));
+
+    if tags.duration_ms.is_none() && is_mp3 {
+        file.seek(SeekFrom::Start(audio_start))?;
+        let mut buf = vec![0u8; FRAME_SEARCH_LEN];
+        let n = read_up_to(&mut file, &mut buf)?;
+        buf.truncate(n);
+        tags.duration_ms = mp3_duration(&buf, file_len.saturating_sub(audio_start));
+    }
+
+    Ok(tags)
+}
+
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> Result<usize> {
+    let mut total = 0;
+    while total < buf.len() {
+        let n = file.read(&mut buf[total..])?;
+        if n == 0 {
+            break;
+        }
+        total += n;
+    }
+    Ok(total)
+}
+
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |acc, b| (acc << 7) | (*b as u32 & 0x7f))
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |acc, b| (acc << 8) | *b as u32)
+}
+
+fn parse_id3v2(version: u8, flags: u8, body: &[u8]) -> Tags {
+    let mut tags = Tags::default();
+    let mut pos = 0;
+
+    // Extended header
+    if flags & 0x40 != 0 && body.len() >= 4 {
+        pos = match version {
+            3 => 4 + be_u32(&body[..4]) as usize,
+            _ => syncsafe(&body[..4]) as usize,
+        };
+    }
+
+    let (id_len, header_len) = if version == 2 { (3, 6) } else { (4, 10) };
+
+    while pos + header_len <= body.len() {
+        let id = &body[pos..pos + id_len];
+        if id[0] == 0 {
+            break; // padding
+        }
+
+        let size = match version {
+            2 => be_u32(&body[pos + 3..pos + 6]),
+            3 => be_u32(&body[pos + 4..pos + 8]),
+            _ => syncsafe(&body[pos + 4..pos + 8]),
+        } as usize;
+
+        let start = pos + header_len;
+        let end = (start + size).min(body.len());
+        let value = decode_text(&body[start..end]);
+
+        match id {
+            b"TIT2" | b"TT2" => tags.title = value,
+            b"TPE1" | b"TP1" => tags.artist = value,
+            b"TSRC" | b"TRC" => tags.isrc = value,
+            b"TLEN" | b"TLE" => {
+                tags.duration_ms = value.and_then(|v| v.trim().parse().ok());
+            }
+            _ => {}
+        }
+
+        pos = start + size;
+    }
+
+    tags
+}
+
+/// Decode an ID3v2 text frame: an encoding byte followed by the text.
+fn decode_text(data: &[u8]) -> Option<String> {
+    let (&encoding, text) = data.split_first()?;
+
+    let decoded = match encoding {
+        0 => text.iter().map(|&b| b as char).collect(),
+        1 | 2 => {
+            let (big_endian, text) = match text {
+                [0xfe, 0xff, rest @ ..] => (true, rest),
+                [0xff, 0xfe, rest @ ..] => (false, rest),
+                _ => (encoding == 2, text),
+            };
+            let units: Vec<u16> = text
+                .chunks_exact(2)
+                .map(|c| {
+                    if big_endian {
+                        u16::from_be_bytes([c[0], c[1]])
+                    } else {
+                        u16::from_le_bytes([c[0], c[1]])
+                    }
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(text).into_owned(),
+    };
+
+    // Frames may hold several NUL-separated values; keep the first
+    let first = decoded.split('\0').next().unwrap_or_default().trim();
+    (!first.is_empty()).then(|| first.to_string())
+}
+
+fn read_id3v1(file: &mut File, file_len: u64) -> Result<Tags> {
+    if file_len < 128 {
+        return Ok(Tags::default());
+    }
+
+    let mut tag = [0u8; 128];
+    file.seek(SeekFrom::Start(file_len - 128))?;
+    file.read_exact(&mut tag)?;
+
+    if &tag[..3] != b"TAG" {
+        return Ok(Tags::default());
+    }
+
+    let field = |bytes: &[u8]| {
+        let text: String = bytes
+            .iter()
+            .take_while(|&&b| b != 0)
+            .map(|&b| b as char)
+            .collect();
+        let text = text.trim().to_string();
+        (!text.is_empty()).then_some(text)
+    };
+
+    Ok(Tags {
+        title: field(&tag[3..33]),
+        artist: field(&tag[33..63]),
+        ..Tags::default()
+    })
+}
+
+/// Duration of an MP3 stream, given its first bytes and total audio length.
+fn mp3_duration(buf: &[u8], audio_len: u64) -> Option<u64> {
+    let offset = (0..buf.len().saturating_sub(4)).find(|&i| {
+        buf[i] == 0xff && buf[i + 1] & 0xe0 == 0xe0 && frame_info(&buf[i..]).is_some()
+    })?;
+    let frame = &buf[offset..];
+    let info = frame_info(frame)?;
+
+    // A Xing/Info header in the first frame holds the exact frame count
+    let xing_offset = 4 + match (info.mpeg1, info.mono) {
+        (true, false) => 32,
+        (true, true) => 17,
+        (false, false) => 17,
+        (false, true) => 9,
+    };
+    if let Some(xing) = frame.get(xing_offset..xing_offset + 12) {
+        if (&xing[..4] == b"Xing" || &xing[..4] == b"Info") && xing[7] & 0x01 != 0 {
+            let frames = be_u32(&xing[8..12]) as u64;
+            return Some(frames * info.samples_per_frame * 1000 / info.sample_rate);
+        }
+    }
+
+    let audio_len = audio_len.saturating_sub(offset as u64);
+    Some(audio_len * 8 / info.bitrate_kbps)
+}
+
+struct FrameInfo {
+    mpeg1: bool,
+    mono: bool,
+    bitrate_kbps: u64,
+    sample_rate: u64,
+    samples_per_frame: u64,
+}
+
+/// Parse an MPEG audio Layer III frame header.
+fn frame_info(header: &[u8]) -> Option<FrameInfo> {
+    const BITRATES_V1: [u64; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const BITRATES_V2: [u64; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+    let header = header.get(..4)?;
+    let version = (header[1] >> 3) & 0x03; // 0: 2.5, 2: 2, 3: 1
+    let layer = (header[1] >> 1) & 0x03; // 1: Layer III
+    let bitrate_index = (header[2] >> 4) as usize;
+    let rate_index = ((header[2] >> 2) & 0x03) as usize;
+
+    if version == 1 || layer != 1 || bitrate_index == 0 || bitrate_index == 15 || rate_index == 3 {
+        return None;
+    }
+
+    let mpeg1 = version == 3;
+    let sample_rate = match version {
+        3 => [44100, 48000, 32000][rate_index],
+        2 => [22050, 24000, 16000][rate_index],
+        _ => [11025, 12000, 8000][rate_index],
+    };
+    let bitrate_kbps = if mpeg1 {
+        BITRATES_V1[bitrate_index]
+    } else {
+        BITRATES_V2[bitrate_index]
+    };
+
+    Some(FrameInfo {
+        mpeg1,
+        mono: header[3] >> 6 == 3,
+        bitrate_kbps,
+        sample_rate,
+        samples_per_frame: if mpeg1 { 1152 } else { 576 },
+    })
+}
+
+fn read_flac(file: &mut File) -> Result<Tags> {
+    let mut tags = Tags::default();
+
+    loop {
+        let mut header = [0u8; 4];
+        if read_up_to(file, &mut header)? < 4 {
+            break;
+        }
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7f;
+        let len = be_u32(&header[1..4]) as usize;
+
+        match block_type {
+            // STREAMINFO
+            0 => {
+                let mut block = vec![0u8; len];
+                file.read_exact(&mut block)?;
+                if block.len() >= 18 {
+                    let sample_rate = (be_u32(&block[10..13]) >> 4) as u64;
+                    let total_samples =
+                        ((block[13] as u64 & 0x0f) << 32) | be_u32(&block[14..18]) as u64;
+                    if sample_rate > 0 && total_samples > 0 {
+                        tags.duration_ms = Some(total_samples * 1000 / sample_rate);
+                    }
+                }
+            }
+            // VORBIS_COMMENT
+            4 => {
+                let mut block = vec![0u8; len];
+                file.read_exact(&mut block)?;
+                apply_vorbis_comments(&block, &mut tags);
+            }
+            _ => {
+                file.seek(SeekFrom::Current(len as i64))?;
+            }
+        }
+
+        if is_last {
+            break;
+        }
+    }
+
+    Ok(tags)
+}
+
+fn apply_vorbis_comments(block: &[u8], tags: &mut Tags) {
+    let le_u32 = |pos: usize| -> Option<usize> {
+        let bytes = block.get(pos..pos + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    };
+
+    let Some(vendor_len) = le_u32(0) else {
+        return;
+    };
+    let mut pos = 4 + vendor_len;
+    let Some(count) = le_u32(pos) else {
+        return;
+    };
+    pos += 4;
+
+    for _ in 0..count {
+        let Some(len) = le_u32(pos) else {
+            return;
+        };
+        pos += 4;
+        let Some(comment) = block.get(pos..pos + len) else {
+            return;
+        };
+        pos += len;
+
+        let comment = String::from_utf8_lossy(comment);
+        let Some((key, value)) = comment.split_once('=') else {
+            continue;
+        };
+        let value = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+
+        match key.to_ascii_uppercase().as_str() {
+            "TITLE" if tags.title.is_none() => tags.title = value,
+            "ARTIST" if tags.artist.is_none() => tags.artist = value,
+            "ISRC" if tags.isrc.is_none() => tags.isrc = value,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn id3_frame(id: &[u8], text: &str) -> Vec<u8> {
+        let mut data = vec![3u8]; // UTF-8
+        data.extend_from_slice(text.as_bytes());
+
+        let mut frame = id.to_vec();
+        let size = data.len() as u32;
+        frame.extend_from_slice(&[
+            (size >> 21) as u8 & 0x7f,
+            (size >> 14) as u8 & 0x7f,
+            (size >> 7) as u8 & 0x7f,
+            size as u8 & 0x7f,
+        ]);
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(&data);
+        frame
+    }
+
+    #[test]
+    fn test_read_id3v24_with_cbr_duration() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("song.mp3");
+
+        let mut body = Vec::new();
+        body.extend(id3_frame(b"TIT2", "Café del Mar"));
+        body.extend(id3_frame(b"TPE1", "Energy 52"));
+        body.extend(id3_frame(b"TSRC", "DEA129700001"));
+
+        let size = body.len() as u32;
+        let mut file = b"ID3\x04\x00\x00".to_vec();
+        file.extend_from_slice(&[
+            (size >> 21) as u8 & 0x7f,
+            (size >> 14) as u8 & 0x7f,
+            (size >> 7) as u8 & 0x7f,
+            size as u8 & 0x7f,
+        ]);
+        file.extend(body);
+
+        // MPEG1 Layer III, 128 kbps, 44.1 kHz, stereo; 10 seconds of audio
+        let audio_len = 128_000 / 8 * 10;
+        let mut audio = vec![0u8; audio_len];
+        audio[..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0x00]);
+        file.extend(audio);
+        fs::write(&path, file).unwrap();
+
+        let tags = read(&path).unwrap();
+        assert_eq!(tags.title.as_deref(), Some("Café del Mar"));
+        assert_eq!(tags.artist.as_deref(), Some("Energy 52"));
+        assert_eq!(tags.isrc.as_deref(), Some("DEA129700001"));
+        assert_eq!(tags.duration_ms, Some(10_000));
+    }
+
+    #[test]
+    fn test_read_flac() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("song.flac");
+
+        let mut streaminfo = vec![0u8; 34];
+        // 44100 Hz, 441000 samples = 10 seconds
+        let sample_rate: u32 = 44100;
+        streaminfo[10] = (sample_rate >> 12) as u8;
+        streaminfo[11] = (sample_rate >> 4) as u8;
+        streaminfo[12] = ((sample_rate & 0x0f) << 4) as u8;
+        streaminfo[14..18].copy_from_slice(&441_000u32.to_be_bytes());
+
+        let mut comments = Vec::new();
+        comments.extend_from_slice(&4u32.to_le_bytes());
+        comments.extend_from_slice(b"grit");
+        let entries = ["TITLE=Windowlicker", "artist=Aphex Twin"];
+        comments.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        for entry in entries {
+            comments.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+            comments.extend_from_slice(entry.as_bytes());
+        }
+
+        let mut file = b"fLaC".to_vec();
+        file.push(0);
+        file.extend_from_slice(&(streaminfo.len() as u32).to_be_bytes()[1..]);
+        file.extend(streaminfo);
+        file.push(0x80 | 4);
+        file.extend_from_slice(&(comments.len() as u32).to_be_bytes()[1..]);
+        file.extend(comments);
+        fs::write(&path, file).unwrap();
+
+        let tags = read(&path).unwrap();
+        assert_eq!(tags.title.as_deref(), Some("Windowlicker"));
+        assert_eq!(tags.artist.as_deref(), Some("Aphex Twin"));
+        assert_eq!(tags.duration_ms, Some(10_000));
+    }
+
+    #[test]
+    fn test_read_id3v1_fallback() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("old.mp3");
+
+        let mut tag = vec![0u8; 128];
+        tag[..3].copy_from_slice(b"TAG");
+        tag[3..8].copy_from_slice(b"Title");
+        tag[33..39].copy_from_slice(b"Artist");
+        fs::write(&path, tag).unwrap();
+
+        let tags = read(&path).unwrap();
+        assert_eq!(tags.title.as_deref(), Some("Title"));
+        assert_eq!(tags.artist.as_deref(), Some("Artist"));
+    }
+
+    #[test]
+    fn test_no_duration_for_other_formats() {
+        let temp = TempDir::new().unwrap();
+
+        // Bytes that pass for an MPEG frame header inside an M4A file
+        let mut data = b"\x00\x00\x00\x20ftypM4A ".to_vec();
+        data.resize(64, 0);
+        data.extend_from_slice(&[0xff, 0xfb, 0x90, 0x00]);
+        data.resize(16_000, 0);
+
+        let m4a = temp.path().join("song.m4a");
+        fs::write(&m4a, &data).unwrap();
+        assert_eq!(read(&m4a).unwrap().duration_ms, None);
+
+        // The same bytes in a file named .mp3 are scanned
+        let mp3 = temp.path().join("song.mp3");
+        fs::write(&mp3, &data).unwrap();
+        assert!(read(&mp3).unwrap().duration_ms.is_some());
+    }
+}
@@ -1,9 +1,20 @@
+#[cfg(test)]
+pub mod fake;
+pub mod local;
+pub mod plugin;
+mod reorder;
 pub mod spotify;
+pub mod subsonic;
 mod traits;
 mod types;
 pub mod youtube;
 
+#[cfg(test)]
+pub use fake::FakeProvider;
+pub use local::LocalProvider;
+pub use plugin::PluginProvider;
 pub use spotify::SpotifyProvider;
+pub use subsonic::SubsonicProvider;
 pub use traits::Provider;
 pub use types::*;
 pub use youtube::YoutubeProvider;
@@ -0,0 +1,385 @@
+//! External providers: a `grit-provider-<name>` executable on `PATH` that
+//! speaks JSON-RPC 2.0 over stdin/stdout, one message per line.
+//!
+//! The process is started on first use and kept for the rest of the
+//! command. Its stderr goes to the terminal.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::Mutex,
+};
+
+use crate::provider::{
+    DiffPatch, OAuthToken, PlaylistSnapshot, Provider, ProviderKind, RemotePlaylist, SourceKind,
+    Track,
+};
+
+const EXECUTABLE_PREFIX: &str = "grit-provider-";
+
+/// JSON-RPC "method not found"
+const METHOD_NOT_FOUND: i64 = -32601;
+
+pub struct PluginProvider {
+    name: String,
+    executable: PathBuf,
+    grit_dir: PathBuf,
+    session: Mutex<Option<Session>>,
+}
+
+struct Session {
+    // Held so the process is killed when the provider is dropped
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: Option<u64>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// A track as plugins send and receive it: grit's `Track` without the
+/// provider, which is implied.
+#[derive(Serialize, Deserialize)]
+struct WireTrack {
+    id: String,
+    name: String,
+    #[serde(default)]
+    artists: Vec<String>,
+    #[serde(default)]
+    duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metadata: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WirePlaylist {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default)]
+    tracks: Vec<WireTrack>,
+}
+
+impl From<&Track> for WireTrack {
+    fn from(track: &Track) -> Self {
+        Self {
+            id: track.id.clone(),
+            name: track.name.clone(),
+            artists: track.artists.clone(),
+            duration_ms: track.duration_ms,
+            metadata: track.metadata.clone(),
+        }
+    }
+}
+
+/// Path of the `grit-provider-<name>` executable, if it is on `PATH`.
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}", EXECUTABLE_PREFIX, name);
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+impl PluginProvider {
+    pub fn new(name: &str, executable: impl Into<PathBuf>, grit_dir: &Path) -> Self {
+        Self {
+            name: name.to_string(),
+            executable: executable.into(),
+            grit_dir: grit_dir.to_path_buf(),
+            session: Mutex::new(None),
+        }
+    }
+
+    /// The plugin named `name`, looked up on `PATH`.
+    pub fn find(name: &str, grit_dir: &Path) -> Result<Self> {
+        let executable = find_executable(name).with_context(|| {
+            format!(
+                "Unknown provider '{}': no {}{} executable on PATH",
+                name, EXECUTABLE_PREFIX, name
+            )
+        })?;
+        Ok(Self::new(name, executable, grit_dir))
+    }
+
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Plugin(self.name.clone())
+    }
+
+    fn spawn(&self) -> Result<Session> {
+        let mut child = Command::new(&self.executable)
+            .env("GRIT_DIR", &self.grit_dir)
+            .env("GRIT_PROVIDER", &self.name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start plugin {:?}", self.executable))?;
+
+        let stdin = child.stdin.take().context("Plugin stdin unavailable")?;
+        let stdout = child.stdout.take().context("Plugin stdout unavailable")?;
+        Ok(Session {
+            _child: child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            next_id: 1,
+        })
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let mut guard = self.session.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.spawn()?);
+        }
+        let session = guard.as_mut().expect("session started above");
+
+        let id = session.next_id;
+        session.next_id += 1;
+
+        let mut line = serde_json::to_string(&Request {
+            jsonrpc: "2.0",
+            id,
+            method,
+            params,
+        })
+        .context("Failed to serialize plugin request")?;
+        line.push('\n');
+
+        session
+            .stdin
+            .write_all(line.as_bytes())
+            .await
+            .with_context(|| format!("Failed to write to the {} plugin", self.name))?;
+        session.stdin.flush().await?;
+
+        let mut reply = String::new();
+        let read = session
+            .stdout
+            .read_line(&mut reply)
+            .await
+            .with_context(|| format!("Failed to read from the {} plugin", self.name))?;
+        if read == 0 {
+            *guard = None;
+            bail!("The {} plugin exited during '{}'", self.name, method);
+        }
+
+        let response: Response = serde_json::from_str(&reply).with_context(|| {
+            format!(
+                "Invalid response from the {} plugin: {}",
+                self.name,
+                reply.trim()
+            )
+        })?;
+        if response.id != Some(id) {
+            bail!(
+                "The {} plugin answered request {:?}, expected {}",
+                self.name,
+                response.id,
+                id
+            );
+        }
+
+        if let Some(error) = response.error {
+            if error.code == METHOD_NOT_FOUND {
+                bail!("The {} plugin doesn't support '{}'", self.name, method);
+            }
+            bail!("{}: {}", self.name, error.message);
+        }
+
+        serde_json::from_value(response.result.unwrap_or(Value::Null)).with_context(|| {
+            format!(
+                "Unexpected '{}' result from the {} plugin",
+                method, self.name
+            )
+        })
+    }
+
+    fn track(&self, wire: WireTrack) -> Track {
+        Track {
+            id: wire.id,
+            name: wire.name,
+            artists: wire.artists,
+            duration_ms: wire.duration_ms,
+            provider: self.kind(),
+            metadata: wire.metadata,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for PluginProvider {
+    async fn fetch(&self, playlist_id: &str) -> Result<PlaylistSnapshot> {
+        let playlist: WirePlaylist = self
+            .call("fetch", json!({ "playlist_id": playlist_id }))
+            .await?;
+
+        Ok(PlaylistSnapshot {
+            id: playlist.id.unwrap_or_else(|| playlist_id.to_string()),
+            name: playlist.name,
+            description: playlist.description,
+            tracks: playlist.tracks.into_iter().map(|t| self.track(t)).collect(),
+            provider: self.kind(),
+            source: SourceKind::Playlist,
+            snapshot_hash: String::new(),
+            metadata: None,
+        })
+    }
+
+    async fn apply(
+        &self,
+        playlist_id: &str,
+        patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        let desired = WirePlaylist {
+            id: Some(desired_state.id.clone()),
+            name: desired_state.name.clone(),
+            description: desired_state.description.clone(),
+            tracks: desired_state.tracks.iter().map(WireTrack::from).collect(),
+        };
+        let _: Value = self
+            .call(
+                "apply",
+                json!({ "playlist_id": playlist_id, "patch": patch, "desired": desired }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn playable_url(&self, track: &Track) -> Result<String> {
+        self.call("playable_url", json!({ "track": WireTrack::from(track) }))
+            .await
+    }
+
+    async fn fetch_track(&self, track_id: &str) -> Result<Track> {
+        let track: WireTrack = self
+            .call("fetch_track", json!({ "track_id": track_id }))
+            .await?;
+        Ok(self.track(track))
+    }
+
+    async fn search_by_query(&self, query: &str) -> Result<Vec<Track>> {
+        let tracks: Vec<WireTrack> = self
+            .call("search_by_query", json!({ "query": query }))
+            .await?;
+        Ok(tracks.into_iter().map(|t| self.track(t)).collect())
+    }
+
+    fn oauth_url(&self, _redirect_uri: &str, _state: &str) -> String {
+        String::new()
+    }
+
+    async fn exchange_code(&self, _code: &str, _redirect_uri: &str) -> Result<OAuthToken> {
+        bail!("Plugins handle their own authentication")
+    }
+
+    async fn refresh_token(&self, _token: &OAuthToken) -> Result<OAuthToken> {
+        bail!("Plugins handle their own authentication")
+    }
+
+    async fn can_modify_playlist(&self, playlist_id: &str) -> Result<bool> {
+        self.call("can_modify_playlist", json!({ "playlist_id": playlist_id }))
+            .await
+    }
+
+    async fn list_playlists(&self) -> Result<Vec<RemotePlaylist>> {
+        self.call("list_playlists", json!({})).await
+    }
+
+    async fn create_playlist(&self, name: &str, description: Option<&str>) -> Result<String> {
+        self.call(
+            "create_playlist",
+            json!({ "name": name, "description": description }),
+        )
+        .await
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    const SCRIPT: &str = r#"#!/bin/sh
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed 's/^{"jsonrpc":"2.0","id":\([0-9][0-9]*\).*/\1/')
+  case "$line" in
+    *'"method":"fetch"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"name":"Mix","tracks":[{"id":"t1","name":"Song","artists":["A"],"duration_ms":1000}]}}\n' "$id" ;;
+    *'"method":"can_modify_playlist"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":true}\n' "$id" ;;
+    *'"method":"fetch_track"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"error":{"code":1,"message":"no such track"}}\n' "$id" ;;
+    *)
+      printf '{"jsonrpc":"2.0","id":%s,"error":{"code":-32601,"message":"Method not found"}}\n' "$id" ;;
+  esac
+done
+"#;
+
+    fn plugin(temp: &TempDir) -> PluginProvider {
+        let path = temp.path().join("grit-provider-test");
+        std::fs::write(&path, SCRIPT).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        PluginProvider::new("test", path, temp.path())
+    }
+
+    #[tokio::test]
+    async fn test_calls_over_stdio() {
+        let temp = TempDir::new().unwrap();
+        let provider = plugin(&temp);
+
+        let snap = provider.fetch("pl1").await.unwrap();
+        assert_eq!(snap.id, "pl1");
+        assert_eq!(snap.name, "Mix");
+        assert_eq!(snap.tracks[0].artists, vec!["A"]);
+        assert_eq!(
+            snap.tracks[0].provider,
+            ProviderKind::Plugin("test".to_string())
+        );
+
+        // Same process answers later requests
+        assert!(provider.can_modify_playlist("pl1").await.unwrap());
+
+        let err = provider.fetch_track("t9").await.unwrap_err();
+        assert_eq!(err.to_string(), "test: no such track");
+
+        let err = provider.search_by_query("x").await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("doesn't support 'search_by_query'"));
+    }
+}
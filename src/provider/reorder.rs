@@ -0,0 +1,203 @@
+//! Planning the moves that turn a playlist's current order into the desired
+//! one, computed locally so providers don't refetch between moves.
+
+use std::collections::{HashMap, VecDeque};
+
+/// Move `range_length` items starting at `range_start` so they come before
+/// the item at `insert_before`, both indexes taken before the move (the
+/// semantics of Spotify's reorder endpoint).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeMove {
+    pub range_start: usize,
+    pub range_length: usize,
+    pub insert_before: usize,
+}
+
+impl RangeMove {
+    pub fn apply<T>(&self, items: &mut Vec<T>) {
+        let moved: Vec<T> = items
+            .drain(self.range_start..self.range_start + self.range_length)
+            .collect();
+        let at = if self.insert_before > self.range_start {
+            self.insert_before - self.range_length
+        } else {
+            self.insert_before
+        };
+        items.splice(at..at, moved);
+    }
+
+    /// The same move as single-item moves `(from, to)`, where `to` is the
+    /// item's index after it moves (the semantics of YouTube's `position`).
+    pub fn single_moves(&self) -> Vec<(usize, usize)> {
+        if self.insert_before < self.range_start {
+            (0..self.range_length)
+                .map(|i| (self.range_start + i, self.insert_before + i))
+                .collect()
+        } else {
+            vec![(self.range_start, self.insert_before - 1); self.range_length]
+        }
+    }
+}
+
+/// Range moves that reorder `current` into `desired`, applied in order.
+///
+/// Items on the longest subsequence already in desired order stay put; the
+/// others are moved next to their predecessor, in runs where they are
+/// already adjacent. Duplicates are matched by occurrence, and items missing
+/// from `desired` end up last in their current order.
+pub fn plan_moves<T: Eq + std::hash::Hash>(current: &[T], desired: &[T]) -> Vec<RangeMove> {
+    let mut order = ranks(current, desired);
+    let n = order.len();
+
+    let mut placed = vec![false; n];
+    for i in longest_increasing(&order) {
+        placed[order[i]] = true;
+    }
+
+    let mut moves = Vec::new();
+    for key in 0..n {
+        if placed[key] {
+            continue;
+        }
+
+        let start = position(&order, key);
+        let mut length = 1;
+        while start + length < n && order[start + length] == key + length && !placed[key + length] {
+            length += 1;
+        }
+
+        let target = match key {
+            0 => 0,
+            _ => position(&order, key - 1) + 1,
+        };
+        if target != start {
+            let range_move = RangeMove {
+                range_start: start,
+                range_length: length,
+                insert_before: target,
+            };
+            range_move.apply(&mut order);
+            moves.push(range_move);
+        }
+
+        placed[key..key + length].fill(true);
+    }
+
+    moves
+}
+
+/// The rank of each current item in the desired order, 0..current.len().
+fn ranks<T: Eq + std::hash::Hash>(current: &[T], desired: &[T]) -> Vec<usize> {
+    let mut slots: HashMap<&T, VecDeque<usize>> = HashMap::new();
+    for (i, item) in desired.iter().enumerate() {
+        slots.entry(item).or_default().push_back(i);
+    }
+
+    // Unmatched items sort after every desired position
+    let keys: Vec<usize> = current
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            slots
+                .get_mut(item)
+                .and_then(VecDeque::pop_front)
+                .unwrap_or(desired.len() + i)
+        })
+        .collect();
+
+    let mut sorted: Vec<usize> = (0..keys.len()).collect();
+    sorted.sort_by_key(|&i| keys[i]);
+    let mut ranks = vec![0; keys.len()];
+    for (rank, i) in sorted.into_iter().enumerate() {
+        ranks[i] = rank;
+    }
+    ranks
+}
+
+/// Indexes of one longest strictly increasing subsequence of `values`.
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // tails[k]: index of the smallest tail of an increasing run of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; values.len()];
+
+    for (i, &value) in values.iter().enumerate() {
+        let k = tails.partition_point(|&t| values[t] < value);
+        previous[i] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+
+    let mut indexes = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        indexes.push(i);
+        next = previous[i];
+    }
+    indexes.reverse();
+    indexes
+}
+
+fn position(order: &[usize], key: usize) -> usize {
+    order.iter().position(|&k| k == key).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reorder(current: &[&str], desired: &[&str]) -> (Vec<String>, usize) {
+        let moves = plan_moves(current, desired);
+        let mut items: Vec<String> = current.iter().map(|s| s.to_string()).collect();
+        for range_move in &moves {
+            range_move.apply(&mut items);
+        }
+
+        // Single-item moves must give the same result
+        let mut singles: Vec<String> = current.iter().map(|s| s.to_string()).collect();
+        for (from, to) in moves.iter().flat_map(RangeMove::single_moves) {
+            let item = singles.remove(from);
+            singles.insert(to, item);
+        }
+        assert_eq!(items, singles);
+
+        (items, moves.len())
+    }
+
+    #[test]
+    fn test_plan_moves_reaches_desired_order() {
+        let cases: [(&[&str], &[&str]); 6] = [
+            (&["a", "b", "c"], &["a", "b", "c"]),
+            (&["a", "b", "c", "d"], &["d", "c", "b", "a"]),
+            (&["a", "b", "c", "d", "e"], &["c", "d", "e", "a", "b"]),
+            (&["x", "a", "b", "y", "c"], &["a", "b", "c", "x", "y"]),
+            (&["a", "b", "a", "c"], &["a", "c", "a", "b"]),
+            (&["e", "a", "d", "b", "c"], &["a", "b", "c", "d", "e"]),
+        ];
+        for (current, desired) in cases {
+            assert_eq!(reorder(current, desired).0, desired);
+        }
+    }
+
+    #[test]
+    fn test_plan_moves_moves_runs_together() {
+        // One track moved to the end
+        assert_eq!(reorder(&["a", "b", "c", "d"], &["b", "c", "d", "a"]).1, 1);
+        // A block of three moved as one range
+        let (_, moves) = reorder(
+            &["a", "b", "c", "d", "e", "f"],
+            &["d", "e", "f", "a", "b", "c"],
+        );
+        assert_eq!(moves, 1);
+        // Reversal needs n - 1 moves
+        assert_eq!(reorder(&["a", "b", "c", "d"], &["d", "c", "b", "a"]).1, 3);
+    }
+
+    #[test]
+    fn test_plan_moves_keeps_unknown_items_last() {
+        let (items, _) = reorder(&["z", "b", "a"], &["a", "b"]);
+        assert_eq!(items, vec!["a", "b", "z"]);
+    }
+}
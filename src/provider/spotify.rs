@@ -1,9 +1,14 @@
+use crate::provider::reorder::plan_moves;
 use crate::provider::{
-    DiffPatch, OAuthToken, PlaylistSnapshot, Provider, ProviderKind, Track, TrackChange,
+    Capabilities, DiffPatch, Endpoints, OAuthToken, PlaylistSnapshot, Provider, ProviderKind,
+    RemotePlaylist, SourceKind, Track, TrackChange,
 };
-use anyhow::{Context, Result};
+use crate::state::diff::occurrence_changes;
+use crate::utils::http::{HttpClient, RequestBuilder};
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use serde::Deserialize;
+use std::collections::HashMap;
 use tokio::sync::Mutex;
 
 const AUTH_URL: &str = "https://accounts.spotify.com/authorize";
@@ -15,7 +20,12 @@ pub struct SpotifyProvider {
     client_secret: String,
     token: Mutex<Option<OAuthToken>>,
     grit_dir: Option<std::path::PathBuf>,
-    http: reqwest::Client,
+    http: HttpClient,
+    endpoints: Endpoints,
+    search_limit: usize,
+    /// Track IDs and snapshot ID of each playlist as last fetched or
+    /// changed, for `apply_known`
+    known: std::sync::Mutex<HashMap<String, (Vec<String>, String)>>,
 }
 
 #[derive(Deserialize)]
@@ -41,6 +51,39 @@ struct SpotifyPlaylist {
 #[derive(Deserialize)]
 struct SpotifyOwner {
     id: String,
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SpotifyPlaylistPage {
+    items: Vec<SpotifyPlaylistSummary>,
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SpotifyPlaylistSummary {
+    id: String,
+    name: String,
+    owner: SpotifyOwner,
+    #[serde(default)]
+    collaborative: bool,
+    tracks: SpotifyTrackCount,
+}
+
+#[derive(Deserialize)]
+struct SpotifyTrackCount {
+    total: usize,
+}
+
+#[derive(Deserialize)]
+struct SpotifySnapshotResponse {
+    snapshot_id: String,
+}
+
+#[derive(Deserialize)]
+struct SpotifyCreatedPlaylist {
+    id: String,
 }
 
 #[derive(Deserialize)]
@@ -65,6 +108,36 @@ struct SpotifyTrackObject {
     name: String,
     duration_ms: u64,
     artists: Vec<SpotifyArtist>,
+    #[serde(default)]
+    external_ids: Option<SpotifyExternalIds>,
+    /// Missing from an album's own track list
+    #[serde(default)]
+    album: Option<SpotifyAlbumName>,
+}
+
+#[derive(Deserialize)]
+struct SpotifyAlbumName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct SpotifyExternalIds {
+    isrc: Option<String>,
+}
+
+impl SpotifyTrackObject {
+    /// Track metadata carrying the ISRC, used for cross-provider matching,
+    /// and the album name, used by exports.
+    fn metadata(&self) -> Option<serde_json::Value> {
+        let mut metadata = serde_json::Map::new();
+        if let Some(isrc) = self.external_ids.as_ref().and_then(|e| e.isrc.as_ref()) {
+            metadata.insert("isrc".to_string(), isrc.clone().into());
+        }
+        if let Some(album) = &self.album {
+            metadata.insert("album".to_string(), album.name.clone().into());
+        }
+        (!metadata.is_empty()).then_some(serde_json::Value::Object(metadata))
+    }
 }
 
 #[derive(Deserialize)]
@@ -131,10 +204,31 @@ impl SpotifyProvider {
             client_secret,
             token: Mutex::new(None),
             grit_dir: None,
-            http: reqwest::Client::new(),
+            http: HttpClient::new(),
+            endpoints: Self::default_endpoints(),
+            search_limit: 10,
+            known: Default::default(),
+        }
+    }
+
+    pub fn default_endpoints() -> Endpoints {
+        Endpoints {
+            api: API_BASE.to_string(),
+            auth: AUTH_URL.to_string(),
+            token: TOKEN_URL.to_string(),
         }
     }
 
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.search_limit = limit;
+        self
+    }
+
     pub fn with_token(mut self, token: &OAuthToken, grit_dir: &std::path::Path) -> Self {
         self.token = Mutex::new(Some(token.clone()));
         self.grit_dir = Some(grit_dir.to_path_buf());
@@ -164,12 +258,12 @@ impl SpotifyProvider {
         drop(token_guard);
 
         if Self::is_token_expired(&current_token) {
-            println!("Token expired, refreshing...");
+            eprintln!("Token expired, refreshing...");
             let new_token = self.refresh_token(&current_token).await?;
 
             if let Some(grit_dir) = &self.grit_dir {
                 use crate::state::credentials;
-                credentials::save(grit_dir, ProviderKind::Spotify, &new_token)?;
+                credentials::save(grit_dir, &ProviderKind::Spotify, &new_token)?;
             }
 
             *self.token.lock().await = Some(new_token.clone());
@@ -188,7 +282,8 @@ impl SpotifyProvider {
     async fn token_request(&self, params: &[(&str, &str)]) -> Result<SpotifyTokenResponse> {
         let response = self
             .http
-            .post(TOKEN_URL)
+            .post(&self.endpoints.token)
+            .read_only()
             .header(
                 "Authorization",
                 format!("Basic {}", self.basic_auth_header()),
@@ -230,14 +325,122 @@ impl SpotifyProvider {
             .context("Failed to parse API response")
     }
 
+    /// Send a change to a playlist's tracks and return the playlist's new
+    /// snapshot ID.
+    async fn send_change(
+        &self,
+        request: RequestBuilder,
+        token: &str,
+        body: &serde_json::Value,
+    ) -> Result<String> {
+        let response: SpotifySnapshotResponse = request
+            .header("Authorization", format!("Bearer {}", token))
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse Spotify playlist change response")?;
+        Ok(response.snapshot_id)
+    }
+
+    /// Change the playlist from `current_ids`, as of `snapshot_id`, to
+    /// `desired_state` without fetching it: removals, additions at the end,
+    /// then range moves, each made against the last snapshot ID.
+    async fn apply_tracks(
+        &self,
+        playlist_id: &str,
+        mut current_ids: Vec<String>,
+        mut snapshot_id: String,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        let token = self.get_token().await?;
+        let url = format!("{}/playlists/{}/tracks", self.endpoints.api, playlist_id);
+        let desired_ids: Vec<&str> = desired_state.tracks.iter().map(|t| t.id.as_str()).collect();
+
+        // Step 1: Remove tracks with more copies than wanted. Removing a URI
+        // removes every copy; the ones still wanted are added back below
+        // Batch removals - Spotify allows up to 100 tracks per request
+        let current: Vec<&str> = current_ids.iter().map(String::as_str).collect();
+        let (removals, _) = occurrence_changes(&current, &desired_ids);
+        let mut ids_to_remove: Vec<String> = Vec::new();
+        for i in removals {
+            if !ids_to_remove.iter().any(|id| id == current[i]) {
+                ids_to_remove.push(current[i].to_string());
+            }
+        }
+
+        for chunk in ids_to_remove.chunks(100) {
+            let tracks: Vec<serde_json::Value> = chunk
+                .iter()
+                .map(|id| serde_json::json!({"uri": format!("spotify:track:{}", id)}))
+                .collect();
+            let body = serde_json::json!({
+                "tracks": tracks,
+                "snapshot_id": snapshot_id
+            });
+
+            // Removing a URI that is already gone changes nothing, so this
+            // can be retried; the moves below can't
+            snapshot_id = self
+                .send_change(self.http.delete(&url).idempotent(), &token, &body)
+                .await?;
+            current_ids.retain(|id| !chunk.contains(id));
+        }
+
+        // Step 2: Add missing copies to the END (we'll reorder later)
+        // Batch additions - Spotify allows up to 100 tracks per request
+        let current: Vec<&str> = current_ids.iter().map(String::as_str).collect();
+        let (_, additions) = occurrence_changes(&current, &desired_ids);
+        let ids_to_add: Vec<&str> = additions.into_iter().map(|i| desired_ids[i]).collect();
+
+        for chunk in ids_to_add.chunks(100) {
+            let uris: Vec<String> = chunk
+                .iter()
+                .map(|id| format!("spotify:track:{}", id))
+                .collect();
+            let body = serde_json::json!({
+                "uris": uris
+            });
+
+            snapshot_id = self
+                .send_change(self.http.post(&url), &token, &body)
+                .await?;
+            current_ids.extend(chunk.iter().map(|id| id.to_string()));
+        }
+
+        // Step 3: Reorder playlist to match desired state
+        let mut order: Vec<&str> = current_ids.iter().map(String::as_str).collect();
+        for range_move in plan_moves(&order, &desired_ids) {
+            let body = serde_json::json!({
+                "range_start": range_move.range_start,
+                "insert_before": range_move.insert_before,
+                "range_length": range_move.range_length,
+                "snapshot_id": snapshot_id
+            });
+
+            snapshot_id = self.send_change(self.http.put(&url), &token, &body).await?;
+            range_move.apply(&mut order);
+        }
+
+        let ids = order.into_iter().map(str::to_string).collect();
+        self.known
+            .lock()
+            .unwrap()
+            .insert(playlist_id.to_string(), (ids, snapshot_id));
+        Ok(())
+    }
+
     pub async fn fetch_album(&self, album_id: &str) -> Result<PlaylistSnapshot> {
         let token = self.get_token().await?;
-        let url = format!("{}/albums/{}", API_BASE, album_id);
+        let url = format!("{}/albums/{}", self.endpoints.api, album_id);
 
         let album: SpotifyAlbum = self.api_get(&url, &token).await?;
 
         let mut all_tracks = Vec::new();
 
+        let metadata = Some(serde_json::json!({ "album": album.name }));
         for track in album.tracks.items {
             all_tracks.push(Track {
                 id: track.id,
@@ -245,7 +448,7 @@ impl SpotifyProvider {
                 artists: track.artists.into_iter().map(|a| a.name).collect(),
                 duration_ms: track.duration_ms,
                 provider: ProviderKind::Spotify,
-                metadata: None,
+                metadata: metadata.clone(),
             });
         }
 
@@ -260,7 +463,7 @@ impl SpotifyProvider {
                     artists: track.artists.into_iter().map(|a| a.name).collect(),
                     duration_ms: track.duration_ms,
                     provider: ProviderKind::Spotify,
-                    metadata: None,
+                    metadata: metadata.clone(),
                 });
             }
 
@@ -275,10 +478,130 @@ impl SpotifyProvider {
             description: Some(format!("Album by {}", album_artists.join(", "))),
             tracks: all_tracks,
             provider: ProviderKind::Spotify,
+            source: SourceKind::Album,
+            snapshot_hash: String::new(),
+            metadata: None,
+        })
+    }
+
+    /// Saved tracks of the account ("Liked Songs"), most recently liked
+    /// first, as a snapshot with ID `id`.
+    pub async fn fetch_liked(&self, id: &str) -> Result<PlaylistSnapshot> {
+        let token = self.get_token().await?;
+
+        let mut all_tracks = Vec::new();
+        let mut next_url = Some(format!("{}/me/tracks?limit=50", self.endpoints.api));
+        while let Some(url) = next_url {
+            let response = self
+                .http
+                .get(&url)
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .context("Failed to send API request")?;
+            let page: SpotifyTracks = check_library_access(response)
+                .await?
+                .json()
+                .await
+                .context("Failed to parse API response")?;
+
+            for item in page.items {
+                if let Some(track) = item.track {
+                    all_tracks.push(Track {
+                        metadata: track.metadata(),
+                        id: track.id,
+                        name: track.name,
+                        artists: track.artists.into_iter().map(|a| a.name).collect(),
+                        duration_ms: track.duration_ms,
+                        provider: ProviderKind::Spotify,
+                    });
+                }
+            }
+
+            next_url = page.next;
+        }
+
+        Ok(PlaylistSnapshot {
+            id: id.to_string(),
+            name: "Liked Songs".to_string(),
+            description: None,
+            tracks: all_tracks,
+            provider: ProviderKind::Spotify,
+            source: SourceKind::Liked,
             snapshot_hash: String::new(),
             metadata: None,
         })
     }
+
+    /// Save added tracks and unsave removed ones. Moves are ignored: Spotify
+    /// orders liked songs by when they were saved.
+    async fn apply_liked(&self, patch: &DiffPatch) -> Result<()> {
+        let token = self.get_token().await?;
+
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        for change in &patch.changes {
+            match change {
+                TrackChange::Removed { track, .. } => removed.push(track.id.as_str()),
+                TrackChange::Added { track, index } => added.push((*index, track.id.as_str())),
+                TrackChange::Moved { .. } => {}
+            }
+        }
+
+        // Up to 50 IDs per request
+        let url = format!("{}/me/tracks", self.endpoints.api);
+        for chunk in removed.chunks(50) {
+            let response = self
+                .http
+                .delete(&url)
+                .idempotent()
+                .query(&[("ids", chunk.join(","))])
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await?;
+            check_library_access(response).await?;
+        }
+
+        // Tracks saved in one request share their save time, so each gets
+        // its own second, newest first as in the snapshot
+        added.sort();
+        let now = chrono::Utc::now();
+        let saves: Vec<serde_json::Value> = added
+            .iter()
+            .enumerate()
+            .map(|(i, (_, id))| {
+                let added_at = now - chrono::Duration::seconds(i as i64);
+                serde_json::json!({
+                    "id": id,
+                    "added_at": added_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                })
+            })
+            .collect();
+        for chunk in saves.chunks(50) {
+            let response = self
+                .http
+                .put(&url)
+                .idempotent()
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&serde_json::json!({ "timestamped_ids": chunk }))
+                .send()
+                .await?;
+            check_library_access(response).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Fail on an error status, pointing tokens from before liked songs were
+/// supported (without the library scopes) at signing in again.
+async fn check_library_access(response: reqwest::Response) -> Result<reqwest::Response> {
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        bail!(
+            "Spotify refused access to your liked songs; run 'grit auth spotify' again to grant it"
+        );
+    }
+    Ok(response.error_for_status()?)
 }
 
 #[async_trait]
@@ -291,12 +614,14 @@ impl Provider for SpotifyProvider {
             "playlist-modify-private",
             "user-modify-playback-state",
             "user-read-playback-state",
+            "user-library-read",
+            "user-library-modify",
         ]
         .join(" ");
 
         format!(
             "{}?client_id={}&response_type=code&redirect_uri={}&scope={}&state={}",
-            AUTH_URL,
+            self.endpoints.auth,
             urlencoding::encode(&self.client_id),
             urlencoding::encode(redirect_uri),
             urlencoding::encode(&scopes),
@@ -336,9 +661,38 @@ impl Provider for SpotifyProvider {
         Ok(new_token)
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            sources: vec![SourceKind::Playlist, SourceKind::Album, SourceKind::Liked],
+            ..Capabilities::default()
+        }
+    }
+
+    async fn fetch_source(&self, source: SourceKind, id: &str) -> Result<PlaylistSnapshot> {
+        match source {
+            SourceKind::Playlist => self.fetch(id).await,
+            SourceKind::Album => self.fetch_album(id).await,
+            SourceKind::Liked => self.fetch_liked(id).await,
+        }
+    }
+
+    async fn apply_source(
+        &self,
+        source: SourceKind,
+        id: &str,
+        patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        match source {
+            SourceKind::Playlist => self.apply(id, patch, desired_state).await,
+            SourceKind::Liked => self.apply_liked(patch).await,
+            SourceKind::Album => bail!("Albums can't be changed"),
+        }
+    }
+
     async fn fetch(&self, playlist_id: &str) -> Result<PlaylistSnapshot> {
         let token = self.get_token().await?;
-        let url = format!("{}/playlists/{}", API_BASE, playlist_id);
+        let url = format!("{}/playlists/{}", self.endpoints.api, playlist_id);
 
         let playlist: SpotifyPlaylist = self.api_get(&url, &token).await?;
 
@@ -347,12 +701,12 @@ impl Provider for SpotifyProvider {
         for item in playlist.tracks.items {
             if let Some(track) = item.track {
                 all_tracks.push(Track {
+                    metadata: track.metadata(),
                     id: track.id,
                     name: track.name,
                     artists: track.artists.into_iter().map(|a| a.name).collect(),
                     duration_ms: track.duration_ms,
                     provider: ProviderKind::Spotify,
-                    metadata: None,
                 });
             }
         }
@@ -364,12 +718,12 @@ impl Provider for SpotifyProvider {
             for item in page.items {
                 if let Some(track) = item.track {
                     all_tracks.push(Track {
+                        metadata: track.metadata(),
                         id: track.id,
                         name: track.name,
                         artists: track.artists.into_iter().map(|a| a.name).collect(),
                         duration_ms: track.duration_ms,
                         provider: ProviderKind::Spotify,
-                        metadata: None,
                     });
                 }
             }
@@ -377,12 +731,19 @@ impl Provider for SpotifyProvider {
             next_url = page.next;
         }
 
+        let ids = all_tracks.iter().map(|t| t.id.clone()).collect();
+        self.known
+            .lock()
+            .unwrap()
+            .insert(playlist_id.to_string(), (ids, playlist.snapshot_id.clone()));
+
         Ok(PlaylistSnapshot {
             id: playlist.id,
             name: playlist.name,
             description: playlist.description,
             tracks: all_tracks,
             provider: ProviderKind::Spotify,
+            source: SourceKind::Playlist,
             snapshot_hash: playlist.snapshot_id,
             metadata: None,
         })
@@ -391,106 +752,31 @@ impl Provider for SpotifyProvider {
     async fn apply(
         &self,
         playlist_id: &str,
-        patch: &DiffPatch,
+        _patch: &DiffPatch,
         desired_state: &PlaylistSnapshot,
     ) -> Result<()> {
-        let token = self.get_token().await?;
-
-        // Step 1: Remove tracks that shouldn't be there
-        // Batch removals - Spotify allows up to 100 tracks per request
-        let tracks_to_remove: Vec<serde_json::Value> = patch
-            .changes
-            .iter()
-            .filter_map(|change| {
-                if let TrackChange::Removed { track, .. } = change {
-                    Some(serde_json::json!({"uri": format!("spotify:track:{}", track.id)}))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        for chunk in tracks_to_remove.chunks(100) {
-            let body = serde_json::json!({
-                "tracks": chunk
-            });
-
-            let url = format!("{}/playlists/{}/tracks", API_BASE, playlist_id);
-
-            self.http
-                .delete(&url)
-                .header("Authorization", format!("Bearer {}", token))
-                .json(&body)
-                .send()
-                .await?
-                .error_for_status()?;
-        }
-
-        // Step 2: Add new tracks to the END (we'll reorder later)
-        // Batch additions - Spotify allows up to 100 tracks per request
-        let uris_to_add: Vec<String> = patch
-            .changes
-            .iter()
-            .filter_map(|change| {
-                if let TrackChange::Added { track, .. } = change {
-                    Some(format!("spotify:track:{}", track.id))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        for chunk in uris_to_add.chunks(100) {
-            let body = serde_json::json!({
-                "uris": chunk
-            });
-
-            self.http
-                .post(format!("{}/playlists/{}/tracks", API_BASE, playlist_id))
-                .header("Authorization", format!("Bearer {}", token))
-                .json(&body)
-                .send()
-                .await?
-                .error_for_status()?;
-        }
-
-        // Step 3: Reorder playlist to match desired state
-        // After removals and additions, reorder tracks to match desired order
-        // We process from the beginning, moving each track to its correct position
-
-        for (desired_idx, desired_track) in desired_state.tracks.iter().enumerate() {
-            // Fetch current state to find where this track is now
-            let current = self.fetch(playlist_id).await?;
-
-            let current_idx = current.tracks.iter().position(|t| t.id == desired_track.id);
-
-            if let Some(current_idx) = current_idx {
-                if current_idx != desired_idx {
-                    // Need to move this track to its correct position
-                    let insert_before = if current_idx < desired_idx {
-                        desired_idx + 1
-                    } else {
-                        desired_idx
-                    };
-
-                    let body = serde_json::json!({
-                        "range_start": current_idx,
-                        "insert_before": insert_before,
-                        "range_length": 1
-                    });
+        let current = self.fetch(playlist_id).await?;
+        let ids = current.tracks.into_iter().map(|t| t.id).collect();
+        self.apply_tracks(playlist_id, ids, current.snapshot_hash, desired_state)
+            .await
+    }
 
-                    self.http
-                        .put(format!("{}/playlists/{}/tracks", API_BASE, playlist_id))
-                        .header("Authorization", format!("Bearer {}", token))
-                        .json(&body)
-                        .send()
-                        .await?
-                        .error_for_status()?;
-                }
+    async fn apply_known(
+        &self,
+        source: SourceKind,
+        id: &str,
+        current: &PlaylistSnapshot,
+        patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        let known = self.known.lock().unwrap().get(id).cloned();
+        let matches = |ids: &Vec<String>| ids.iter().eq(current.tracks.iter().map(|t| &t.id));
+        match known {
+            Some((ids, snapshot_id)) if source == SourceKind::Playlist && matches(&ids) => {
+                self.apply_tracks(id, ids, snapshot_id, desired_state).await
             }
+            _ => self.apply_source(source, id, patch, desired_state).await,
         }
-
-        Ok(())
     }
 
     async fn playable_url(&self, track: &Track) -> Result<String> {
@@ -500,9 +786,10 @@ impl Provider for SpotifyProvider {
     async fn search_by_query(&self, query: &str) -> Result<Vec<Track>> {
         let token = self.get_token().await?;
         let url = format!(
-            "{}/search?q={}&type=track&limit=10",
-            API_BASE,
-            urlencoding::encode(query)
+            "{}/search?q={}&type=track&limit={}",
+            self.endpoints.api,
+            urlencoding::encode(query),
+            self.search_limit.clamp(1, 50)
         );
 
         let resp: SpotifySearchResponse = self.api_get(&url, &token).await?;
@@ -512,12 +799,12 @@ impl Provider for SpotifyProvider {
             .items
             .into_iter()
             .map(|track| Track {
+                metadata: track.metadata(),
                 id: track.id,
                 name: track.name,
                 artists: track.artists.into_iter().map(|a| a.name).collect(),
                 duration_ms: track.duration_ms,
                 provider: ProviderKind::Spotify,
-                metadata: None,
             })
             .collect();
 
@@ -526,29 +813,342 @@ impl Provider for SpotifyProvider {
 
     async fn fetch_track(&self, track_id: &str) -> Result<Track> {
         let token = self.get_token().await?;
-        let url = format!("{}/tracks/{}", API_BASE, track_id);
+        let url = format!("{}/tracks/{}", self.endpoints.api, track_id);
 
         let track: SpotifyTrackObject = self.api_get(&url, &token).await?;
 
         Ok(Track {
+            metadata: track.metadata(),
             id: track.id,
             name: track.name,
             artists: track.artists.into_iter().map(|a| a.name).collect(),
             duration_ms: track.duration_ms,
             provider: ProviderKind::Spotify,
-            metadata: None,
         })
     }
 
     async fn can_modify_playlist(&self, playlist_id: &str) -> Result<bool> {
         let token = self.get_token().await?;
 
-        let user_url = format!("{}/me", API_BASE);
+        let user_url = format!("{}/me", self.endpoints.api);
         let user: SpotifyUser = self.api_get(&user_url, &token).await?;
 
-        let playlist_url = format!("{}/playlists/{}", API_BASE, playlist_id);
+        let playlist_url = format!("{}/playlists/{}", self.endpoints.api, playlist_id);
         let playlist: SpotifyPlaylist = self.api_get(&playlist_url, &token).await?;
 
         Ok(playlist.owner.id == user.id || playlist.collaborative)
     }
+
+    async fn list_playlists(&self) -> Result<Vec<RemotePlaylist>> {
+        let token = self.get_token().await?;
+
+        let user_url = format!("{}/me", self.endpoints.api);
+        let user: SpotifyUser = self.api_get(&user_url, &token).await?;
+
+        let mut playlists = Vec::new();
+        let mut next_url = Some(format!("{}/me/playlists?limit=50", self.endpoints.api));
+        while let Some(url) = next_url {
+            let page: SpotifyPlaylistPage = self.api_get(&url, &token).await?;
+
+            for playlist in page.items {
+                playlists.push(RemotePlaylist {
+                    owned: playlist.owner.id == user.id || playlist.collaborative,
+                    owner: Some(playlist.owner.display_name.unwrap_or(playlist.owner.id)),
+                    id: playlist.id,
+                    name: playlist.name,
+                    tracks: Some(playlist.tracks.total),
+                });
+            }
+
+            next_url = page.next;
+        }
+
+        Ok(playlists)
+    }
+
+    async fn create_playlist(&self, name: &str, description: Option<&str>) -> Result<String> {
+        let token = self.get_token().await?;
+
+        let user_url = format!("{}/me", self.endpoints.api);
+        let user: SpotifyUser = self.api_get(&user_url, &token).await?;
+
+        let body = serde_json::json!({
+            "name": name,
+            "description": description.unwrap_or_default(),
+            "public": false
+        });
+
+        let created: SpotifyCreatedPlaylist = self
+            .http
+            .post(format!(
+                "{}/users/{}/playlists",
+                self.endpoints.api, user.id
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse created playlist")?;
+
+        Ok(created.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Request, TestServer};
+
+    fn track(id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "track": {
+                "id": id,
+                "name": id,
+                "duration_ms": 1000,
+                "artists": [{"name": "A"}],
+                "external_ids": {"isrc": format!("ISRC-{}", id)},
+                "album": {"name": "LP"}
+            }
+        })
+    }
+
+    fn provider(server: &TestServer, grit_dir: &std::path::Path) -> SpotifyProvider {
+        let token = OAuthToken {
+            access_token: "token".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_at: None,
+            refresh_token: None,
+            scope: None,
+        };
+        SpotifyProvider::new("client".to_string(), "secret".to_string())
+            .with_endpoints(Endpoints {
+                api: server.url.clone(),
+                ..SpotifyProvider::default_endpoints()
+            })
+            .with_token(&token, grit_dir)
+    }
+
+    #[tokio::test]
+    async fn test_liked_songs_fetch_and_apply() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut server = TestServer::bind().await;
+        let url = server.url.clone();
+        server.serve(move |request: &Request| {
+            let body = match (request.method.as_str(), request.param("offset")) {
+                ("GET", None) => serde_json::json!({
+                    "items": [track("t3"), track("t2")],
+                    "next": format!("{}/me/tracks?offset=2", url)
+                }),
+                ("GET", Some(_)) => serde_json::json!({"items": [track("t1")], "next": null}),
+                _ => serde_json::json!({}),
+            };
+            (200, body.to_string())
+        });
+        let provider = provider(&server, dir.path());
+
+        let liked = provider
+            .fetch_source(SourceKind::Liked, "spotify-liked")
+            .await
+            .unwrap();
+        assert_eq!(liked.id, "spotify-liked");
+        assert_eq!(liked.source, SourceKind::Liked);
+        let ids: Vec<_> = liked.tracks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["t3", "t2", "t1"]);
+
+        let mut desired = liked.clone();
+        let removed = desired.tracks.remove(1);
+        for (index, id) in [(0, "t5"), (1, "t4")] {
+            let mut new_track = removed.clone();
+            new_track.id = id.to_string();
+            desired.tracks.insert(index, new_track);
+        }
+        let patch = crate::state::diff(&liked, &desired);
+
+        provider
+            .apply_source(SourceKind::Liked, "spotify-liked", &patch, &desired)
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        let changes: Vec<_> = requests.iter().filter(|r| r.method != "GET").collect();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].method, "DELETE");
+        assert_eq!(changes[0].param("ids"), Some("t2"));
+
+        // Each save has its own time, newest first like the snapshot
+        assert_eq!(changes[1].method, "PUT");
+        let saves = changes[1].json()["timestamped_ids"].clone();
+        assert_eq!(saves[0]["id"], "t5");
+        assert_eq!(saves[1]["id"], "t4");
+        assert!(saves[0]["added_at"].as_str() > saves[1]["added_at"].as_str());
+    }
+
+    #[tokio::test]
+    async fn test_liked_songs_without_library_scopes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut server = TestServer::bind().await;
+        server.serve(|_: &Request| (403, r#"{"error":{"status":403}}"#.to_string()));
+        let provider = provider(&server, dir.path());
+
+        let err = provider
+            .fetch_source(SourceKind::Liked, "spotify-liked")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("grit auth spotify"));
+    }
+
+    #[tokio::test]
+    async fn test_list_playlists_pages_and_marks_owned() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut server = TestServer::bind().await;
+        let url = server.url.clone();
+        server.serve(move |request: &Request| {
+            let playlist = |id: &str, owner: &str, collaborative: bool| {
+                serde_json::json!({
+                    "id": id,
+                    "name": format!("List {}", id),
+                    "owner": {"id": owner, "display_name": null},
+                    "collaborative": collaborative,
+                    "tracks": {"total": 3}
+                })
+            };
+            let body = match (request.path.as_str(), request.param("offset")) {
+                ("/me", _) => serde_json::json!({"id": "me"}),
+                (_, None) => serde_json::json!({
+                    "items": [playlist("p1", "me", false), playlist("p2", "other", false)],
+                    "next": format!("{}/me/playlists?offset=2", url)
+                }),
+                (_, Some(_)) => serde_json::json!({
+                    "items": [playlist("p3", "other", true)],
+                    "next": null
+                }),
+            };
+            (200, body.to_string())
+        });
+
+        let playlists = provider(&server, dir.path())
+            .list_playlists()
+            .await
+            .unwrap();
+        let summary: Vec<_> = playlists
+            .iter()
+            .map(|p| (p.id.as_str(), p.owned, p.owner.as_deref(), p.tracks))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("p1", true, Some("me"), Some(3)),
+                ("p2", false, Some("other"), Some(3)),
+                ("p3", true, Some("other"), Some(3)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_plans_from_a_single_fetch() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut server = TestServer::bind().await;
+        server.serve(|request: &Request| {
+            let body = match request.method.as_str() {
+                "GET" => serde_json::json!({
+                    "id": "p1",
+                    "name": "Mix",
+                    "description": null,
+                    "snapshot_id": "s1",
+                    "owner": {"id": "me", "display_name": null},
+                    "collaborative": false,
+                    "tracks": {
+                        "items": [track("a"), track("b"), track("c"), track("d")],
+                        "next": null
+                    }
+                }),
+                _ => serde_json::json!({"snapshot_id": "s2"}),
+            };
+            (200, body.to_string())
+        });
+        let provider = provider(&server, dir.path());
+
+        let playlist = |ids: &[&str]| PlaylistSnapshot {
+            id: "p1".to_string(),
+            ..PlaylistSnapshot::fixture(ProviderKind::Spotify, ids)
+        };
+        let local = playlist(&["a", "b", "c", "d"]);
+        let desired = playlist(&["f", "a", "b", "c", "d"]);
+        let patch = crate::state::diff(&local, &desired);
+
+        provider.apply("p1", &patch, &desired).await.unwrap();
+
+        let requests = server.requests();
+        let methods: Vec<_> = requests.iter().map(|r| r.method.as_str()).collect();
+        assert_eq!(methods, vec!["GET", "POST", "PUT"]);
+        assert_eq!(
+            requests[2].json(),
+            serde_json::json!({
+                "range_start": 4,
+                "insert_before": 0,
+                "range_length": 1,
+                "snapshot_id": "s2"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_known_steps_skip_the_fetch() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut server = TestServer::bind().await;
+        server.serve(|request: &Request| {
+            let body = match request.method.as_str() {
+                "GET" => serde_json::json!({
+                    "id": "p1",
+                    "name": "Mix",
+                    "description": null,
+                    "snapshot_id": "s1",
+                    "owner": {"id": "me", "display_name": null},
+                    "collaborative": false,
+                    "tracks": {"items": [track("a"), track("b")], "next": null}
+                }),
+                _ => serde_json::json!({"snapshot_id": "s2"}),
+            };
+            (200, body.to_string())
+        });
+        let provider = provider(&server, dir.path());
+
+        let current = provider.fetch("p1").await.unwrap();
+        assert_eq!(
+            current.tracks[0].metadata,
+            Some(serde_json::json!({"isrc": "ISRC-a", "album": "LP"}))
+        );
+        let with = |ids: &[&str]| PlaylistSnapshot {
+            tracks: ids
+                .iter()
+                .map(|id| Track {
+                    id: id.to_string(),
+                    ..current.tracks[0].clone()
+                })
+                .collect(),
+            ..current.clone()
+        };
+
+        // A second copy is added, then the first copy dropped: Spotify
+        // deletes every copy of "a", so one is added back at the end
+        let (step1, step2) = (with(&["a", "b", "a"]), with(&["b", "a"]));
+        for (from, to) in [(&current, &step1), (&step1, &step2)] {
+            let patch = crate::state::diff(from, to);
+            provider
+                .apply_known(SourceKind::Playlist, "p1", from, &patch, to)
+                .await
+                .unwrap();
+        }
+
+        let requests = server.requests();
+        let methods: Vec<_> = requests.iter().map(|r| r.method.as_str()).collect();
+        assert_eq!(methods, vec!["GET", "POST", "DELETE", "POST"]);
+        assert_eq!(
+            requests[3].json()["uris"],
+            serde_json::json!(["spotify:track:a"])
+        );
+    }
 }
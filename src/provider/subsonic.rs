@@ -0,0 +1,544 @@
+//! Self-hosted music servers speaking the Subsonic REST API (Navidrome,
+//! Gonic, Airsonic, Jellyfin via its Subsonic plugin, ...).
+//!
+//! Requests authenticate with a token and salt: every call sends a fresh
+//! random salt `s` and `t = md5(password + s)`, so the password itself
+//! never goes over the wire.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use md5::{Digest, Md5};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::provider::{
+    DiffPatch, OAuthToken, PlaylistSnapshot, Provider, ProviderKind, RemotePlaylist, SourceKind,
+    Track,
+};
+use crate::utils::http::{HttpClient, RequestBuilder};
+
+/// API version grit speaks; 1.16.1 is what current servers implement.
+const API_VERSION: &str = "1.16.1";
+const CLIENT_NAME: &str = "grit";
+
+pub struct SubsonicProvider {
+    base_url: String,
+    username: String,
+    password: String,
+    http: HttpClient,
+    search_limit: usize,
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "subsonic-response")]
+    response: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct PlaylistResponse {
+    playlist: SubsonicPlaylist,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubsonicPlaylist {
+    id: String,
+    name: String,
+    #[serde(default)]
+    comment: Option<String>,
+    #[serde(default)]
+    owner: Option<String>,
+    #[serde(default)]
+    changed: Option<String>,
+    #[serde(default)]
+    entry: Vec<Song>,
+}
+
+#[derive(Deserialize)]
+struct PlaylistsResponse {
+    playlists: PlaylistList,
+}
+
+#[derive(Deserialize)]
+struct PlaylistList {
+    #[serde(default)]
+    playlist: Vec<SubsonicPlaylistInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubsonicPlaylistInfo {
+    id: String,
+    name: String,
+    #[serde(default)]
+    owner: Option<String>,
+    #[serde(default)]
+    song_count: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SongResponse {
+    song: Song,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    search_result3: SearchResult,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+    #[serde(default)]
+    song: Vec<Song>,
+}
+
+#[derive(Deserialize)]
+struct Song {
+    id: String,
+    title: String,
+    #[serde(default)]
+    artist: Option<String>,
+    #[serde(default)]
+    album: Option<String>,
+    /// Seconds
+    #[serde(default)]
+    duration: u64,
+    /// OpenSubsonic extension, a list of codes
+    #[serde(default)]
+    isrc: Vec<String>,
+}
+
+impl Song {
+    fn into_track(self) -> Track {
+        let mut metadata = serde_json::Map::new();
+        if let Some(album) = self.album {
+            metadata.insert("album".to_string(), album.into());
+        }
+        if let Some(isrc) = self.isrc.into_iter().next() {
+            metadata.insert("isrc".to_string(), isrc.into());
+        }
+
+        Track {
+            id: self.id,
+            name: self.title,
+            artists: self.artist.into_iter().collect(),
+            duration_ms: self.duration * 1000,
+            provider: ProviderKind::Subsonic,
+            metadata: (!metadata.is_empty()).then_some(metadata.into()),
+        }
+    }
+}
+
+impl SubsonicProvider {
+    pub fn new(base_url: &str, username: &str, password: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            http: HttpClient::new(),
+            search_limit: 10,
+        }
+    }
+
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.search_limit = limit;
+        self
+    }
+
+    /// Use `http` instead of a client with the configured settings.
+    #[cfg(test)]
+    pub fn with_http(mut self, http: HttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Query parameters that authenticate a request.
+    fn auth_params(&self) -> Vec<(&'static str, String)> {
+        let salt = format!("{:016x}", rand::random::<u64>());
+        let token = format!(
+            "{:x}",
+            Md5::digest(format!("{}{}", self.password, salt).as_bytes())
+        );
+
+        vec![
+            ("u", self.username.clone()),
+            ("t", token),
+            ("s", salt),
+            ("v", API_VERSION.to_string()),
+            ("c", CLIENT_NAME.to_string()),
+            ("f", "json".to_string()),
+        ]
+    }
+
+    fn endpoint(&self, method: &str) -> String {
+        format!("{}/rest/{}", self.base_url, method)
+    }
+
+    /// Call `method` and return the body of its `subsonic-response`.
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> Result<T> {
+        self.send(self.http.get(self.endpoint(method)), method, params)
+            .await
+    }
+
+    /// Call a `method` that changes something. Every Subsonic call is a
+    /// GET, so it is marked for dry runs to skip. It isn't retried after a
+    /// server error, which may have come after the change was made.
+    async fn call_mutating<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> Result<T> {
+        let request = self.http.get(self.endpoint(method)).mutating();
+        self.send(request, method, params).await
+    }
+
+    /// Call a `method` that replaces something, with `params` in a form
+    /// POST body rather than the URL, which proxies and servers limit to a
+    /// few KB. It has the same effect when sent twice, so it is retried.
+    async fn call_form<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> Result<T> {
+        let request = self
+            .http
+            .post(self.endpoint(method))
+            .idempotent()
+            .form(params);
+        self.send(request, method, &[]).await
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> Result<T> {
+        let response = request
+            .query(&self.auth_params())
+            .query(params)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach Subsonic server at {}", self.base_url))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            bail!("Subsonic API error {}: {}", status, error_text);
+        }
+
+        let envelope: Envelope = response
+            .json()
+            .await
+            .context("Failed to parse Subsonic response")?;
+
+        if envelope.response.get("status").and_then(|s| s.as_str()) != Some("ok") {
+            let error: Option<ApiError> = envelope
+                .response
+                .get("error")
+                .and_then(|e| serde_json::from_value(e.clone()).ok());
+            match error {
+                // 40: wrong username or password
+                Some(e) if e.code == 40 => bail!(
+                    "Subsonic rejected the credentials ({}). Run 'grit auth subsonic' again.",
+                    e.message
+                ),
+                Some(e) => bail!("Subsonic error {} in {}: {}", e.code, method, e.message),
+                None => bail!("Subsonic call {} failed", method),
+            }
+        }
+
+        serde_json::from_value(envelope.response)
+            .with_context(|| format!("Unexpected Subsonic response to {}", method))
+    }
+
+    /// Check the server is reachable and accepts the credentials.
+    pub async fn ping(&self) -> Result<()> {
+        self.call::<serde_json::Value>("ping", &[]).await?;
+        Ok(())
+    }
+
+    async fn get_playlist(&self, playlist_id: &str) -> Result<SubsonicPlaylist> {
+        let response: PlaylistResponse = self
+            .call("getPlaylist", &[("id", playlist_id.to_string())])
+            .await?;
+        Ok(response.playlist)
+    }
+}
+
+#[async_trait]
+impl Provider for SubsonicProvider {
+    async fn fetch(&self, playlist_id: &str) -> Result<PlaylistSnapshot> {
+        let playlist = self.get_playlist(playlist_id).await?;
+
+        Ok(PlaylistSnapshot {
+            id: playlist.id,
+            name: playlist.name,
+            description: playlist.comment.filter(|c| !c.is_empty()),
+            tracks: playlist.entry.into_iter().map(Song::into_track).collect(),
+            provider: ProviderKind::Subsonic,
+            source: SourceKind::Playlist,
+            snapshot_hash: playlist.changed.unwrap_or_default(),
+            metadata: None,
+        })
+    }
+
+    async fn apply(
+        &self,
+        playlist_id: &str,
+        _patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        // updatePlaylist can only append and remove by index, so replace the
+        // whole track list at once, which createPlaylist does when given an
+        // ID; a song ID each is too much for a URL, hence the POST
+        let mut params = vec![("playlistId", playlist_id.to_string())];
+        params.extend(
+            desired_state
+                .tracks
+                .iter()
+                .map(|track| ("songId", track.id.clone())),
+        );
+
+        self.call_form::<serde_json::Value>("createPlaylist", &params)
+            .await
+            .context("Failed to update playlist")?;
+        Ok(())
+    }
+
+    async fn playable_url(&self, track: &Track) -> Result<String> {
+        let mut url = reqwest::Url::parse(&self.endpoint("stream"))
+            .with_context(|| format!("Invalid Subsonic URL {}", self.base_url))?;
+        url.query_pairs_mut()
+            .extend_pairs(self.auth_params())
+            .append_pair("id", &track.id);
+        Ok(url.to_string())
+    }
+
+    async fn fetch_track(&self, track_id: &str) -> Result<Track> {
+        let response: SongResponse = self
+            .call("getSong", &[("id", track_id.to_string())])
+            .await?;
+        Ok(response.song.into_track())
+    }
+
+    async fn search_by_query(&self, query: &str) -> Result<Vec<Track>> {
+        let response: SearchResponse = self
+            .call(
+                "search3",
+                &[
+                    ("query", query.to_string()),
+                    ("songCount", self.search_limit.max(1).to_string()),
+                    ("artistCount", "0".to_string()),
+                    ("albumCount", "0".to_string()),
+                ],
+            )
+            .await?;
+
+        Ok(response
+            .search_result3
+            .song
+            .into_iter()
+            .map(Song::into_track)
+            .collect())
+    }
+
+    fn oauth_url(&self, _redirect_uri: &str, _state: &str) -> String {
+        String::new()
+    }
+
+    async fn exchange_code(&self, _code: &str, _redirect_uri: &str) -> Result<OAuthToken> {
+        bail!("Subsonic servers use a username and password, not OAuth")
+    }
+
+    async fn refresh_token(&self, _token: &OAuthToken) -> Result<OAuthToken> {
+        bail!("Subsonic servers use a username and password, not OAuth")
+    }
+
+    async fn can_modify_playlist(&self, playlist_id: &str) -> Result<bool> {
+        let playlist = self.get_playlist(playlist_id).await?;
+        Ok(playlist.owner.as_deref() == Some(self.username.as_str()))
+    }
+
+    async fn list_playlists(&self) -> Result<Vec<RemotePlaylist>> {
+        let response: PlaylistsResponse = self.call("getPlaylists", &[]).await?;
+
+        Ok(response
+            .playlists
+            .playlist
+            .into_iter()
+            .map(|p| RemotePlaylist {
+                owned: p.owner.as_deref() == Some(self.username.as_str()),
+                id: p.id,
+                name: p.name,
+                owner: p.owner,
+                tracks: p.song_count,
+            })
+            .collect())
+    }
+
+    async fn create_playlist(&self, name: &str, description: Option<&str>) -> Result<String> {
+        let response: PlaylistResponse = self
+            .call_mutating("createPlaylist", &[("name", name.to_string())])
+            .await
+            .context("Failed to create playlist (the server must support API 1.14 or later)")?;
+        let id = response.playlist.id;
+
+        if let Some(comment) = description.filter(|d| !d.is_empty()) {
+            self.call_mutating::<serde_json::Value>(
+                "updatePlaylist",
+                &[("playlistId", id.clone()), ("comment", comment.to_string())],
+            )
+            .await?;
+        }
+
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Request, TestServer};
+
+    /// A Subsonic server with one playlist, owned by alice (password "secret").
+    async fn mock_server() -> TestServer {
+        let mut server = TestServer::bind().await;
+        server.serve(respond);
+        server
+    }
+
+    fn respond(request: &Request) -> (u16, String) {
+        let salt = request.param("s").unwrap_or_default();
+        let expected = format!("{:x}", Md5::digest(format!("secret{}", salt).as_bytes()));
+        if request.param("u") != Some("alice") || request.param("t") != Some(expected.as_str()) {
+            let failed = r#"{"subsonic-response":{"status":"failed","error":{"code":40,"message":"Wrong username or password"}}}"#;
+            return (200, failed.to_string());
+        }
+
+        let mut body = match request.path.trim_start_matches("/rest/") {
+            "getPlaylist" => serde_json::json!({
+                "playlist": {
+                    "id": "pl1",
+                    "name": "Road trip",
+                    "comment": "Loud",
+                    "owner": "alice",
+                    "changed": "2026-01-01T00:00:00Z",
+                    "entry": [
+                        {"id": "s1", "title": "One", "artist": "A", "album": "X", "duration": 200},
+                        {"id": "s2", "title": "Two", "artist": "B", "duration": 180, "isrc": ["USRC1"]}
+                    ]
+                }
+            }),
+            "search3" => serde_json::json!({
+                "searchResult3": {"song": [{"id": "s3", "title": "Three", "artist": "C", "duration": 100}]}
+            }),
+            _ => serde_json::json!({}),
+        };
+
+        body["status"] = "ok".into();
+        body["version"] = API_VERSION.into();
+        (
+            200,
+            serde_json::json!({ "subsonic-response": body }).to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fetch_search_and_apply() {
+        let server = mock_server().await;
+        let provider = SubsonicProvider::new(&server.url, "alice", "secret");
+
+        let mut snap = provider.fetch("pl1").await.unwrap();
+        assert_eq!(snap.name, "Road trip");
+        assert_eq!(snap.description.as_deref(), Some("Loud"));
+        assert_eq!(snap.tracks.len(), 2);
+        assert_eq!(snap.tracks[0].duration_ms, 200_000);
+        assert_eq!(snap.tracks[0].artists, vec!["A"]);
+        assert_eq!(snap.tracks[1].metadata.as_ref().unwrap()["isrc"], "USRC1");
+        assert!(provider.can_modify_playlist("pl1").await.unwrap());
+
+        let found = provider.search_by_query("three").await.unwrap();
+        assert_eq!(found[0].id, "s3");
+
+        snap.tracks.swap(0, 1);
+        snap.tracks.push(found[0].clone());
+        provider
+            .apply("pl1", &DiffPatch::default(), &snap)
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        let update = requests.last().unwrap();
+        assert_eq!(update.method, "POST");
+        assert_eq!(update.path, "/rest/createPlaylist");
+        assert_eq!(update.form("playlistId"), vec!["pl1"]);
+        assert_eq!(update.form("songId"), vec!["s2", "s1", "s3"]);
+        assert!(update.params("songId").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_replacing_a_playlist_is_retried() {
+        use crate::utils::http::Settings;
+        use std::time::Duration;
+
+        let mut server = TestServer::bind().await;
+        server.serve(|_: &Request| (500, "{}".to_string()));
+        let provider = SubsonicProvider::new(&server.url, "alice", "secret").with_http(
+            HttpClient::with_settings(Settings {
+                max_retries: 1,
+                base_delay: Duration::from_millis(1),
+                ..Settings::default()
+            }),
+        );
+
+        // The server may have made the playlist before failing
+        assert!(provider.create_playlist("Mix", None).await.is_err());
+        assert_eq!(server.requests().len(), 1);
+
+        let snap = PlaylistSnapshot::fixture(ProviderKind::Subsonic, &[]);
+        assert!(provider
+            .apply("pl1", &DiffPatch::default(), &snap)
+            .await
+            .is_err());
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_password() {
+        let server = mock_server().await;
+        let provider = SubsonicProvider::new(&server.url, "alice", "wrong");
+
+        let err = provider.fetch("pl1").await.unwrap_err();
+        assert!(err.to_string().contains("rejected the credentials"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_url() {
+        let provider = SubsonicProvider::new("https://music.example.com/", "alice", "secret");
+        let track = Track {
+            id: "s1".to_string(),
+            name: "One".to_string(),
+            artists: vec![],
+            duration_ms: 0,
+            provider: ProviderKind::Subsonic,
+            metadata: None,
+        };
+
+        let url = provider.playable_url(&track).await.unwrap();
+        assert!(url.starts_with("https://music.example.com/rest/stream?"));
+        assert!(url.contains("u=alice") && url.contains("id=s1"));
+        assert!(!url.contains("secret"));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::provider::{DiffPatch, OAuthToken, PlaylistSnapshot, Track};
+use crate::provider::{
+    Capabilities, DiffPatch, OAuthToken, PlaylistSnapshot, RemotePlaylist, SourceKind, Track,
+};
 use async_trait::async_trait;
 
 #[async_trait]
@@ -6,6 +8,19 @@ pub trait Provider: Send + Sync {
     /// Fetch playlist snapshot from remote
     async fn fetch(&self, playlist_id: &str) -> anyhow::Result<PlaylistSnapshot>;
 
+    /// What this provider can read and change
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Fetch a snapshot of any source kind listed in `capabilities`
+    async fn fetch_source(&self, source: SourceKind, id: &str) -> anyhow::Result<PlaylistSnapshot> {
+        match source {
+            SourceKind::Playlist => self.fetch(id).await,
+            _ => anyhow::bail!("This provider can't fetch {} sources", source),
+        }
+    }
+
     /// Apply changes to remote playlist to match desired state
     async fn apply(
         &self,
@@ -14,6 +29,36 @@ pub trait Provider: Send + Sync {
         desired_state: &PlaylistSnapshot,
     ) -> anyhow::Result<()>;
 
+    /// Apply changes to a source of any kind that `capabilities` lists and
+    /// that is editable
+    async fn apply_source(
+        &self,
+        source: SourceKind,
+        id: &str,
+        patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> anyhow::Result<()> {
+        match source {
+            SourceKind::Playlist => self.apply(id, patch, desired_state).await,
+            _ => anyhow::bail!("This provider can't change {} sources", source),
+        }
+    }
+
+    /// Apply changes to a source the caller knows to be at `current` (just
+    /// fetched, or left so by the previous apply), so providers that plan
+    /// from the remote's state can skip fetching it again
+    async fn apply_known(
+        &self,
+        source: SourceKind,
+        id: &str,
+        current: &PlaylistSnapshot,
+        patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> anyhow::Result<()> {
+        let _ = current;
+        self.apply_source(source, id, patch, desired_state).await
+    }
+
     /// Get playable URL for a track
     async fn playable_url(&self, track: &Track) -> anyhow::Result<String>;
 
@@ -33,4 +78,16 @@ pub trait Provider: Send + Sync {
 
     /// Check if the authenticated user can modify the playlist
     async fn can_modify_playlist(&self, playlist_id: &str) -> anyhow::Result<bool>;
+
+    /// Every playlist the authenticated account owns or follows
+    async fn list_playlists(&self) -> anyhow::Result<Vec<RemotePlaylist>> {
+        anyhow::bail!("This provider can't list playlists")
+    }
+
+    /// Create an empty private playlist for the authenticated user and return its ID
+    async fn create_playlist(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> anyhow::Result<String>;
 }
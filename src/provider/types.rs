@@ -1,19 +1,52 @@
-use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{fmt, str::FromStr};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProviderKind {
     Spotify,
     Youtube,
+    Local,
+    /// A self-hosted Subsonic-compatible server (Navidrome, Gonic, ...)
+    Subsonic,
+    /// An external `grit-provider-<name>` executable
+    Plugin(String),
 }
 
 impl fmt::Display for ProviderKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            // kabab-case, as that's what `clap::ValueEnum` parses by default
             ProviderKind::Spotify => write!(f, "spotify"),
             ProviderKind::Youtube => write!(f, "youtube"),
+            ProviderKind::Local => write!(f, "local"),
+            ProviderKind::Subsonic => write!(f, "subsonic"),
+            ProviderKind::Plugin(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Parses the built-in names case-insensitively; any other name refers to
+/// a `grit-provider-<name>` plugin.
+impl FromStr for ProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        match name.as_str() {
+            "spotify" => Ok(ProviderKind::Spotify),
+            "youtube" => Ok(ProviderKind::Youtube),
+            "local" => Ok(ProviderKind::Local),
+            "subsonic" => Ok(ProviderKind::Subsonic),
+            _ if !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Ok(ProviderKind::Plugin(name))
+            }
+            _ => Err(format!(
+                "invalid provider '{}' (expected spotify, youtube, local, subsonic or a plugin name)",
+                s
+            )),
         }
     }
 }
@@ -29,6 +62,84 @@ pub struct Track {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// What a tracked snapshot was fetched from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    #[default]
+    Playlist,
+    Album,
+    /// The account's liked songs (or videos), newest first
+    Liked,
+}
+
+impl SourceKind {
+    /// Whether changes to the source can be pushed back to its provider.
+    pub fn is_editable(self) -> bool {
+        match self {
+            SourceKind::Playlist | SourceKind::Liked => true,
+            SourceKind::Album => false,
+        }
+    }
+
+    /// Whether tracks can be put in any order. Liked songs are always
+    /// ordered by when they were liked.
+    pub fn can_reorder(self) -> bool {
+        self == SourceKind::Playlist
+    }
+
+    fn is_playlist(&self) -> bool {
+        *self == SourceKind::Playlist
+    }
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceKind::Playlist => write!(f, "playlist"),
+            SourceKind::Album => write!(f, "album"),
+            SourceKind::Liked => write!(f, "liked"),
+        }
+    }
+}
+
+/// What a provider supports, so commands can refuse early with a clear
+/// message instead of failing halfway through an API call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Sources `fetch_source` can read
+    pub sources: Vec<SourceKind>,
+    /// Playlists can be changed with `apply`
+    pub write: bool,
+    /// New playlists can be made with `create_playlist`
+    pub create: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            sources: vec![SourceKind::Playlist],
+            write: true,
+            create: true,
+        }
+    }
+}
+
+/// A playlist the authenticated account owns or follows, as listed by
+/// `Provider::list_playlists`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemotePlaylist {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// The account can change it (owned or collaborative)
+    #[serde(default)]
+    pub owned: bool,
+    #[serde(default)]
+    pub tracks: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaylistSnapshot {
     pub id: String,
@@ -36,11 +147,50 @@ pub struct PlaylistSnapshot {
     pub description: Option<String>,
     pub tracks: Vec<Track>,
     pub provider: ProviderKind,
+    /// Omitted for playlists, so snapshots (and their hashes) from before
+    /// sources were recorded are unchanged
+    #[serde(default, skip_serializing_if = "SourceKind::is_playlist")]
+    pub source: SourceKind,
     pub snapshot_hash: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
 }
 
+#[cfg(test)]
+impl Track {
+    /// A track named "Song <id>" by "Artist", for tests.
+    pub fn fixture(id: &str, provider: ProviderKind) -> Self {
+        Track {
+            id: id.to_string(),
+            name: format!("Song {}", id),
+            artists: vec!["Artist".to_string()],
+            duration_ms: 200_000,
+            provider,
+            metadata: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl PlaylistSnapshot {
+    /// Playlist "pl1" named "Mix" holding a fixture track per id, for tests.
+    pub fn fixture(provider: ProviderKind, ids: &[&str]) -> Self {
+        PlaylistSnapshot {
+            id: "pl1".to_string(),
+            name: "Mix".to_string(),
+            description: None,
+            tracks: ids
+                .iter()
+                .map(|id| Track::fixture(id, provider.clone()))
+                .collect(),
+            provider,
+            source: SourceKind::Playlist,
+            snapshot_hash: String::new(),
+            metadata: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TrackChange {
     Added {
@@ -63,6 +213,15 @@ pub struct DiffPatch {
     pub changes: Vec<TrackChange>,
 }
 
+/// Base URLs of a provider's web API and OAuth endpoints. They default to
+/// the real service and can be pointed elsewhere, e.g. at a mock server.
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    pub api: String,
+    pub auth: String,
+    pub token: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthToken {
     pub access_token: String,
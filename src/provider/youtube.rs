@@ -1,9 +1,17 @@
+use crate::provider::reorder::plan_moves;
 use crate::provider::{
-    DiffPatch, OAuthToken, PlaylistSnapshot, Provider, ProviderKind, Track, TrackChange,
+    Capabilities, DiffPatch, Endpoints, OAuthToken, PlaylistSnapshot, Provider, ProviderKind,
+    RemotePlaylist, SourceKind, Track, TrackChange,
 };
-use anyhow::{Context, Result};
+use crate::state::diff::occurrence_changes;
+use crate::state::quota;
+use crate::utils::http::HttpClient;
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::sync::Mutex;
 
 const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
@@ -15,7 +23,14 @@ pub struct YoutubeProvider {
     client_secret: String,
     token: Mutex<Option<OAuthToken>>,
     grit_dir: Option<std::path::PathBuf>,
-    http: reqwest::Client,
+    http: HttpClient,
+    endpoints: Endpoints,
+    search_limit: usize,
+    /// Daily quota units before grit warns; 0 for no limit
+    quota_budget: Arc<AtomicU64>,
+    /// Items (item ID, video ID) of each playlist as last fetched or
+    /// changed, for `apply_known`
+    known: std::sync::Mutex<HashMap<String, Vec<(String, String)>>>,
 }
 
 #[derive(Deserialize)]
@@ -30,18 +45,35 @@ struct YoutubeTokenResponse {
 #[derive(Deserialize)]
 struct YoutubePlaylistResponse {
     items: Vec<YoutubePlaylist>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct YoutubeCreatedPlaylist {
+    id: String,
 }
 
 #[derive(Deserialize)]
 struct YoutubePlaylist {
     id: String,
     snippet: YoutubePlaylistSnippet,
+    #[serde(rename = "contentDetails")]
+    content_details: Option<YoutubePlaylistContentDetails>,
 }
 
 #[derive(Deserialize)]
 struct YoutubePlaylistSnippet {
     title: String,
     description: Option<String>,
+    #[serde(rename = "channelTitle")]
+    channel_title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct YoutubePlaylistContentDetails {
+    #[serde(rename = "itemCount")]
+    item_count: usize,
 }
 
 #[derive(Deserialize)]
@@ -51,6 +83,11 @@ struct YoutubePlaylistItemsResponse {
     next_page_token: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct YoutubeInsertedItem {
+    id: String,
+}
+
 #[derive(Deserialize)]
 struct YoutubePlaylistItem {
     id: String,
@@ -75,10 +112,13 @@ struct YoutubeItemContentDetails {
 #[derive(Deserialize)]
 struct YoutubeVideoResponse {
     items: Vec<YoutubeVideo>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct YoutubeVideo {
+    id: String,
     snippet: YoutubeVideoSnippet,
     #[serde(rename = "contentDetails")]
     content_details: YoutubeVideoContentDetails,
@@ -123,13 +163,48 @@ impl YoutubeProvider {
             client_secret,
             token: Mutex::new(None),
             grit_dir: None,
-            http: reqwest::Client::new(),
+            http: HttpClient::new(),
+            endpoints: Self::default_endpoints(),
+            search_limit: 10,
+            quota_budget: Arc::new(AtomicU64::new(quota::DEFAULT_BUDGET)),
+            known: Default::default(),
         }
     }
 
+    pub fn default_endpoints() -> Endpoints {
+        Endpoints {
+            api: API_BASE.to_string(),
+            auth: AUTH_URL.to_string(),
+            token: TOKEN_URL.to_string(),
+        }
+    }
+
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.search_limit = limit;
+        self
+    }
+
+    pub fn with_quota_budget(self, budget: u64) -> Self {
+        self.quota_budget.store(budget, Ordering::Relaxed);
+        self
+    }
+
+    /// Use `token`, saving refreshed tokens and recording quota use in
+    /// `grit_dir`. A dry run writes nothing there, so its reads go unrecorded.
     pub fn with_token(mut self, token: &OAuthToken, grit_dir: &std::path::Path) -> Self {
         self.token = Mutex::new(Some(token.clone()));
         self.grit_dir = Some(grit_dir.to_path_buf());
+        if !self.http.is_dry_run() {
+            self.http = self.http.with_meter(quota::meter(
+                grit_dir.to_path_buf(),
+                self.quota_budget.clone(),
+            ));
+        }
         self
     }
 
@@ -154,12 +229,12 @@ impl YoutubeProvider {
         drop(token_guard);
 
         if Self::is_token_expired(&current_token) {
-            println!("Token expired, refreshing...");
+            eprintln!("Token expired, refreshing...");
             let new_token = self.refresh_token(&current_token).await?;
 
             if let Some(grit_dir) = &self.grit_dir {
                 use crate::state::credentials;
-                credentials::save(grit_dir, ProviderKind::Youtube, &new_token)?;
+                credentials::save(grit_dir, &ProviderKind::Youtube, &new_token)?;
             }
 
             *self.token.lock().await = Some(new_token.clone());
@@ -172,7 +247,8 @@ impl YoutubeProvider {
     async fn token_request(&self, params: &[(&str, &str)]) -> Result<YoutubeTokenResponse> {
         let response = self
             .http
-            .post(TOKEN_URL)
+            .post(&self.endpoints.token)
+            .read_only()
             .form(params)
             .send()
             .await
@@ -189,10 +265,64 @@ impl YoutubeProvider {
             .context("Failed to parse token response")
     }
 
-    async fn api_get<T: serde::de::DeserializeOwned>(&self, url: &str, token: &str) -> Result<T> {
+    /// Quota units `apply_known` spends to apply `patch` to `current`,
+    /// giving `desired`: a write per removed or added copy and per
+    /// single-item move. Nothing is read, as the items are known.
+    pub fn apply_cost(
+        source: SourceKind,
+        current: &[Track],
+        patch: &DiffPatch,
+        desired: &[Track],
+    ) -> u64 {
+        if source == SourceKind::Liked {
+            let ratings = patch
+                .changes
+                .iter()
+                .filter(|c| !matches!(c, TrackChange::Moved { .. }))
+                .count();
+            return ratings as u64 * quota::WRITE;
+        }
+
+        let current: Vec<&str> = current.iter().map(|t| t.id.as_str()).collect();
+        let desired_ids: Vec<&str> = desired.iter().map(|t| t.id.as_str()).collect();
+        let (removals, additions) = occurrence_changes(&current, &desired_ids);
+
+        let mut items: Vec<&str> = current
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !removals.contains(i))
+            .map(|(_, id)| *id)
+            .collect();
+        items.extend(additions.iter().map(|&i| desired_ids[i]));
+        let moves: usize = plan_moves(&items, &desired_ids)
+            .iter()
+            .map(|range_move| range_move.range_length)
+            .sum();
+
+        (removals.len() + additions.len() + moves) as u64 * quota::WRITE
+    }
+
+    /// Quota units `fetch_source` spends on a source of `tracks` tracks.
+    pub fn fetch_cost(source: SourceKind, tracks: usize) -> u64 {
+        let pages = tracks.div_ceil(50).max(1) as u64;
+        match source {
+            // Liked videos come with their details
+            SourceKind::Liked => pages * quota::READ,
+            // The playlist, then a page of items and one of videos at a time
+            _ => (1 + 2 * pages) * quota::READ,
+        }
+    }
+
+    async fn api_get<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        token: &str,
+        units: u64,
+    ) -> Result<T> {
         let response = self
             .http
             .get(url)
+            .cost(units)
             .header("Authorization", format!("Bearer {}", token))
             .send()
             .await
@@ -221,14 +351,14 @@ impl YoutubeProvider {
         loop {
             let mut url = format!(
                 "{}/playlistItems?part=snippet,contentDetails&playlistId={}&maxResults=50",
-                API_BASE, playlist_id
+                self.endpoints.api, playlist_id
             );
 
             if let Some(token_str) = &page_token {
                 url.push_str(&format!("&pageToken={}", token_str));
             }
 
-            let resp: YoutubePlaylistItemsResponse = self.api_get(&url, token).await?;
+            let resp: YoutubePlaylistItemsResponse = self.api_get(&url, token, quota::READ).await?;
 
             for item in resp.items {
                 items.push((item.id, item.content_details.video_id));
@@ -243,6 +373,186 @@ impl YoutubeProvider {
         Ok(items)
     }
 
+    /// Change the playlist from `items`, as (item ID, video ID), to
+    /// `desired_state` without fetching it: removals, insertions at the
+    /// end, then single-item moves.
+    async fn apply_items(
+        &self,
+        playlist_id: &str,
+        mut items: Vec<(String, String)>,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        let token = self.get_token().await?;
+        let desired_ids: Vec<&str> = desired_state.tracks.iter().map(|t| t.id.as_str()).collect();
+        let current_ids: Vec<&str> = items.iter().map(|(_, vid)| vid.as_str()).collect();
+        let (removals, additions) = occurrence_changes(&current_ids, &desired_ids);
+
+        // Step 1: Remove the copies that shouldn't be there, last first so
+        // the indexes stay valid
+        for &i in removals.iter().rev() {
+            let (item_id, _) = items.remove(i);
+            let url = format!("{}/playlistItems?id={}", self.endpoints.api, item_id);
+
+            // A repeated delete fails with 404, so it isn't retried
+            self.http
+                .delete(&url)
+                .cost(quota::WRITE)
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
+        // Step 2: Add missing copies to the END (we'll reorder later)
+        for &i in &additions {
+            let video_id = desired_ids[i];
+            let body = serde_json::json!({
+                "snippet": {
+                    "playlistId": playlist_id,
+                    "resourceId": {
+                        "kind": "youtube#video",
+                        "videoId": video_id
+                    }
+                    // No position - adds to end
+                }
+            });
+
+            let inserted: YoutubeInsertedItem = self
+                .http
+                .post(format!("{}/playlistItems?part=snippet", self.endpoints.api))
+                .cost(quota::WRITE)
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&body)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await
+                .context("Failed to parse YouTube playlist item")?;
+            items.push((inserted.id, video_id.to_string()));
+        }
+
+        // Step 3: Reorder playlist to match desired state
+        // Items move one at a time, so each range move becomes one update per item
+        let current_ids: Vec<&str> = items.iter().map(|(_, vid)| vid.as_str()).collect();
+        let moves: Vec<(usize, usize)> = plan_moves(&current_ids, &desired_ids)
+            .iter()
+            .flat_map(|range_move| range_move.single_moves())
+            .collect();
+
+        for (from, to) in moves {
+            let item = items.remove(from);
+            let body = serde_json::json!({
+                "id": item.0,
+                "snippet": {
+                    "playlistId": playlist_id,
+                    "resourceId": {
+                        "kind": "youtube#video",
+                        "videoId": item.1
+                    },
+                    "position": to
+                }
+            });
+
+            // Setting an absolute position can be repeated
+            self.http
+                .put(format!("{}/playlistItems?part=snippet", self.endpoints.api))
+                .cost(quota::WRITE)
+                .idempotent()
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&body)
+                .send()
+                .await?
+                .error_for_status()?;
+            items.insert(to, item);
+        }
+
+        self.known
+            .lock()
+            .unwrap()
+            .insert(playlist_id.to_string(), items);
+        Ok(())
+    }
+
+    /// Videos the account has liked ("Liked videos"), most recent first, as
+    /// a snapshot with ID `id`.
+    pub async fn fetch_liked(&self, id: &str) -> Result<PlaylistSnapshot> {
+        let token = self.get_token().await?;
+
+        let mut all_tracks = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/videos?part=snippet,contentDetails&myRating=like&maxResults=50",
+                self.endpoints.api
+            );
+
+            if let Some(token_str) = &page_token {
+                url.push_str(&format!("&pageToken={}", token_str));
+            }
+
+            let resp: YoutubeVideoResponse = self.api_get(&url, &token, quota::READ).await?;
+
+            for video in resp.items {
+                all_tracks.push(Track {
+                    duration_ms: Self::parse_iso8601_duration(&video.content_details.duration),
+                    id: video.id,
+                    name: video.snippet.title,
+                    artists: vec![video
+                        .snippet
+                        .channel_title
+                        .unwrap_or_else(|| "Unknown".to_string())],
+                    provider: ProviderKind::Youtube,
+                    metadata: None,
+                });
+            }
+
+            page_token = resp.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(PlaylistSnapshot {
+            id: id.to_string(),
+            name: "Liked videos".to_string(),
+            description: None,
+            tracks: all_tracks,
+            provider: ProviderKind::Youtube,
+            source: SourceKind::Liked,
+            snapshot_hash: String::new(),
+            metadata: None,
+        })
+    }
+
+    /// Like added videos and clear the rating of removed ones. Moves are
+    /// ignored: liked videos are ordered by when they were liked.
+    async fn apply_liked(&self, patch: &DiffPatch) -> Result<()> {
+        let token = self.get_token().await?;
+
+        // Like the oldest first so the newest ends up on top, as in the snapshot
+        for change in patch.changes.iter().rev() {
+            let (video_id, rating) = match change {
+                TrackChange::Removed { track, .. } => (&track.id, "none"),
+                TrackChange::Added { track, .. } => (&track.id, "like"),
+                TrackChange::Moved { .. } => continue,
+            };
+
+            self.http
+                .post(format!("{}/videos/rate", self.endpoints.api))
+                .cost(quota::WRITE)
+                .query(&[("id", video_id.as_str()), ("rating", rating)])
+                .header("Authorization", format!("Bearer {}", token))
+                .header("Content-Length", "0")
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
+        Ok(())
+    }
+
     fn parse_iso8601_duration(duration: &str) -> u64 {
         // Parse ISO 8601 duration format (PT1H2M3S) to milliseconds
         let duration = duration.trim_start_matches("PT");
@@ -276,7 +586,7 @@ impl Provider for YoutubeProvider {
 
         format!(
             "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}&access_type=offline&prompt=consent",
-            AUTH_URL,
+            self.endpoints.auth,
             urlencoding::encode(&self.client_id),
             urlencoding::encode(redirect_uri),
             urlencoding::encode(scopes),
@@ -320,15 +630,45 @@ impl Provider for YoutubeProvider {
         Ok(new_token)
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            sources: vec![SourceKind::Playlist, SourceKind::Liked],
+            ..Capabilities::default()
+        }
+    }
+
+    async fn fetch_source(&self, source: SourceKind, id: &str) -> Result<PlaylistSnapshot> {
+        match source {
+            SourceKind::Playlist => self.fetch(id).await,
+            SourceKind::Liked => self.fetch_liked(id).await,
+            SourceKind::Album => bail!("YouTube has no albums"),
+        }
+    }
+
+    async fn apply_source(
+        &self,
+        source: SourceKind,
+        id: &str,
+        patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        match source {
+            SourceKind::Playlist => self.apply(id, patch, desired_state).await,
+            SourceKind::Liked => self.apply_liked(patch).await,
+            SourceKind::Album => bail!("YouTube has no albums"),
+        }
+    }
+
     async fn fetch(&self, playlist_id: &str) -> Result<PlaylistSnapshot> {
         let token = self.get_token().await?;
 
         let playlist_url = format!(
             "{}/playlists?part=snippet,contentDetails&id={}&key={}",
-            API_BASE, playlist_id, self.client_id
+            self.endpoints.api, playlist_id, self.client_id
         );
 
-        let playlist_resp: YoutubePlaylistResponse = self.api_get(&playlist_url, &token).await?;
+        let playlist_resp: YoutubePlaylistResponse =
+            self.api_get(&playlist_url, &token, quota::READ).await?;
 
         let playlist = playlist_resp
             .items
@@ -337,37 +677,60 @@ impl Provider for YoutubeProvider {
             .context("Playlist not found")?;
 
         let mut all_tracks = Vec::new();
+        let mut items = Vec::new();
         let mut page_token: Option<String> = None;
 
         loop {
             let mut items_url = format!(
                 "{}/playlistItems?part=snippet,contentDetails&playlistId={}&maxResults=50",
-                API_BASE, playlist_id
+                self.endpoints.api, playlist_id
             );
 
             if let Some(token) = &page_token {
                 items_url.push_str(&format!("&pageToken={}", token));
             }
 
-            let items_resp: YoutubePlaylistItemsResponse = self.api_get(&items_url, &token).await?;
+            let items_resp: YoutubePlaylistItemsResponse =
+                self.api_get(&items_url, &token, quota::READ).await?;
 
             let video_ids: Vec<String> = items_resp
                 .items
                 .iter()
                 .map(|item| item.content_details.video_id.clone())
                 .collect();
+            items.extend(
+                items_resp
+                    .items
+                    .iter()
+                    .map(|item| (item.id.clone(), item.content_details.video_id.clone())),
+            );
 
             if !video_ids.is_empty() {
                 let videos_url = format!(
                     "{}/videos?part=snippet,contentDetails&id={}",
-                    API_BASE,
+                    self.endpoints.api,
                     video_ids.join(",")
                 );
 
-                let videos_resp: YoutubeVideoResponse = self.api_get(&videos_url, &token).await?;
-
-                for (item, video) in items_resp.items.iter().zip(videos_resp.items.iter()) {
-                    let duration_ms = Self::parse_iso8601_duration(&video.content_details.duration);
+                let videos_resp: YoutubeVideoResponse =
+                    self.api_get(&videos_url, &token, quota::READ).await?;
+                // Deleted and private videos are left out of the response,
+                // but their items stay in the playlist
+                let durations: HashMap<&str, u64> = videos_resp
+                    .items
+                    .iter()
+                    .map(|video| {
+                        let duration =
+                            Self::parse_iso8601_duration(&video.content_details.duration);
+                        (video.id.as_str(), duration)
+                    })
+                    .collect();
+
+                for item in &items_resp.items {
+                    let duration_ms = durations
+                        .get(item.content_details.video_id.as_str())
+                        .copied()
+                        .unwrap_or(0);
                     let artist = item
                         .snippet
                         .video_owner_channel_title
@@ -391,12 +754,18 @@ impl Provider for YoutubeProvider {
             }
         }
 
+        self.known
+            .lock()
+            .unwrap()
+            .insert(playlist_id.to_string(), items);
+
         Ok(PlaylistSnapshot {
             id: playlist.id.clone(),
             name: playlist.snippet.title,
             description: playlist.snippet.description,
             tracks: all_tracks,
             provider: ProviderKind::Youtube,
+            source: SourceKind::Playlist,
             snapshot_hash: format!("yt-{}", playlist.id),
             metadata: None,
         })
@@ -405,95 +774,35 @@ impl Provider for YoutubeProvider {
     async fn apply(
         &self,
         playlist_id: &str,
-        patch: &DiffPatch,
+        _patch: &DiffPatch,
         desired_state: &PlaylistSnapshot,
     ) -> Result<()> {
         let token = self.get_token().await?;
+        let items = self.fetch_playlist_item_ids(playlist_id, &token).await?;
+        self.apply_items(playlist_id, items, desired_state).await
+    }
 
-        // Step 1: Remove tracks that shouldn't be there
-        let playlist_items = self.fetch_playlist_item_ids(playlist_id, &token).await?;
-
-        for change in &patch.changes {
-            if let TrackChange::Removed { track, .. } = change {
-                if let Some((item_id, _)) = playlist_items.iter().find(|(_, vid)| vid == &track.id)
-                {
-                    let url = format!("{}/playlistItems?id={}", API_BASE, item_id);
-
-                    self.http
-                        .delete(&url)
-                        .header("Authorization", format!("Bearer {}", token))
-                        .send()
-                        .await?
-                        .error_for_status()?;
-                }
-            }
-        }
-
-        // Step 2: Add new tracks to the END (we'll reorder later)
-        for change in &patch.changes {
-            if let TrackChange::Added { track, .. } = change {
-                let body = serde_json::json!({
-                    "snippet": {
-                        "playlistId": playlist_id,
-                        "resourceId": {
-                            "kind": "youtube#video",
-                            "videoId": track.id
-                        }
-                        // No position - adds to end
-                    }
-                });
-
-                self.http
-                    .post(format!("{}/playlistItems?part=snippet", API_BASE))
-                    .header("Authorization", format!("Bearer {}", token))
-                    .json(&body)
-                    .send()
-                    .await?
-                    .error_for_status()?;
-            }
-        }
-
-        // Step 3: Reorder playlist to match desired state
-        // Process from the beginning, moving each track to its correct position
-        for (desired_idx, desired_track) in desired_state.tracks.iter().enumerate() {
-            // Fetch current state to find where this track is now and get its item_id
-            let current = self.fetch(playlist_id).await?;
-            let playlist_items = self.fetch_playlist_item_ids(playlist_id, &token).await?;
-
-            let current_idx = current.tracks.iter().position(|t| t.id == desired_track.id);
-
-            if let Some(current_idx) = current_idx {
-                if current_idx != desired_idx {
-                    // Find the item_id for this track
-                    if let Some((item_id, _)) = playlist_items
-                        .iter()
-                        .find(|(_, vid)| vid == &desired_track.id)
-                    {
-                        let body = serde_json::json!({
-                            "id": item_id,
-                            "snippet": {
-                                "playlistId": playlist_id,
-                                "resourceId": {
-                                    "kind": "youtube#video",
-                                    "videoId": desired_track.id
-                                },
-                                "position": desired_idx
-                            }
-                        });
-
-                        self.http
-                            .put(format!("{}/playlistItems?part=snippet", API_BASE))
-                            .header("Authorization", format!("Bearer {}", token))
-                            .json(&body)
-                            .send()
-                            .await?
-                            .error_for_status()?;
-                    }
-                }
+    async fn apply_known(
+        &self,
+        source: SourceKind,
+        id: &str,
+        current: &PlaylistSnapshot,
+        patch: &DiffPatch,
+        desired_state: &PlaylistSnapshot,
+    ) -> Result<()> {
+        let known = self.known.lock().unwrap().get(id).cloned();
+        let matches = |items: &Vec<(String, String)>| {
+            items
+                .iter()
+                .map(|(_, vid)| vid)
+                .eq(current.tracks.iter().map(|t| &t.id))
+        };
+        match known {
+            Some(items) if source == SourceKind::Playlist && matches(&items) => {
+                self.apply_items(id, items, desired_state).await
             }
+            _ => self.apply_source(source, id, patch, desired_state).await,
         }
-
-        Ok(())
     }
 
     async fn playable_url(&self, track: &Track) -> Result<String> {
@@ -503,9 +812,10 @@ impl Provider for YoutubeProvider {
     async fn search_by_query(&self, query: &str) -> Result<Vec<Track>> {
         let token = self.get_token().await?;
         let url = format!(
-            "{}/search?part=snippet&q={}&type=video&maxResults=10",
-            API_BASE,
-            urlencoding::encode(query)
+            "{}/search?part=snippet&q={}&type=video&maxResults={}",
+            self.endpoints.api,
+            urlencoding::encode(query),
+            self.search_limit.clamp(1, 50)
         );
 
         #[derive(Deserialize)]
@@ -524,7 +834,7 @@ impl Provider for YoutubeProvider {
             video_id: String,
         }
 
-        let resp: SearchResponse = self.api_get(&url, &token).await?;
+        let resp: SearchResponse = self.api_get(&url, &token, quota::SEARCH).await?;
 
         let video_ids: Vec<String> = resp
             .items
@@ -538,11 +848,12 @@ impl Provider for YoutubeProvider {
 
         let videos_url = format!(
             "{}/videos?part=snippet,contentDetails&id={}",
-            API_BASE,
+            self.endpoints.api,
             video_ids.join(",")
         );
 
-        let videos_resp: YoutubeVideoResponse = self.api_get(&videos_url, &token).await?;
+        let videos_resp: YoutubeVideoResponse =
+            self.api_get(&videos_url, &token, quota::READ).await?;
 
         let tracks = resp
             .items
@@ -574,10 +885,10 @@ impl Provider for YoutubeProvider {
         let token = self.get_token().await?;
         let url = format!(
             "{}/videos?part=snippet,contentDetails&id={}",
-            API_BASE, track_id
+            self.endpoints.api, track_id
         );
 
-        let resp: YoutubeVideoResponse = self.api_get(&url, &token).await?;
+        let resp: YoutubeVideoResponse = self.api_get(&url, &token, quota::READ).await?;
 
         let video = resp.items.into_iter().next().context("Track not found")?;
 
@@ -599,11 +910,199 @@ impl Provider for YoutubeProvider {
 
     async fn can_modify_playlist(&self, playlist_id: &str) -> Result<bool> {
         let token = self.get_token().await?;
-        let url = format!("{}/playlists?part=snippet&id={}", API_BASE, playlist_id);
+        let url = format!(
+            "{}/playlists?part=snippet&id={}",
+            self.endpoints.api, playlist_id
+        );
 
-        match self.api_get::<YoutubePlaylistResponse>(&url, &token).await {
+        match self
+            .api_get::<YoutubePlaylistResponse>(&url, &token, quota::READ)
+            .await
+        {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
         }
     }
+
+    /// YouTube only lists the channel's own playlists, not saved ones.
+    async fn list_playlists(&self) -> Result<Vec<RemotePlaylist>> {
+        let token = self.get_token().await?;
+
+        let mut playlists = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/playlists?part=snippet,contentDetails&mine=true&maxResults=50",
+                self.endpoints.api
+            );
+
+            if let Some(token_str) = &page_token {
+                url.push_str(&format!("&pageToken={}", token_str));
+            }
+
+            let resp: YoutubePlaylistResponse = self.api_get(&url, &token, quota::READ).await?;
+
+            for playlist in resp.items {
+                playlists.push(RemotePlaylist {
+                    id: playlist.id,
+                    name: playlist.snippet.title,
+                    owner: playlist.snippet.channel_title,
+                    owned: true,
+                    tracks: playlist.content_details.map(|d| d.item_count),
+                });
+            }
+
+            page_token = resp.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(playlists)
+    }
+
+    async fn create_playlist(&self, name: &str, description: Option<&str>) -> Result<String> {
+        let token = self.get_token().await?;
+
+        let body = serde_json::json!({
+            "snippet": {
+                "title": name,
+                "description": description.unwrap_or_default()
+            },
+            "status": {
+                "privacyStatus": "private"
+            }
+        });
+
+        let created: YoutubeCreatedPlaylist = self
+            .http
+            .post(format!(
+                "{}/playlists?part=snippet,status",
+                self.endpoints.api
+            ))
+            .cost(quota::WRITE)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse created playlist")?;
+
+        Ok(created.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::diff;
+
+    #[tokio::test]
+    async fn test_fetch_keeps_unavailable_videos_in_place() {
+        use crate::utils::test_server::{Request, TestServer};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut server = TestServer::bind().await;
+        server.serve(|request: &Request| {
+            let item = |n: u32, title: &str| {
+                serde_json::json!({
+                    "id": format!("item{}", n),
+                    "snippet": {"title": title, "videoOwnerChannelTitle": "Band"},
+                    "contentDetails": {"videoId": format!("v{}", n)}
+                })
+            };
+            let video = |n: u32, duration: &str| {
+                serde_json::json!({
+                    "id": format!("v{}", n),
+                    "snippet": {"title": format!("Song {}", n)},
+                    "contentDetails": {"duration": duration}
+                })
+            };
+            let body = match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/playlists") => serde_json::json!({
+                    "items": [{"id": "PL1", "snippet": {"title": "Mix"}}]
+                }),
+                ("GET", "/playlistItems") => serde_json::json!({
+                    "items": [item(1, "Song 1"), item(2, "Deleted video"), item(3, "Song 3")]
+                }),
+                // v2 is deleted, so /videos leaves it out
+                ("GET", "/videos") => serde_json::json!({
+                    "items": [video(1, "PT1M"), video(3, "PT3M")]
+                }),
+                _ => serde_json::json!({}),
+            };
+            (200, body.to_string())
+        });
+
+        let token = OAuthToken {
+            access_token: "token".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_at: None,
+            refresh_token: None,
+            scope: None,
+        };
+        let provider = YoutubeProvider::new("client".to_string(), "secret".to_string())
+            .with_endpoints(Endpoints {
+                api: server.url.clone(),
+                ..YoutubeProvider::default_endpoints()
+            })
+            .with_token(&token, dir.path());
+
+        let current = provider.fetch("PL1").await.unwrap();
+        let tracks: Vec<_> = current
+            .tracks
+            .iter()
+            .map(|t| (t.id.as_str(), t.duration_ms))
+            .collect();
+        assert_eq!(tracks, vec![("v1", 60_000), ("v2", 0), ("v3", 180_000)]);
+
+        // The recorded items match the snapshot, so no second fetch is needed
+        let desired = PlaylistSnapshot::fixture(ProviderKind::Youtube, &["v1", "v3"]);
+        let patch = diff(&current, &desired);
+        let before = server.requests().len();
+        provider
+            .apply_known(SourceKind::Playlist, "PL1", &current, &patch, &desired)
+            .await
+            .unwrap();
+        let sent: Vec<_> = server.requests()[before..]
+            .iter()
+            .map(|r| (r.method.clone(), r.param("id").map(str::to_string)))
+            .collect();
+        assert_eq!(
+            sent,
+            vec![("DELETE".to_string(), Some("item2".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_apply_cost_counts_each_write() {
+        let cost = |current: &[&str], desired: &[&str], source| {
+            let (current, desired) = (
+                PlaylistSnapshot::fixture(ProviderKind::Youtube, current),
+                PlaylistSnapshot::fixture(ProviderKind::Youtube, desired),
+            );
+            let patch = diff(&current, &desired);
+            YoutubeProvider::apply_cost(source, &current.tracks, &patch, &desired.tracks)
+        };
+
+        // One delete, one insert, then "d" moved to the front
+        assert_eq!(
+            cost(&["a", "b", "c"], &["d", "a", "c"], SourceKind::Playlist),
+            3 * quota::WRITE
+        );
+        assert_eq!(cost(&["a"], &["a"], SourceKind::Playlist), 0);
+        // Each surplus copy is deleted on its own
+        assert_eq!(
+            cost(&["a", "b", "a", "a"], &["a", "b"], SourceKind::Playlist),
+            2 * quota::WRITE
+        );
+        // Liked videos are rated one at a time and never read
+        assert_eq!(
+            cost(&["a", "b"], &["b", "c"], SourceKind::Liked),
+            2 * quota::WRITE
+        );
+    }
 }
@@ -0,0 +1,218 @@
+//! Single-file bundles of a tracked playlist and its history, for sharing
+//! without copying `.grit` by hand.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{snapshot, JournalEntry};
+use crate::provider::PlaylistSnapshot;
+
+const FORMAT: &str = "grit-bundle";
+const VERSION: u32 = 1;
+
+/// Files of a playlist directory that go into a bundle. Anything else
+/// (staged changes, per-playlist config, reports) stays on this machine.
+const PLAYLIST_FILES: [&str; 3] = ["playlist.yaml", "journal.log", "remotes.json"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub playlist_id: String,
+    pub created_at: DateTime<Utc>,
+    /// Contents by path relative to the playlist directory
+    pub files: BTreeMap<String, String>,
+}
+
+impl Bundle {
+    /// Pack a tracked playlist: its snapshot, journal, remotes and every
+    /// stored snapshot.
+    pub fn create(grit_dir: &Path, playlist_id: &str) -> Result<Self> {
+        let dir = snapshot::snapshot_path(grit_dir, playlist_id)
+            .parent()
+            .context("Invalid playlist directory")?
+            .to_path_buf();
+
+        let mut files = BTreeMap::new();
+        for name in PLAYLIST_FILES {
+            let path = dir.join(name);
+            if path.exists() {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {:?}", path))?;
+                files.insert(name.to_string(), content);
+            }
+        }
+
+        let snapshots_dir = snapshot::snapshots_dir(grit_dir, playlist_id);
+        if snapshots_dir.exists() {
+            let entries = fs::read_dir(&snapshots_dir)
+                .with_context(|| format!("Failed to read directory {:?}", snapshots_dir))?;
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let relative = format!("snapshots/{}", name);
+                if is_bundled(&relative) {
+                    let content = fs::read_to_string(entry.path())
+                        .with_context(|| format!("Failed to read {:?}", entry.path()))?;
+                    files.insert(relative, content);
+                }
+            }
+        }
+
+        Ok(Self {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            playlist_id: playlist_id.to_string(),
+            created_at: Utc::now(),
+            files,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let bundle: Self = serde_json::from_str(&content)
+            .with_context(|| format!("{:?} is not a grit bundle", path))?;
+
+        if bundle.format != FORMAT {
+            bail!("{:?} is not a grit bundle", path);
+        }
+        if bundle.version > VERSION {
+            bail!(
+                "Bundle version {} is newer than this grit supports ({})",
+                bundle.version,
+                VERSION
+            );
+        }
+        Ok(bundle)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize bundle")?;
+        fs::write(path, json).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    pub fn snapshot(&self) -> Result<PlaylistSnapshot> {
+        let content = self
+            .files
+            .get("playlist.yaml")
+            .context("Bundle has no playlist.yaml")?;
+        serde_yaml::from_str(content).context("Failed to parse the bundled playlist.yaml")
+    }
+
+    pub fn journal(&self) -> Result<Vec<JournalEntry>> {
+        self.files
+            .get("journal.log")
+            .map(String::as_str)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("Failed to parse bundled journal"))
+            .collect()
+    }
+
+    /// Recreate the playlist under `grit_dir`. Fails if it is already
+    /// tracked; files a bundle shouldn't contain are ignored.
+    pub fn unpack(&self, grit_dir: &Path) -> Result<()> {
+        let id = &self.playlist_id;
+        if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+            bail!("Invalid playlist ID '{}' in bundle", id);
+        }
+
+        let snapshot_path = snapshot::snapshot_path(grit_dir, id);
+        if snapshot_path.exists() {
+            bail!("Playlist {} is already tracked", id);
+        }
+
+        // Validate before writing anything
+        self.snapshot()?;
+        self.journal()?;
+
+        let dir = snapshot_path
+            .parent()
+            .context("Invalid playlist directory")?;
+        fs::create_dir_all(dir.join("snapshots"))
+            .with_context(|| format!("Failed to create directory {:?}", dir))?;
+
+        for (name, content) in self.files.iter().filter(|(name, _)| is_bundled(name)) {
+            let path = dir.join(name);
+            fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a path inside the playlist directory belongs in a bundle.
+fn is_bundled(relative: &str) -> bool {
+    if PLAYLIST_FILES.contains(&relative) {
+        return true;
+    }
+    relative
+        .strip_prefix("snapshots/")
+        .and_then(|name| name.strip_suffix(".yaml"))
+        .is_some_and(|hash| !hash.is_empty() && hash.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderKind;
+    use crate::state::{staging, Operation};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_and_unpack_roundtrip() {
+        let source = TempDir::new().unwrap();
+        let grit_dir = source.path();
+
+        let snap = PlaylistSnapshot {
+            id: "pl".to_string(),
+            ..PlaylistSnapshot::fixture(ProviderKind::Spotify, &[])
+        };
+        let hash = snapshot::compute_hash(&snap).unwrap();
+        snapshot::save(&snap, &snapshot::snapshot_path(grit_dir, "pl")).unwrap();
+        snapshot::save_by_hash(&snap, &hash, grit_dir, "pl").unwrap();
+        JournalEntry::append(
+            &JournalEntry::journal_path(grit_dir, "pl"),
+            &JournalEntry::new(Operation::Init, hash.clone(), 0, 0, 0),
+        )
+        .unwrap();
+        staging::save_staged(grit_dir, "pl", &Default::default()).unwrap();
+        fs::write(grit_dir.join("encryption.key"), b"secret").unwrap();
+
+        let bundle = Bundle::create(grit_dir, "pl").unwrap();
+        let names: Vec<&str> = bundle.files.keys().map(String::as_str).collect();
+        let snapshot_file = format!("snapshots/{}.yaml", hash);
+        assert_eq!(
+            names,
+            vec!["journal.log", "playlist.yaml", snapshot_file.as_str()]
+        );
+
+        let file = source.path().join("mix.gritbundle");
+        bundle.write(&file).unwrap();
+
+        let target = TempDir::new().unwrap();
+        let bundle = Bundle::read(&file).unwrap();
+        bundle.unpack(target.path()).unwrap();
+
+        assert_eq!(
+            snapshot::load_by_hash(&hash, target.path(), "pl")
+                .unwrap()
+                .name,
+            "Mix"
+        );
+        assert_eq!(bundle.journal().unwrap().len(), 1);
+        assert!(bundle.unpack(target.path()).is_err());
+    }
+
+    #[test]
+    fn test_unpack_ignores_unexpected_paths() {
+        assert!(is_bundled("snapshots/abc123.yaml"));
+        assert!(!is_bundled("snapshots/../../credentials/spotify.yaml"));
+        assert!(!is_bundled("../encryption.key"));
+        assert!(!is_bundled("staged.json"));
+    }
+}
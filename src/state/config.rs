@@ -0,0 +1,380 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use toml::{Table, Value};
+
+/// Where a setting is stored. Later layers override earlier ones:
+/// global, then repository, then per-playlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConfigScope {
+    Global,
+    Repo,
+    Playlist,
+}
+
+impl std::fmt::Display for ConfigScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigScope::Global => write!(f, "global"),
+            ConfigScope::Repo => write!(f, "repo"),
+            ConfigScope::Playlist => write!(f, "playlist"),
+        }
+    }
+}
+
+/// Settings grit understands, with a short description for `grit config list`.
+pub const KNOWN_KEYS: &[(&str, &str)] = &[
+    (
+        "core.provider",
+        "Default provider for init and search (spotify/youtube)",
+    ),
+    ("user.name", "Author recorded in the journal"),
+    ("spotify.client_id", "Spotify OAuth client ID"),
+    ("spotify.client_secret", "Spotify OAuth client secret"),
+    ("youtube.client_id", "YouTube OAuth client ID"),
+    ("youtube.client_secret", "YouTube OAuth client secret"),
+    (
+        "auth.port",
+        "Local port for the OAuth redirect (default 8888)",
+    ),
+    (
+        "search.limit",
+        "Number of results fetched per search (default 10)",
+    ),
+    (
+        "search.page_size",
+        "Search results shown per page (default 5)",
+    ),
+    ("local.root", "Music directory used by the local provider"),
+    ("subsonic.url", "Base URL of the Subsonic server"),
+    ("subsonic.username", "Subsonic account name"),
+    (
+        "player.backend",
+        "Playback backend: auto, spotify or mpv (default auto)",
+    ),
+    (
+        "lyrics.url",
+        "LRCLIB-compatible lyrics server (default https://lrclib.net/api)",
+    ),
+    (
+        "spotify.api_url",
+        "Spotify API base URL (for proxies and tests)",
+    ),
+    (
+        "spotify.auth_url",
+        "Spotify OAuth authorization URL (for proxies and tests)",
+    ),
+    (
+        "spotify.token_url",
+        "Spotify OAuth token URL (for proxies and tests)",
+    ),
+    (
+        "youtube.api_url",
+        "YouTube API base URL (for proxies and tests)",
+    ),
+    (
+        "youtube.auth_url",
+        "YouTube OAuth authorization URL (for proxies and tests)",
+    ),
+    (
+        "youtube.token_url",
+        "YouTube OAuth token URL (for proxies and tests)",
+    ),
+    (
+        "youtube.quota_budget",
+        "Daily YouTube API units grit may spend, 0 for no limit (default 10000)",
+    ),
+    (
+        "http.max_retries",
+        "Retries of a rate-limited or failed API request (default 4)",
+    ),
+    (
+        "http.max_requests",
+        "API requests a command may send, 0 for no limit (default 0)",
+    ),
+    (
+        "storage.backend",
+        "History storage: journal or git (default journal)",
+    ),
+    (
+        "alias.<name>",
+        "Command alias, e.g. alias.ci = \"commit -m\"",
+    ),
+];
+
+/// Settings read as numbers; `grit config set` refuses anything else for them.
+pub const NUMERIC_KEYS: &[&str] = &[
+    "auth.port",
+    "search.limit",
+    "search.page_size",
+    "youtube.quota_budget",
+    "http.max_retries",
+    "http.max_requests",
+];
+
+/// Settings read with the playlist layer; the rest only come from the global
+/// and repository files.
+pub const PLAYLIST_KEYS: &[&str] = &["player.backend", "lyrics.url", "storage.backend"];
+
+/// Merged view over the global, repository and per-playlist config files.
+#[derive(Debug, Default)]
+pub struct Config {
+    layers: Vec<(ConfigScope, Table)>,
+}
+
+impl Config {
+    /// Load every layer that exists. `playlist_id` selects the per-playlist layer.
+    pub fn load(grit_dir: &Path, playlist_id: Option<&str>) -> Result<Self> {
+        let mut layers = Vec::new();
+
+        if let Some(path) = global_path() {
+            layers.push((ConfigScope::Global, read_table(&path)?));
+        }
+        layers.push((ConfigScope::Repo, read_table(&repo_path(grit_dir))?));
+        if let Some(id) = playlist_id {
+            layers.push((
+                ConfigScope::Playlist,
+                read_table(&playlist_path(grit_dir, id))?,
+            ));
+        }
+
+        Ok(Self { layers })
+    }
+
+    /// Look up a dotted key such as `search.page_size`, most specific layer first.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|(_, table)| lookup(table, key))
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<String> {
+        match self.get(key)? {
+            Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    pub fn get_u64(&self, key: &str) -> Result<Option<u64>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Integer(n)) if *n >= 0 => Ok(Some(*n as u64)),
+            Some(Value::String(s)) => s
+                .parse()
+                .map(Some)
+                .with_context(|| format!("Config key '{}' must be a number", key)),
+            Some(_) => anyhow::bail!("Config key '{}' must be a positive number", key),
+        }
+    }
+
+    /// Every key/value pair with the scope it comes from. Overridden values are omitted.
+    pub fn entries(&self) -> Vec<(String, Value, ConfigScope)> {
+        let mut merged: Vec<(String, Value, ConfigScope)> = Vec::new();
+
+        for (scope, table) in &self.layers {
+            let mut flat = Vec::new();
+            flatten("", table, &mut flat);
+            for (key, value) in flat {
+                merged.retain(|(k, _, _)| k != &key);
+                merged.push((key, value, *scope));
+            }
+        }
+
+        merged.sort_by(|a, b| a.0.cmp(&b.0));
+        merged
+    }
+
+    /// Expansion for `alias.<name>`, if configured.
+    pub fn alias(&self, name: &str) -> Option<String> {
+        self.get_str(&format!("alias.{}", name))
+    }
+}
+
+/// `$GRIT_CONFIG`, else `$XDG_CONFIG_HOME/grit/config.toml`, else `~/.config/grit/config.toml`.
+pub fn global_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("GRIT_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = std::env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok()?;
+    Some(base.join("grit").join("config.toml"))
+}
+
+pub fn repo_path(grit_dir: &Path) -> PathBuf {
+    grit_dir.join("config.toml")
+}
+
+pub fn playlist_path(grit_dir: &Path, playlist_id: &str) -> PathBuf {
+    grit_dir
+        .join("playlists")
+        .join(playlist_id)
+        .join("config.toml")
+}
+
+/// Set `key` in the config file at `path`. Numbers and booleans are stored
+/// as such; everything else is stored as a string.
+pub fn set(path: &Path, key: &str, raw_value: &str) -> Result<()> {
+    let mut table = read_table(path)?;
+    let value = parse_value(raw_value);
+
+    let (parents, leaf) = split_key(key)?;
+    let mut current = &mut table;
+    for part in parents {
+        let entry = current
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Table(Table::new()));
+        current = entry
+            .as_table_mut()
+            .with_context(|| format!("Config key '{}' is not a section", part))?;
+    }
+    current.insert(leaf.to_string(), value);
+
+    write_table(path, &table)
+}
+
+/// Remove `key` from the config file at `path`. Returns whether it was present.
+pub fn unset(path: &Path, key: &str) -> Result<bool> {
+    let mut table = read_table(path)?;
+
+    let (parents, leaf) = split_key(key)?;
+    let mut current = &mut table;
+    for part in parents {
+        match current.get_mut(part).and_then(Value::as_table_mut) {
+            Some(next) => current = next,
+            None => return Ok(false),
+        }
+    }
+
+    let removed = current.remove(leaf).is_some();
+    if removed {
+        write_table(path, &table)?;
+    }
+    Ok(removed)
+}
+
+fn split_key(key: &str) -> Result<(Vec<&str>, &str)> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.len() < 2 || parts.iter().any(|p| p.is_empty()) {
+        anyhow::bail!("Invalid config key '{}' (expected section.name)", key);
+    }
+    let (leaf, parents) = parts.split_last().expect("key has at least two parts");
+    Ok((parents.to_vec(), leaf))
+}
+
+fn parse_value(raw: &str) -> Value {
+    if let Ok(n) = raw.parse::<i64>() {
+        Value::Integer(n)
+    } else if let Ok(b) = raw.parse::<bool>() {
+        Value::Boolean(b)
+    } else {
+        Value::String(raw.to_string())
+    }
+}
+
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
+
+fn flatten(prefix: &str, table: &Table, out: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let full = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(inner) => flatten(&full, inner, out),
+            other => out.push((full, other.clone())),
+        }
+    }
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    if !path.exists() {
+        return Ok(Table::new());
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read config {:?}", path))?;
+    content
+        .parse::<Table>()
+        .with_context(|| format!("Failed to parse config {:?}", path))
+}
+
+fn write_table(path: &Path, table: &Table) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {:?}", parent))?;
+    }
+    let content = toml::to_string_pretty(table).context("Failed to serialize config")?;
+    fs::write(path, content).with_context(|| format!("Failed to write config {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_set_get_unset() {
+        let temp = TempDir::new().unwrap();
+        let path = repo_path(temp.path());
+
+        set(&path, "search.page_size", "8").unwrap();
+        set(&path, "core.provider", "youtube").unwrap();
+
+        let config = Config {
+            layers: vec![(ConfigScope::Repo, read_table(&path).unwrap())],
+        };
+        assert_eq!(config.get_u64("search.page_size").unwrap(), Some(8));
+        assert_eq!(config.get_str("core.provider").as_deref(), Some("youtube"));
+
+        assert!(unset(&path, "search.page_size").unwrap());
+        assert!(!unset(&path, "search.page_size").unwrap());
+    }
+
+    #[test]
+    fn test_playlist_layer_overrides_repo() {
+        let temp = TempDir::new().unwrap();
+        set(&repo_path(temp.path()), "player.backend", "spotify").unwrap();
+        set(&playlist_path(temp.path(), "p1"), "player.backend", "mpv").unwrap();
+
+        let repo_only = Config::load(temp.path(), None).unwrap();
+        let with_playlist = Config::load(temp.path(), Some("p1")).unwrap();
+
+        assert_eq!(
+            repo_only.get_str("player.backend").as_deref(),
+            Some("spotify")
+        );
+        assert_eq!(
+            with_playlist.get_str("player.backend").as_deref(),
+            Some("mpv")
+        );
+
+        let (_, _, scope) = with_playlist
+            .entries()
+            .into_iter()
+            .find(|(k, _, _)| k == "player.backend")
+            .unwrap();
+        assert_eq!(scope, ConfigScope::Playlist);
+    }
+
+    #[test]
+    fn test_invalid_keys_rejected() {
+        let temp = TempDir::new().unwrap();
+        let path = repo_path(temp.path());
+        assert!(set(&path, "nosection", "x").is_err());
+        assert!(set(&path, "a..b", "x").is_err());
+    }
+}
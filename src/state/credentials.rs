@@ -5,7 +5,7 @@ use base64::Engine;
 use std::fs;
 use std::path::Path;
 
-pub fn save(grit_dir: &Path, provider: ProviderKind, token: &OAuthToken) -> Result<()> {
+pub fn save(grit_dir: &Path, provider: &ProviderKind, token: &OAuthToken) -> Result<()> {
     let path = credentials_path(grit_dir, provider);
 
     if let Some(parent) = path.parent() {
@@ -32,7 +32,7 @@ pub fn save(grit_dir: &Path, provider: ProviderKind, token: &OAuthToken) -> Resu
     Ok(())
 }
 
-pub fn load(grit_dir: &Path, provider: ProviderKind) -> Result<Option<OAuthToken>> {
+pub fn load(grit_dir: &Path, provider: &ProviderKind) -> Result<Option<OAuthToken>> {
     let path = credentials_path(grit_dir, provider);
 
     if !path.exists() {
@@ -56,7 +56,6 @@ pub fn load(grit_dir: &Path, provider: ProviderKind) -> Result<Option<OAuthToken
     Ok(Some(token))
 }
 
-#[allow(dead_code)]
 pub fn is_expired(token: &OAuthToken) -> bool {
     match token.expires_at {
         Some(expires_at) => {
@@ -72,7 +71,7 @@ pub fn is_expired(token: &OAuthToken) -> bool {
 }
 
 /// Delete credentials for a provider
-pub fn delete(grit_dir: &Path, provider: ProviderKind) -> Result<()> {
+pub fn delete(grit_dir: &Path, provider: &ProviderKind) -> Result<()> {
     let path = credentials_path(grit_dir, provider);
 
     if path.exists() {
@@ -83,12 +82,10 @@ pub fn delete(grit_dir: &Path, provider: ProviderKind) -> Result<()> {
     Ok(())
 }
 
-fn credentials_path(grit_dir: &Path, provider: ProviderKind) -> std::path::PathBuf {
-    let filename = match provider {
-        ProviderKind::Spotify => "spotify.json",
-        ProviderKind::Youtube => "youtube.json",
-    };
-    grit_dir.join("credentials").join(filename)
+fn credentials_path(grit_dir: &Path, provider: &ProviderKind) -> std::path::PathBuf {
+    grit_dir
+        .join("credentials")
+        .join(format!("{}.json", provider))
 }
 
 #[cfg(test)]
@@ -111,8 +108,8 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let token = sample_token();
 
-        save(temp.path(), ProviderKind::Spotify, &token).unwrap();
-        let loaded = load(temp.path(), ProviderKind::Spotify).unwrap();
+        save(temp.path(), &ProviderKind::Spotify, &token).unwrap();
+        let loaded = load(temp.path(), &ProviderKind::Spotify).unwrap();
 
         assert!(loaded.is_some());
         let loaded = loaded.unwrap();
@@ -123,7 +120,7 @@ mod tests {
     #[test]
     fn test_load_nonexistent() {
         let temp = TempDir::new().unwrap();
-        let loaded = load(temp.path(), ProviderKind::Spotify).unwrap();
+        let loaded = load(temp.path(), &ProviderKind::Spotify).unwrap();
         assert!(loaded.is_none());
     }
 
@@ -168,11 +165,11 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let token = sample_token();
 
-        save(temp.path(), ProviderKind::Spotify, &token).unwrap();
-        assert!(load(temp.path(), ProviderKind::Spotify).unwrap().is_some());
+        save(temp.path(), &ProviderKind::Spotify, &token).unwrap();
+        assert!(load(temp.path(), &ProviderKind::Spotify).unwrap().is_some());
 
-        delete(temp.path(), ProviderKind::Spotify).unwrap();
-        assert!(load(temp.path(), ProviderKind::Spotify).unwrap().is_none());
+        delete(temp.path(), &ProviderKind::Spotify).unwrap();
+        assert!(load(temp.path(), &ProviderKind::Spotify).unwrap().is_none());
     }
 
     #[test]
@@ -195,11 +192,11 @@ mod tests {
             scope: None,
         };
 
-        save(temp.path(), ProviderKind::Spotify, &spotify_token).unwrap();
-        save(temp.path(), ProviderKind::Youtube, &youtube_token).unwrap();
+        save(temp.path(), &ProviderKind::Spotify, &spotify_token).unwrap();
+        save(temp.path(), &ProviderKind::Youtube, &youtube_token).unwrap();
 
-        let loaded_spotify = load(temp.path(), ProviderKind::Spotify).unwrap().unwrap();
-        let loaded_youtube = load(temp.path(), ProviderKind::Youtube).unwrap().unwrap();
+        let loaded_spotify = load(temp.path(), &ProviderKind::Spotify).unwrap().unwrap();
+        let loaded_youtube = load(temp.path(), &ProviderKind::Youtube).unwrap().unwrap();
 
         assert_eq!(loaded_spotify.access_token, "spotify_token");
         assert_eq!(loaded_youtube.access_token, "youtube_token");
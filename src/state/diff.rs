@@ -2,6 +2,7 @@ use anyhow::Result;
 
 use crate::provider::{DiffPatch, PlaylistSnapshot, Track, TrackChange};
 use std::collections::HashMap;
+use std::hash::Hash;
 
 pub fn diff(old: &PlaylistSnapshot, new: &PlaylistSnapshot) -> DiffPatch {
     let mut changes = Vec::new();
@@ -55,6 +56,88 @@ pub fn diff(old: &PlaylistSnapshot, new: &PlaylistSnapshot) -> DiffPatch {
     DiffPatch { changes }
 }
 
+/// Changes from `current` to `desired` counted per occurrence, unlike
+/// `diff`, for remotes that may hold a track more than once: the indexes of
+/// `current` to remove and of `desired` to add. When a track has more
+/// copies on one side, the later copies are the ones removed or added.
+pub fn occurrence_changes<T: Eq + Hash>(current: &[T], desired: &[T]) -> (Vec<usize>, Vec<usize>) {
+    fn counts<T: Eq + Hash>(items: &[T]) -> HashMap<&T, usize> {
+        let mut counts = HashMap::new();
+        for item in items {
+            *counts.entry(item).or_default() += 1;
+        }
+        counts
+    }
+
+    fn surplus<T: Eq + Hash>(items: &[T], other: &HashMap<&T, usize>) -> Vec<usize> {
+        let mut seen: HashMap<&T, usize> = HashMap::new();
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                let copy = seen.entry(*item).or_default();
+                *copy += 1;
+                *copy > other.get(*item).copied().unwrap_or(0)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    (
+        surplus(current, &counts(desired)),
+        surplus(desired, &counts(current)),
+    )
+}
+
+/// Like `diff`, but each copy of a track counts, so `apply_patch` turns
+/// `old` into exactly `new`, duplicates included: removals, additions at
+/// their final index, then moves applied one after another.
+pub fn diff_occurrences(old: &PlaylistSnapshot, new: &PlaylistSnapshot) -> DiffPatch {
+    let old_ids: Vec<&str> = old.tracks.iter().map(|t| t.id.as_str()).collect();
+    let new_ids: Vec<&str> = new.tracks.iter().map(|t| t.id.as_str()).collect();
+    let (removals, additions) = occurrence_changes(&old_ids, &new_ids);
+
+    let mut changes = Vec::new();
+    let mut tracks: Vec<&Track> = Vec::new();
+    for (index, track) in old.tracks.iter().enumerate() {
+        if removals.contains(&index) {
+            changes.push(TrackChange::Removed {
+                track: track.clone(),
+                index,
+            });
+        } else {
+            tracks.push(track);
+        }
+    }
+
+    for index in additions {
+        let track = &new.tracks[index];
+        changes.push(TrackChange::Added {
+            track: track.clone(),
+            index,
+        });
+        tracks.insert(index, track);
+    }
+
+    for (to, wanted) in new.tracks.iter().enumerate() {
+        if tracks[to].id == wanted.id {
+            continue;
+        }
+        let from = (to + 1..tracks.len())
+            .find(|&i| tracks[i].id == wanted.id)
+            .expect("both sides hold the same tracks");
+        let track = tracks.remove(from);
+        tracks.insert(to, track);
+        changes.push(TrackChange::Moved {
+            track: track.clone(),
+            from,
+            to,
+        });
+    }
+
+    DiffPatch { changes }
+}
+
 pub fn apply_patch(snapshot: &mut PlaylistSnapshot, patch: &DiffPatch) -> Result<()> {
     // Process changes in correct order:
     // 1. Removals (from highest index to lowest to avoid shifting issues)
@@ -74,7 +157,7 @@ pub fn apply_patch(snapshot: &mut PlaylistSnapshot, patch: &DiffPatch) -> Result
     }
 
     // Sort removals by index (highest first to avoid shifting)
-    removals.sort_by(|a, b| b.0.cmp(&a.0));
+    removals.sort_by_key(|r| std::cmp::Reverse(r.0));
 
     //remove
     for (_, change) in removals {
@@ -108,3 +191,41 @@ pub fn apply_patch(snapshot: &mut PlaylistSnapshot, patch: &DiffPatch) -> Result
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderKind;
+
+    #[test]
+    fn test_diff_occurrences_keeps_duplicates() {
+        let cases: [(&[&str], &[&str]); 4] = [
+            (&["a", "b"], &["a", "b", "a"]),
+            (&["a", "b", "a", "c"], &["c", "a", "b"]),
+            (&["a", "a", "b"], &["b", "a", "c", "a", "a"]),
+            (&[], &["x", "x"]),
+        ];
+        for (old, new) in cases {
+            let mut snapshot = PlaylistSnapshot::fixture(ProviderKind::Spotify, old);
+            apply_patch(
+                &mut snapshot,
+                &diff_occurrences(
+                    &PlaylistSnapshot::fixture(ProviderKind::Spotify, old),
+                    &PlaylistSnapshot::fixture(ProviderKind::Spotify, new),
+                ),
+            )
+            .unwrap();
+            let ids: Vec<&str> = snapshot.tracks.iter().map(|t| t.id.as_str()).collect();
+            assert_eq!(ids, new, "from {:?}", old);
+        }
+
+        let patch = diff_occurrences(
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b"]),
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b", "a"]),
+        );
+        assert!(matches!(
+            patch.changes[..],
+            [TrackChange::Added { index: 2, .. }]
+        ));
+    }
+}
@@ -0,0 +1,421 @@
+//! Optional storage backend that mirrors each playlist's journal into a git
+//! repository under `.grit/git/<playlist-id>/`, one commit per journal entry.
+//!
+//! `journal.log` is still written, so switching backends loses nothing: the
+//! repository is (re)built from the journal whenever it is behind. Each
+//! commit names its entry in a `Grit-Entry` trailer, which is where the next
+//! sync picks up; commits made by hand have no trailers and are passed over.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use super::{config::Config, snapshot, JournalEntry, Operation};
+
+const TRAILER_OPERATION: &str = "Grit-Operation";
+const TRAILER_SNAPSHOT: &str = "Grit-Snapshot";
+const TRAILER_CHANGES: &str = "Grit-Changes";
+const TRAILER_AUTHOR: &str = "Grit-Author";
+const TRAILER_ENTRY: &str = "Grit-Entry";
+
+/// Separates records in `git log` output
+const RECORD_SEPARATOR: char = '\u{1e}';
+
+/// Whether `storage.backend` selects git for this playlist.
+pub fn enabled(grit_dir: &Path, playlist_id: &str) -> Result<bool> {
+    let config = Config::load(grit_dir, Some(playlist_id))?;
+    match config.get_str("storage.backend").as_deref() {
+        None | Some("journal") => Ok(false),
+        Some("git") => Ok(true),
+        Some(other) => bail!(
+            "Unknown storage.backend '{}' (expected journal or git)",
+            other
+        ),
+    }
+}
+
+pub fn repo_dir(grit_dir: &Path, playlist_id: &str) -> PathBuf {
+    grit_dir.join("git").join(playlist_id)
+}
+
+/// Commit every journal entry the repository doesn't have yet.
+pub fn sync(grit_dir: &Path, playlist_id: &str) -> Result<()> {
+    let repo = repo_dir(grit_dir, playlist_id);
+    if !repo.join(".git").exists() {
+        fs::create_dir_all(&repo)
+            .with_context(|| format!("Failed to create directory {:?}", repo))?;
+        git(&repo, &["init", "--quiet"], &[])?;
+    }
+
+    let entries = JournalEntry::read_all(&JournalEntry::journal_path(grit_dir, playlist_id))?;
+    let exported = exported_count(&repo, &entries)?;
+
+    for (index, entry) in entries.iter().enumerate().skip(exported) {
+        // Entries whose snapshot is gone still get a commit, on the previous tree
+        if let Ok(snap) = snapshot::load_by_hash(&entry.snapshot_hash, grit_dir, playlist_id) {
+            snapshot::save(&snap, &repo.join("playlist.yaml"))?;
+            git(&repo, &["add", "playlist.yaml"], &[])?;
+        }
+        commit(&repo, entry, &entry_id(index, entry)?)?;
+    }
+
+    Ok(())
+}
+
+/// How many journal entries the repository already has: everything up to the
+/// entry named by the newest `Grit-Entry` trailer.
+fn exported_count(repo: &Path, entries: &[JournalEntry]) -> Result<usize> {
+    let bodies = commit_bodies(repo)?;
+    let ids = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| entry_id(index, entry))
+        .collect::<Result<Vec<_>>>()?;
+
+    for body in bodies.iter().rev() {
+        if let Some(id) = trailer(body, TRAILER_ENTRY) {
+            return ids
+                .iter()
+                .position(|known| *known == id)
+                .map(|index| index + 1)
+                .with_context(|| {
+                    format!(
+                        "Entry {} in {:?} is not in the journal; delete the repository to rebuild it",
+                        id, repo
+                    )
+                });
+        }
+    }
+
+    // Repositories written before entries were named have one commit per entry
+    Ok(bodies
+        .iter()
+        .filter(|body| trailer(body, TRAILER_OPERATION).is_some())
+        .count())
+}
+
+/// Identifies a journal entry by its position and content.
+fn entry_id(index: usize, entry: &JournalEntry) -> Result<String> {
+    let json = serde_json::to_string(entry).context("Failed to serialize journal entry")?;
+    let mut hasher = Sha256::new();
+    hasher.update(index.to_string().as_bytes());
+    hasher.update(b"\0");
+    hasher.update(json.as_bytes());
+    Ok(hasher
+        .finalize()
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// The journal as recorded in the repository's history, oldest first.
+pub fn read_log(grit_dir: &Path, playlist_id: &str) -> Result<Vec<JournalEntry>> {
+    let repo = repo_dir(grit_dir, playlist_id);
+    commit_records(&repo, "%aI%x00%B")?
+        .iter()
+        .filter_map(|record| parse_commit(record).transpose())
+        .collect()
+}
+
+/// Commit messages, oldest first.
+fn commit_bodies(repo: &Path) -> Result<Vec<String>> {
+    commit_records(repo, "%B")
+}
+
+/// One `git log` record per commit in `format`, oldest first.
+fn commit_records(repo: &Path, format: &str) -> Result<Vec<String>> {
+    if commit_count(repo)? == 0 {
+        return Ok(Vec::new());
+    }
+
+    let format = format!("--format={}{}", format, RECORD_SEPARATOR);
+    let output = git(repo, &["log", "--reverse", &format], &[])?;
+    Ok(output
+        .split(RECORD_SEPARATOR)
+        .map(str::trim)
+        .filter(|record| !record.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn commit(repo: &Path, entry: &JournalEntry, id: &str) -> Result<()> {
+    let author = entry.author.as_deref().unwrap_or("grit");
+    let date = entry.timestamp.to_rfc3339();
+    let env = [
+        ("GIT_AUTHOR_NAME", author),
+        ("GIT_AUTHOR_EMAIL", ""),
+        ("GIT_AUTHOR_DATE", date.as_str()),
+        ("GIT_COMMITTER_NAME", author),
+        ("GIT_COMMITTER_EMAIL", ""),
+        ("GIT_COMMITTER_DATE", date.as_str()),
+    ];
+
+    let message = commit_message(entry, id);
+    git(
+        repo,
+        &[
+            "-c",
+            "commit.gpgsign=false",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "--no-verify",
+            "-m",
+            &message,
+        ],
+        &env,
+    )?;
+    Ok(())
+}
+
+fn commit_message(entry: &JournalEntry, id: &str) -> String {
+    let summary = entry
+        .message
+        .clone()
+        .unwrap_or_else(|| default_summary(entry));
+
+    let mut trailers = vec![
+        format!("{}: {}", TRAILER_OPERATION, entry.operation),
+        format!("{}: {}", TRAILER_SNAPSHOT, entry.snapshot_hash),
+        format!(
+            "{}: +{} -{} ~{}",
+            TRAILER_CHANGES, entry.added, entry.removed, entry.moved
+        ),
+    ];
+    if let Some(author) = &entry.author {
+        trailers.push(format!("{}: {}", TRAILER_AUTHOR, author));
+    }
+    trailers.push(format!("{}: {}", TRAILER_ENTRY, id));
+
+    format!("{}\n\n{}\n", summary, trailers.join("\n"))
+}
+
+/// Subject for entries without a message, e.g. "pull (+2 -1 ~0)".
+fn default_summary(entry: &JournalEntry) -> String {
+    format!(
+        "{} (+{} -{} ~{})",
+        entry.operation, entry.added, entry.removed, entry.moved
+    )
+}
+
+/// Value of the trailer `name` in the last paragraph of a commit message.
+fn trailer(body: &str, name: &str) -> Option<String> {
+    let (_, trailer_block) = body.rsplit_once("\n\n").unwrap_or(("", body));
+    trailer_block.lines().find_map(|line| {
+        line.strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix(": "))
+            .map(str::to_string)
+    })
+}
+
+/// The journal entry a commit records, or `None` for commits grit didn't make.
+fn parse_commit(record: &str) -> Result<Option<JournalEntry>> {
+    let (date, body) = record
+        .split_once('\0')
+        .context("Unexpected git log output")?;
+    let timestamp: DateTime<Utc> = DateTime::parse_from_rfc3339(date.trim())
+        .with_context(|| format!("Invalid commit date '{}'", date))?
+        .with_timezone(&Utc);
+
+    let Some(operation) = trailer(body, TRAILER_OPERATION) else {
+        return Ok(None);
+    };
+    let text = body.rsplit_once("\n\n").map_or("", |(text, _)| text);
+    let operation = <Operation as clap::ValueEnum>::from_str(&operation, true)
+        .map_err(|_| anyhow::anyhow!("Unknown operation '{}' in git history", operation))?;
+
+    let changes = trailer(body, TRAILER_CHANGES).unwrap_or_default();
+    let count = |prefix: char| {
+        changes
+            .split_whitespace()
+            .find_map(|part| part.strip_prefix(prefix)?.parse().ok())
+            .unwrap_or(0)
+    };
+
+    let mut entry = JournalEntry {
+        timestamp,
+        operation,
+        snapshot_hash: trailer(body, TRAILER_SNAPSHOT).unwrap_or_default(),
+        added: count('+'),
+        removed: count('-'),
+        moved: count('~'),
+        message: None,
+        author: trailer(body, TRAILER_AUTHOR),
+    };
+    let text = text.trim();
+    if text != default_summary(&entry) {
+        entry.message = Some(text.to_string());
+    }
+    Ok(Some(entry))
+}
+
+fn commit_count(repo: &Path) -> Result<usize> {
+    if !repo.join(".git").exists() {
+        return Ok(0);
+    }
+    // rev-list fails on a repository without commits
+    match git(repo, &["rev-list", "--count", "HEAD"], &[]) {
+        Ok(count) => count
+            .trim()
+            .parse()
+            .context("Unexpected git rev-list output"),
+        Err(_) => Ok(0),
+    }
+}
+
+fn git(repo: &Path, args: &[&str], env: &[(&str, &str)]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .envs(env.iter().copied())
+        .output()
+        .context("Failed to run git (is it installed?)")?;
+
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{PlaylistSnapshot, ProviderKind};
+    use tempfile::TempDir;
+
+    fn save_snapshot(grit_dir: &Path) {
+        let snap = PlaylistSnapshot {
+            id: "pl".to_string(),
+            ..PlaylistSnapshot::fixture(ProviderKind::Spotify, &[])
+        };
+        snapshot::save_by_hash(&snap, "h1", grit_dir, "pl").unwrap();
+    }
+
+    fn record(grit_dir: &Path, message: &str) {
+        let entry = JournalEntry::new_with_message(
+            Operation::Commit,
+            "h1".to_string(),
+            1,
+            0,
+            0,
+            message.to_string(),
+        );
+        JournalEntry::append(&JournalEntry::journal_path(grit_dir, "pl"), &entry).unwrap();
+    }
+
+    fn messages(grit_dir: &Path) -> Vec<String> {
+        read_log(grit_dir, "pl")
+            .unwrap()
+            .into_iter()
+            .filter_map(|entry| entry.message)
+            .collect()
+    }
+
+    fn git_in(grit_dir: &Path, args: &[&str]) {
+        git(&repo_dir(grit_dir, "pl"), args, &[]).unwrap();
+    }
+
+    #[test]
+    fn test_sync_and_read_back() {
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path();
+        save_snapshot(grit_dir);
+
+        let journal = JournalEntry::journal_path(grit_dir, "pl");
+        let mut init = JournalEntry::new(Operation::Init, "h1".to_string(), 3, 0, 0);
+        init.author = Some("Alice".to_string());
+        init.timestamp = DateTime::parse_from_rfc3339("2024-01-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let commit = JournalEntry::new_with_message(
+            Operation::Commit,
+            "h1".to_string(),
+            1,
+            2,
+            0,
+            "Add summer songs\n\nFor the road trip".to_string(),
+        );
+        JournalEntry::append(&journal, &init).unwrap();
+        sync(grit_dir, "pl").unwrap();
+        JournalEntry::append(&journal, &commit).unwrap();
+        sync(grit_dir, "pl").unwrap();
+        sync(grit_dir, "pl").unwrap();
+
+        let entries = read_log(grit_dir, "pl").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation, Operation::Init);
+        assert_eq!(entries[0].message, None);
+        assert_eq!(entries[0].author.as_deref(), Some("Alice"));
+        assert_eq!(entries[0].timestamp, init.timestamp);
+        assert_eq!(entries[0].added, 3);
+        assert_eq!(entries[1].message, commit.message);
+        assert_eq!(entries[1].removed, 2);
+        assert!(repo_dir(grit_dir, "pl").join("playlist.yaml").exists());
+    }
+
+    #[test]
+    fn test_sync_resumes_after_an_interrupted_run() {
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path();
+        save_snapshot(grit_dir);
+
+        record(grit_dir, "one");
+        record(grit_dir, "two");
+        record(grit_dir, "three");
+        sync(grit_dir, "pl").unwrap();
+
+        // As if the run had stopped after the first commit
+        git_in(grit_dir, &["reset", "--quiet", "--hard", "HEAD~2"]);
+        assert_eq!(messages(grit_dir), ["one"]);
+
+        sync(grit_dir, "pl").unwrap();
+        assert_eq!(messages(grit_dir), ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn test_sync_passes_over_manual_commits() {
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path();
+        save_snapshot(grit_dir);
+
+        record(grit_dir, "one");
+        sync(grit_dir, "pl").unwrap();
+
+        let repo = repo_dir(grit_dir, "pl");
+        fs::write(repo.join("NOTES.md"), "Summer mixes\n").unwrap();
+        git_in(grit_dir, &["add", "NOTES.md"]);
+        git_in(
+            grit_dir,
+            &[
+                "-c",
+                "user.name=Sam",
+                "-c",
+                "user.email=sam@example.com",
+                "-c",
+                "commit.gpgsign=false",
+                "commit",
+                "--quiet",
+                "-m",
+                "Add notes",
+            ],
+        );
+
+        record(grit_dir, "two");
+        sync(grit_dir, "pl").unwrap();
+
+        assert_eq!(messages(grit_dir), ["one", "two"]);
+        assert_eq!(commit_count(&repo).unwrap(), 3);
+    }
+}
@@ -1,14 +1,17 @@
 use std::{
+    collections::HashMap,
     fs::{self, OpenOptions},
     io::Write,
     path::Path,
+    sync::OnceLock,
 };
 
 use anyhow::{Context, Ok};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
 pub enum Operation {
     Init,
     Pull,
@@ -26,6 +29,39 @@ pub struct JournalEntry {
     pub removed: usize,
     pub moved: usize,
     pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Operation::Init => "init",
+            Operation::Pull => "pull",
+            Operation::Push => "push",
+            Operation::Apply => "apply",
+            Operation::Commit => "commit",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+static CONFIGURED_AUTHOR: OnceLock<String> = OnceLock::new();
+
+/// Set the author from `user.name` in the config. Only the first call has an effect.
+pub fn set_configured_author(name: String) {
+    let _ = CONFIGURED_AUTHOR.set(name);
+}
+
+/// Name recorded as the author of new journal entries.
+/// `GRIT_AUTHOR` wins over `user.name`, which wins over the login name.
+pub fn current_author() -> Option<String> {
+    let from_env = |var: &str| std::env::var(var).ok().filter(|n| !n.trim().is_empty());
+
+    from_env("GRIT_AUTHOR")
+        .or_else(|| CONFIGURED_AUTHOR.get().cloned())
+        .or_else(|| from_env("USER"))
+        .or_else(|| from_env("USERNAME"))
 }
 
 impl JournalEntry {
@@ -38,6 +74,7 @@ impl JournalEntry {
             removed,
             moved,
             message: None,
+            author: current_author(),
         }
     }
 
@@ -57,6 +94,7 @@ impl JournalEntry {
             removed,
             moved,
             message: Some(message),
+            author: current_author(),
         }
     }
 
@@ -96,6 +134,26 @@ impl JournalEntry {
             .collect()
     }
 
+    /// Append `entry` to the playlist's journal and, with the git storage
+    /// backend, commit it to the playlist's repository.
+    pub fn record(grit_dir: &Path, playlist_id: &str, entry: &JournalEntry) -> anyhow::Result<()> {
+        Self::append(&Self::journal_path(grit_dir, playlist_id), entry)?;
+        if super::git_store::enabled(grit_dir, playlist_id)? {
+            super::git_store::sync(grit_dir, playlist_id)?;
+        }
+        Ok(())
+    }
+
+    /// The playlist's history, oldest first, read from the configured
+    /// storage backend.
+    pub fn history(grit_dir: &Path, playlist_id: &str) -> anyhow::Result<Vec<JournalEntry>> {
+        if super::git_store::enabled(grit_dir, playlist_id)? {
+            super::git_store::sync(grit_dir, playlist_id)?;
+            return super::git_store::read_log(grit_dir, playlist_id);
+        }
+        Self::read_all(&Self::journal_path(grit_dir, playlist_id))
+    }
+
     pub fn journal_path(grit_dir: &Path, playlist_id: &str) -> std::path::PathBuf {
         grit_dir
             .join("playlists")
@@ -104,6 +162,141 @@ impl JournalEntry {
     }
 }
 
+/// Who added a track and when, as far as the journal knows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackOrigin {
+    pub author: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The journal entry that last brought each current track into the
+/// playlist. Entries whose snapshot is missing are skipped.
+pub fn track_origins(
+    grit_dir: &Path,
+    playlist_id: &str,
+) -> anyhow::Result<HashMap<String, TrackOrigin>> {
+    let entries = JournalEntry::read_all(&JournalEntry::journal_path(grit_dir, playlist_id))?;
+    let mut origins: HashMap<String, TrackOrigin> = HashMap::new();
+
+    for entry in entries {
+        let std::result::Result::Ok(snap) =
+            super::snapshot::load_by_hash(&entry.snapshot_hash, grit_dir, playlist_id)
+        else {
+            continue;
+        };
+
+        origins.retain(|id, _| snap.tracks.iter().any(|t| &t.id == id));
+        for track in &snap.tracks {
+            origins
+                .entry(track.id.clone())
+                .or_insert_with(|| TrackOrigin {
+                    author: entry.author.clone(),
+                    timestamp: entry.timestamp,
+                });
+        }
+    }
+
+    Ok(origins)
+}
+
+/// Criteria used by `grit log` to select journal entries.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub author: Option<String>,
+    pub grep: Option<String>,
+    pub operations: Vec<Operation>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        if self.since.is_some_and(|since| entry.timestamp < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| entry.timestamp > until) {
+            return false;
+        }
+        if !self.operations.is_empty() && !self.operations.contains(&entry.operation) {
+            return false;
+        }
+        if let Some(author) = &self.author {
+            let author = author.to_lowercase();
+            let matched = entry
+                .author
+                .as_ref()
+                .is_some_and(|a| a.to_lowercase().contains(&author));
+            if !matched {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.grep {
+            let pattern = pattern.to_lowercase();
+            let matched = entry
+                .message
+                .as_ref()
+                .is_some_and(|m| m.to_lowercase().contains(&pattern));
+            if !matched {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Parse a date given to `--since`/`--until`.
+///
+/// Accepts RFC 3339 timestamps, `YYYY-MM-DD`, `YYYY-MM-DD HH:MM:SS` (UTC)
+/// and relative forms such as `3 days ago` or `2 weeks ago`.
+pub fn parse_date(input: &str) -> anyhow::Result<DateTime<Utc>> {
+    let input = input.trim();
+
+    if let std::result::Result::Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let std::result::Result::Ok(dt) = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S") {
+        return Ok(Utc.from_utc_datetime(&dt));
+    }
+    if let std::result::Result::Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let dt = date.and_hms_opt(0, 0, 0).context("Invalid date")?;
+        return Ok(Utc.from_utc_datetime(&dt));
+    }
+
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    if let [amount, unit, "ago"] = parts.as_slice() {
+        let amount: i64 = amount
+            .parse()
+            .with_context(|| format!("Invalid amount in date '{}'", input))?;
+        let span = match unit.trim_end_matches('s') {
+            "minute" => Duration::minutes(amount),
+            "hour" => Duration::hours(amount),
+            "day" => Duration::days(amount),
+            "week" => Duration::weeks(amount),
+            "month" => Duration::days(amount * 30),
+            "year" => Duration::days(amount * 365),
+            _ => anyhow::bail!("Unknown time unit '{}' in date '{}'", unit, input),
+        };
+        return Ok(Utc::now() - span);
+    }
+
+    anyhow::bail!(
+        "Could not parse date '{}' (use YYYY-MM-DD, RFC 3339 or '<n> days ago')",
+        input
+    )
+}
+
+/// Parse a date given to `--until`: like `parse_date`, except that a bare
+/// `YYYY-MM-DD` means the end of that day, so the day itself is included.
+pub fn parse_until(input: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let std::result::Result::Ok(date) = NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d") {
+        let dt = date
+            .and_hms_nano_opt(23, 59, 59, 999_999_999)
+            .context("Invalid date")?;
+        return Ok(Utc.from_utc_datetime(&dt));
+    }
+    parse_date(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,4 +328,97 @@ mod tests {
         let entries = JournalEntry::read_all(&path).unwrap();
         assert!(entries.is_empty());
     }
+
+    fn entry_at(op: Operation, date: &str, message: Option<&str>) -> JournalEntry {
+        let mut entry = JournalEntry::new(op, "abc123".to_string(), 0, 0, 0);
+        entry.timestamp = parse_date(date).unwrap();
+        entry.message = message.map(str::to_string);
+        entry.author = Some("Alice".to_string());
+        entry
+    }
+
+    #[test]
+    fn test_filter_by_date_range() {
+        let filter = LogFilter {
+            since: Some(parse_date("2024-02-01").unwrap()),
+            until: Some(parse_until("2024-03-01").unwrap()),
+            ..Default::default()
+        };
+
+        assert!(!filter.matches(&entry_at(Operation::Commit, "2024-01-15", None)));
+        assert!(filter.matches(&entry_at(Operation::Commit, "2024-02-15", None)));
+        assert!(filter.matches(&entry_at(Operation::Commit, "2024-03-01T18:30:00Z", None)));
+        assert!(!filter.matches(&entry_at(Operation::Commit, "2024-03-02", None)));
+        assert!(!filter.matches(&entry_at(Operation::Commit, "2024-03-15", None)));
+    }
+
+    #[test]
+    fn test_filter_by_operation_author_and_grep() {
+        let filter = LogFilter {
+            operations: vec![Operation::Commit],
+            author: Some("ali".to_string()),
+            grep: Some("CHILL".to_string()),
+            ..Default::default()
+        };
+
+        let commit = entry_at(Operation::Commit, "2024-01-01", Some("add chill vibes"));
+        let push = entry_at(Operation::Push, "2024-01-01", Some("add chill vibes"));
+        let other = entry_at(Operation::Commit, "2024-01-01", Some("remove metal"));
+
+        assert!(filter.matches(&commit));
+        assert!(!filter.matches(&push));
+        assert!(!filter.matches(&other));
+    }
+
+    #[test]
+    fn test_parse_date_formats() {
+        assert!(parse_date("2024-05-01T10:00:00Z").is_ok());
+        assert!(parse_date("2024-05-01 10:00:00").is_ok());
+        assert!(parse_date("2024-05-01").is_ok());
+
+        let week_ago = parse_date("1 week ago").unwrap();
+        assert!(week_ago < Utc::now() - Duration::days(6));
+        assert!(parse_date("yesterday-ish").is_err());
+    }
+
+    #[test]
+    fn test_track_origins_follow_removal_and_readd() {
+        use crate::provider::{PlaylistSnapshot, ProviderKind};
+        use crate::state::snapshot;
+
+        let temp = TempDir::new().unwrap();
+        let grit_dir = temp.path();
+        let journal = JournalEntry::journal_path(grit_dir, "pl");
+
+        let states = [
+            ("h1", vec!["a", "b"], "Alice", "2024-01-01"),
+            ("h2", vec!["a"], "Bob", "2024-02-01"),
+            ("h3", vec!["a", "b"], "Carol", "2024-03-01"),
+        ];
+
+        for (hash, ids, author, date) in states {
+            let snap = PlaylistSnapshot {
+                id: "pl".to_string(),
+                ..PlaylistSnapshot::fixture(ProviderKind::Spotify, &ids)
+            };
+            snapshot::save_by_hash(&snap, hash, grit_dir, "pl").unwrap();
+
+            let mut entry = JournalEntry::new(Operation::Commit, hash.to_string(), 0, 0, 0);
+            entry.author = Some(author.to_string());
+            entry.timestamp = parse_date(date).unwrap();
+            JournalEntry::append(&journal, &entry).unwrap();
+        }
+
+        let origins = track_origins(grit_dir, "pl").unwrap();
+        assert_eq!(origins["a"].author.as_deref(), Some("Alice"));
+        assert_eq!(origins["b"].author.as_deref(), Some("Carol"));
+        assert_eq!(origins["b"].timestamp, parse_date("2024-03-01").unwrap());
+    }
+
+    #[test]
+    fn test_entry_without_author_parses() {
+        let line = r#"{"timestamp":"2024-01-01T00:00:00Z","operation":"Init","snapshot_hash":"abc","added":1,"removed":0,"moved":0,"message":null}"#;
+        let entry: JournalEntry = serde_json::from_str(line).unwrap();
+        assert!(entry.author.is_none());
+    }
 }
@@ -1,10 +1,16 @@
+pub mod bundle;
+pub mod config;
 pub mod credentials;
 pub mod diff;
+pub mod git_store;
 pub mod journal;
+pub mod push_plan;
+pub mod quota;
+pub mod remotes;
 pub mod snapshot;
 pub mod staging;
 pub mod working_playlist;
 
-pub use diff::{apply_patch, diff};
+pub use diff::{apply_patch, diff, diff_occurrences};
 pub use journal::{JournalEntry, Operation};
 pub use staging::*;
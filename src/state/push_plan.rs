@@ -0,0 +1,278 @@
+//! Push plans: the steps of a push, written to disk before the first request
+//! and checkpointed after each step, so an interrupted push can be resumed
+//! with `grit push --continue` or rolled back with `grit push --abort`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::provider::{PlaylistSnapshot, Track};
+use crate::state::diff::occurrence_changes;
+
+/// Tracks removed or added per step, the most Spotify takes in one request.
+const BATCH: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushPlan {
+    /// Remote name, `origin` or a mirror
+    pub remote: String,
+    pub created_at: DateTime<Utc>,
+    /// Hash of the local snapshot being pushed, for the journal entry
+    pub local_hash: String,
+    /// The remote playlist as fetched before the push
+    pub before: PlaylistSnapshot,
+    pub steps: Vec<PushStep>,
+    /// Steps known to be applied
+    pub completed: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub moved: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushStep {
+    pub description: String,
+    /// Remote tracks once the step is applied
+    pub tracks: Vec<Track>,
+}
+
+impl PushPlan {
+    /// Plan the steps from the remote's current state `before` to `desired`:
+    /// removals, then additions appended at the end, in batches, then a
+    /// single reorder.
+    pub fn new(
+        remote: &str,
+        local_hash: &str,
+        before: &PlaylistSnapshot,
+        desired: &PlaylistSnapshot,
+        (added, removed, moved): (usize, usize, usize),
+    ) -> Self {
+        let before_ids: Vec<&str> = before.tracks.iter().map(|t| t.id.as_str()).collect();
+        let desired_ids: Vec<&str> = desired.tracks.iter().map(|t| t.id.as_str()).collect();
+        // Per occurrence, so extra copies of a track get their own steps
+        let (removals, additions) = occurrence_changes(&before_ids, &desired_ids);
+
+        let mut steps = Vec::new();
+        let mut tracks = before.tracks.clone();
+
+        let mut gone = vec![false; before.tracks.len()];
+        for batch in removals.chunks(BATCH) {
+            for &i in batch {
+                gone[i] = true;
+            }
+            tracks = before
+                .tracks
+                .iter()
+                .zip(&gone)
+                .filter(|(_, &gone)| !gone)
+                .map(|(t, _)| t.clone())
+                .collect();
+            steps.push(PushStep {
+                description: format!("Remove {} track(s)", batch.len()),
+                tracks: tracks.clone(),
+            });
+        }
+
+        for batch in additions.chunks(BATCH) {
+            tracks.extend(batch.iter().map(|&i| desired.tracks[i].clone()));
+            steps.push(PushStep {
+                description: format!("Add {} track(s)", batch.len()),
+                tracks: tracks.clone(),
+            });
+        }
+
+        if !same_order(&tracks, &desired.tracks) {
+            steps.push(PushStep {
+                description: "Reorder tracks".to_string(),
+                tracks: desired.tracks.clone(),
+            });
+        }
+
+        Self {
+            remote: remote.to_string(),
+            created_at: Utc::now(),
+            local_hash: local_hash.to_string(),
+            before: before.clone(),
+            steps,
+            completed: 0,
+            added,
+            removed,
+            moved,
+        }
+    }
+
+    pub fn path(grit_dir: &Path, playlist_id: &str) -> PathBuf {
+        grit_dir
+            .join("playlists")
+            .join(playlist_id)
+            .join("push-plan.json")
+    }
+
+    /// The interrupted push of a playlist, if any.
+    pub fn load(grit_dir: &Path, playlist_id: &str) -> Result<Option<Self>> {
+        let path = Self::path(grit_dir, playlist_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    pub fn save(&self, grit_dir: &Path, playlist_id: &str) -> Result<()> {
+        let path = Self::path(grit_dir, playlist_id);
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize push plan")?;
+        fs::write(&path, json).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    pub fn remove(grit_dir: &Path, playlist_id: &str) -> Result<()> {
+        let path = Self::path(grit_dir, playlist_id);
+        if path.exists() {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+        }
+        Ok(())
+    }
+
+    /// The remote playlist as it should be after `steps` steps.
+    pub fn target(&self, steps: usize) -> PlaylistSnapshot {
+        let mut target = self.before.clone();
+        if let Some(step) = steps.checked_sub(1).and_then(|i| self.steps.get(i)) {
+            target.tracks = step.tracks.clone();
+        }
+        target
+    }
+
+    /// Number of steps `remote` reflects: the last checkpoint, or a later
+    /// step whose result was applied before it could be recorded.
+    pub fn steps_applied(&self, remote: &PlaylistSnapshot) -> usize {
+        (self.completed..=self.steps.len())
+            .rev()
+            .find(|&n| self.matches(n, remote))
+            .unwrap_or(self.completed)
+    }
+
+    /// Whether `remote` is in the state expected after `steps` steps. Order
+    /// only counts for sources that can be reordered.
+    pub fn matches(&self, steps: usize, remote: &PlaylistSnapshot) -> bool {
+        let target = self.target(steps);
+        if self.before.source.can_reorder() {
+            same_order(&target.tracks, &remote.tracks)
+        } else {
+            let mut expected: Vec<&str> = target.tracks.iter().map(|t| t.id.as_str()).collect();
+            let mut actual: Vec<&str> = remote.tracks.iter().map(|t| t.id.as_str()).collect();
+            expected.sort_unstable();
+            actual.sort_unstable();
+            expected == actual
+        }
+    }
+}
+
+fn same_order(a: &[Track], b: &[Track]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.id == b.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderKind;
+
+    fn ids(snapshot: &PlaylistSnapshot) -> Vec<&str> {
+        snapshot.tracks.iter().map(|t| t.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_plan_steps_and_resume_point() {
+        let before = PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b", "c"]);
+        let plan = PushPlan::new(
+            "origin",
+            "hash",
+            &before,
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["d", "c", "a"]),
+            (1, 1, 2),
+        );
+
+        let steps: Vec<_> = plan.steps.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(
+            steps,
+            vec!["Remove 1 track(s)", "Add 1 track(s)", "Reorder tracks"]
+        );
+        assert_eq!(ids(&plan.target(1)), vec!["a", "c"]);
+        assert_eq!(ids(&plan.target(2)), vec!["a", "c", "d"]);
+        assert_eq!(ids(&plan.target(3)), vec!["d", "c", "a"]);
+
+        assert_eq!(plan.steps_applied(&before), 0);
+        // The addition went through but the checkpoint wasn't written
+        assert_eq!(
+            plan.steps_applied(&PlaylistSnapshot::fixture(
+                ProviderKind::Spotify,
+                &["a", "c", "d"]
+            )),
+            2
+        );
+        // Halfway through a step: resume at the last checkpoint
+        assert_eq!(
+            plan.steps_applied(&PlaylistSnapshot::fixture(
+                ProviderKind::Spotify,
+                &["c", "a"]
+            )),
+            0
+        );
+    }
+
+    #[test]
+    fn test_plan_counts_duplicates_per_occurrence() {
+        let plan = PushPlan::new(
+            "origin",
+            "hash",
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b"]),
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b", "a"]),
+            (0, 0, 0),
+        );
+        let steps: Vec<_> = plan.steps.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(steps, vec!["Add 1 track(s)"]);
+        assert_eq!(ids(&plan.target(1)), vec!["a", "b", "a"]);
+
+        // The later copy goes
+        let plan = PushPlan::new(
+            "origin",
+            "hash",
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a", "b", "a", "c"]),
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["c", "a", "b"]),
+            (0, 0, 0),
+        );
+        let steps: Vec<_> = plan.steps.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(steps, vec!["Remove 1 track(s)", "Reorder tracks"]);
+        assert_eq!(ids(&plan.target(1)), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_plan_save_load_remove() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("playlists/pl")).unwrap();
+
+        assert!(PushPlan::load(dir.path(), "pl").unwrap().is_none());
+        let plan = PushPlan::new(
+            "yt",
+            "h",
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["a"]),
+            &PlaylistSnapshot::fixture(ProviderKind::Spotify, &["b"]),
+            (1, 1, 0),
+        );
+        plan.save(dir.path(), "pl").unwrap();
+
+        let loaded = PushPlan::load(dir.path(), "pl").unwrap().unwrap();
+        assert_eq!(loaded.remote, "yt");
+        assert_eq!(loaded.steps.len(), 2);
+
+        PushPlan::remove(dir.path(), "pl").unwrap();
+        assert!(PushPlan::load(dir.path(), "pl").unwrap().is_none());
+    }
+}
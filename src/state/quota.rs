@@ -0,0 +1,178 @@
+//! Ledger of the YouTube Data API quota spent per day. Google counts quota
+//! in units per project and resets it at midnight Pacific time; grit keeps
+//! its own tally so a push can be checked against the budget before it
+//! starts instead of failing halfway.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{Context, Result};
+use chrono::{FixedOffset, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::state::config::Config;
+use crate::utils::http::Meter;
+
+/// Units of a read: `list` calls on playlists, items and videos
+pub const READ: u64 = 1;
+/// Units of a `playlistItems` insert, update or delete, a playlist insert
+/// or a video rating
+pub const WRITE: u64 = 50;
+/// Units of a search
+pub const SEARCH: u64 = 100;
+
+/// The daily quota of a new Google Cloud project
+pub const DEFAULT_BUDGET: u64 = 10_000;
+
+/// Days of history kept in the ledger
+const KEEP_DAYS: i64 = 30;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QuotaLedger {
+    /// Units spent per quota day
+    pub days: BTreeMap<NaiveDate, u64>,
+}
+
+impl QuotaLedger {
+    pub fn path(grit_dir: &Path) -> PathBuf {
+        grit_dir.join("youtube-quota.json")
+    }
+
+    pub fn load(grit_dir: &Path) -> Result<Self> {
+        let path = Self::path(grit_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    pub fn save(&self, grit_dir: &Path) -> Result<()> {
+        let path = Self::path(grit_dir);
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize quota ledger")?;
+        fs::write(&path, json).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Units spent today.
+    pub fn used_today(&self) -> u64 {
+        self.days.get(&today()).copied().unwrap_or(0)
+    }
+
+    /// Add `units` to today's total, dropping days older than `KEEP_DAYS`.
+    pub fn add(&mut self, units: u64) {
+        let today = today();
+        *self.days.entry(today).or_default() += units;
+        let oldest = today - chrono::Duration::days(KEEP_DAYS);
+        self.days.retain(|&day, _| day >= oldest);
+    }
+}
+
+/// Record `units` spent now and return today's total.
+pub fn record(grit_dir: &Path, units: u64) -> Result<u64> {
+    let mut ledger = QuotaLedger::load(grit_dir)?;
+    ledger.add(units);
+    ledger.save(grit_dir)?;
+    Ok(ledger.used_today())
+}
+
+/// A meter that records each request's units in the ledger of `grit_dir`
+/// and warns once today's total goes over `budget` (0 for no limit).
+pub fn meter(grit_dir: PathBuf, budget: Arc<AtomicU64>) -> Meter {
+    Arc::new(move |units| {
+        let budget = budget.load(Ordering::Relaxed);
+        match record(&grit_dir, units) {
+            Ok(used) if budget > 0 && used > budget && used - units <= budget => eprintln!(
+                "Warning: {} YouTube quota units used today, over the budget of {} (youtube.quota_budget)",
+                used, budget
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("Warning: failed to record YouTube quota use: {:#}", e),
+        }
+    })
+}
+
+/// The current quota day. Pacific standard time is used all year, so during
+/// daylight saving the day turns over an hour after Google's.
+pub fn today() -> NaiveDate {
+    let pacific = FixedOffset::west_opt(8 * 3600).expect("valid offset");
+    Utc::now().with_timezone(&pacific).date_naive()
+}
+
+/// Daily units grit may spend (`youtube.quota_budget`); 0 for no limit.
+pub fn budget(config: &Config) -> Result<u64> {
+    Ok(config
+        .get_u64("youtube.quota_budget")?
+        .unwrap_or(DEFAULT_BUDGET))
+}
+
+/// Print the estimated `cost` of `what` (e.g. "This push") and stop before
+/// it would take today's usage over `youtube.quota_budget`. A dry run only
+/// warns.
+pub fn check_budget(grit_dir: &Path, what: &str, cost: u64, dry_run: bool) -> Result<()> {
+    let used = QuotaLedger::load(grit_dir)?.used_today();
+    let budget = budget(&Config::load(grit_dir, None)?)?;
+    if budget == 0 {
+        println!(
+            "Estimated YouTube quota: {} units ({} used today)",
+            cost, used
+        );
+        return Ok(());
+    }
+    println!(
+        "Estimated YouTube quota: {} units ({} of {} used today)",
+        cost, used, budget
+    );
+
+    if used + cost > budget {
+        let message = format!(
+            "{} needs about {} YouTube quota units but {} of today's budget of {} are left",
+            what,
+            cost,
+            budget.saturating_sub(used),
+            budget
+        );
+        if dry_run {
+            println!("Warning: {}", message);
+        } else {
+            anyhow::bail!(
+                "{}. Try again after the quota resets at midnight Pacific time, or raise youtube.quota_budget.",
+                message
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_adds_to_today() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(QuotaLedger::load(dir.path()).unwrap().used_today(), 0);
+
+        assert_eq!(record(dir.path(), WRITE).unwrap(), 50);
+        assert_eq!(record(dir.path(), READ).unwrap(), 51);
+
+        let mut ledger = QuotaLedger::load(dir.path()).unwrap();
+        let yesterday = today() - chrono::Duration::days(1);
+        let old = today() - chrono::Duration::days(KEEP_DAYS + 1);
+        ledger.days.insert(yesterday, 9_000);
+        ledger.days.insert(old, 9_000);
+        ledger.add(SEARCH);
+        assert_eq!(ledger.used_today(), 151);
+        assert!(ledger.days.contains_key(&yesterday));
+        assert!(!ledger.days.contains_key(&old));
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::provider::{PlaylistSnapshot, ProviderKind, Track};
+
+/// Name of the implicit remote a playlist was initialized from.
+pub const ORIGIN: &str = "origin";
+
+/// Snapshot metadata flag for playlists that don't exist on their provider
+/// yet, such as ones imported from an account export without an ID.
+pub const UNPUBLISHED: &str = "unpublished";
+
+/// Whether the playlist's ID refers to a playlist on its provider.
+pub fn has_origin(snapshot: &PlaylistSnapshot) -> bool {
+    !snapshot
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get(UNPUBLISHED))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Metadata flagging a playlist as not on its provider yet.
+pub fn unpublished_metadata() -> serde_json::Value {
+    serde_json::json!({ UNPUBLISHED: true })
+}
+
+/// Link the snapshot to the playlist `remote_id` just created for it.
+pub fn mark_published(snapshot: &mut PlaylistSnapshot, remote_id: &str) {
+    snapshot.id = remote_id.to_string();
+    if let Some(metadata) = snapshot.metadata.as_mut().and_then(|m| m.as_object_mut()) {
+        metadata.remove(UNPUBLISHED);
+        if metadata.is_empty() {
+            snapshot.metadata = None;
+        }
+    }
+}
+
+/// Fail with a hint when the playlist has no origin to pull from or push to.
+pub fn require_origin(snapshot: &PlaylistSnapshot) -> Result<()> {
+    if !has_origin(snapshot) {
+        anyhow::bail!(
+            "Playlist '{}' isn't on {} yet. Create it there with 'grit push'.",
+            snapshot.id,
+            snapshot.provider
+        );
+    }
+    Ok(())
+}
+
+/// A mirror of a tracked playlist on another (or the same) provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Remote {
+    pub name: String,
+    pub provider: ProviderKind,
+    pub playlist_id: String,
+}
+
+pub fn remotes_path(grit_dir: &Path, playlist_id: &str) -> PathBuf {
+    grit_dir
+        .join("playlists")
+        .join(playlist_id)
+        .join("remotes.json")
+}
+
+pub fn load(grit_dir: &Path, playlist_id: &str) -> Result<Vec<Remote>> {
+    let path = remotes_path(grit_dir, playlist_id);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+pub fn save(grit_dir: &Path, playlist_id: &str, remotes: &[Remote]) -> Result<()> {
+    let path = remotes_path(grit_dir, playlist_id);
+    let json = serde_json::to_string_pretty(remotes).context("Failed to serialize remotes")?;
+    fs::write(&path, json).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// The counterpart of `track` on `provider`, as recorded in
+/// `metadata.mirrors.<provider>` by an earlier mirror push.
+pub fn mirrored_track(track: &Track, provider: &ProviderKind) -> Option<Track> {
+    if &track.provider == provider {
+        return Some(track.clone());
+    }
+
+    let mirror = track
+        .metadata
+        .as_ref()?
+        .get("mirrors")?
+        .get(provider.to_string())?;
+    serde_json::from_value(mirror.clone()).ok()
+}
+
+/// Record `mirror` as the counterpart of `track` on `mirror.provider`.
+pub fn record_mirror(track: &mut Track, mirror: &Track) -> Result<()> {
+    let metadata = track
+        .metadata
+        .get_or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .context("Track metadata is not an object")?;
+
+    let mirrors = metadata
+        .entry("mirrors")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .context("Track mirrors metadata is not an object")?;
+
+    let mut stored = mirror.clone();
+    stored.metadata = None;
+    mirrors.insert(
+        mirror.provider.to_string(),
+        serde_json::to_value(stored).context("Failed to serialize mirrored track")?,
+    );
+    Ok(())
+}
+
+/// Carry metadata that only exists locally (such as mirror mappings) over to
+/// a freshly fetched remote track list, so a pull doesn't discard it.
+pub fn preserve_local_metadata(local: &[Track], remote: &mut [Track]) {
+    for track in remote.iter_mut().filter(|t| t.metadata.is_none()) {
+        if let Some(existing) = local.iter().find(|t| t.id == track.id) {
+            track.metadata = existing.metadata.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: &str, provider: ProviderKind) -> Track {
+        Track {
+            id: id.to_string(),
+            name: "Song".to_string(),
+            artists: vec!["Artist".to_string()],
+            duration_ms: 200_000,
+            provider,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_record_and_lookup_mirror() {
+        let mut spotify = track("sp1", ProviderKind::Spotify);
+        let youtube = track("yt1", ProviderKind::Youtube);
+
+        assert!(mirrored_track(&spotify, &ProviderKind::Youtube).is_none());
+        assert_eq!(
+            mirrored_track(&spotify, &ProviderKind::Spotify).unwrap().id,
+            "sp1"
+        );
+
+        record_mirror(&mut spotify, &youtube).unwrap();
+        let found = mirrored_track(&spotify, &ProviderKind::Youtube).unwrap();
+        assert_eq!(found.id, "yt1");
+        assert_eq!(found.provider, ProviderKind::Youtube);
+    }
+
+    #[test]
+    fn test_mark_published() {
+        let mut snap = PlaylistSnapshot {
+            id: "new-mix".to_string(),
+            name: "Mix".to_string(),
+            description: None,
+            tracks: vec![],
+            provider: ProviderKind::Spotify,
+            source: Default::default(),
+            snapshot_hash: String::new(),
+            metadata: Some(unpublished_metadata()),
+        };
+        assert!(!has_origin(&snap));
+
+        mark_published(&mut snap, "pl1");
+        assert!(has_origin(&snap));
+        assert_eq!(snap.id, "pl1");
+        assert!(snap.metadata.is_none());
+    }
+
+    #[test]
+    fn test_preserve_local_metadata() {
+        let mut local = track("sp1", ProviderKind::Spotify);
+        record_mirror(&mut local, &track("yt1", ProviderKind::Youtube)).unwrap();
+
+        let mut remote = vec![
+            track("sp1", ProviderKind::Spotify),
+            track("sp2", ProviderKind::Spotify),
+        ];
+        preserve_local_metadata(&[local], &mut remote);
+
+        assert!(mirrored_track(&remote[0], &ProviderKind::Youtube).is_some());
+        assert!(remote[1].metadata.is_none());
+    }
+}
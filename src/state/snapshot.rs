@@ -3,7 +3,7 @@ use std::{fs, path::Path};
 use anyhow::{Context, Ok};
 use sha2::{Digest, Sha256};
 
-use crate::provider::PlaylistSnapshot;
+use crate::provider::{PlaylistSnapshot, ProviderKind, SourceKind};
 
 pub fn compute_hash(snapshot: &PlaylistSnapshot) -> anyhow::Result<String> {
     let yaml = serde_yaml::to_string(snapshot)
@@ -37,7 +37,23 @@ pub fn load(path: &Path) -> anyhow::Result<PlaylistSnapshot> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read snapshot from {:?}", path))?;
 
-    serde_yaml::from_str(&content).with_context(|| "Failed to parse snapshot YAML")
+    let mut snapshot: PlaylistSnapshot =
+        serde_yaml::from_str(&content).with_context(|| "Failed to parse snapshot YAML")?;
+    if is_unmarked_album(&snapshot) {
+        snapshot.source = SourceKind::Album;
+    }
+    Ok(snapshot)
+}
+
+/// Spotify albums tracked before snapshots recorded their source load as
+/// playlists; they are recognized by the description `fetch_album` gives them.
+fn is_unmarked_album(snapshot: &PlaylistSnapshot) -> bool {
+    snapshot.source == SourceKind::Playlist
+        && snapshot.provider == ProviderKind::Spotify
+        && snapshot
+            .description
+            .as_deref()
+            .is_some_and(|d| d.starts_with("Album by "))
 }
 
 pub fn snapshot_path(grit_dir: &Path, playlist_id: &str) -> std::path::PathBuf {
@@ -70,6 +86,24 @@ pub fn save_by_hash(
     save(snapshot, &path)
 }
 
+/// Move everything stored for a playlist (snapshot, history, staged
+/// changes, remotes, settings) from `from` to the ID `to`.
+pub fn rename_playlist(grit_dir: &Path, from: &str, to: &str) -> anyhow::Result<()> {
+    let playlists = grit_dir.join("playlists");
+    if playlists.join(to).exists() {
+        anyhow::bail!("Playlist {} is already tracked", to);
+    }
+    fs::rename(playlists.join(from), playlists.join(to))
+        .with_context(|| format!("Failed to rename playlist {} to {}", from, to))?;
+
+    let repo = super::git_store::repo_dir(grit_dir, from);
+    if repo.exists() {
+        fs::rename(&repo, super::git_store::repo_dir(grit_dir, to))
+            .with_context(|| format!("Failed to rename {:?}", repo))?;
+    }
+    Ok(())
+}
+
 /// Load a snapshot by its hash
 pub fn load_by_hash(
     hash: &str,
@@ -95,7 +129,7 @@ pub fn load_by_hash(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::provider::{ProviderKind, Track};
+    use crate::provider::Track;
     use tempfile::TempDir;
 
     fn sample_snapshot() -> PlaylistSnapshot {
@@ -112,6 +146,7 @@ mod tests {
                 metadata: None,
             }],
             provider: ProviderKind::Spotify,
+            source: SourceKind::Playlist,
             snapshot_hash: String::new(),
             metadata: None,
         }
@@ -139,4 +174,17 @@ mod tests {
         assert_eq!(loaded.name, snapshot.name);
         assert_eq!(loaded.tracks.len(), 1);
     }
+
+    #[test]
+    fn test_load_marks_albums_saved_without_a_source() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("playlist.yaml");
+
+        let mut snapshot = sample_snapshot();
+        snapshot.description = Some("Album by Artist A".to_string());
+        save(&snapshot, &path).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("source:"));
+
+        assert_eq!(load(&path).unwrap().source, SourceKind::Album);
+    }
 }
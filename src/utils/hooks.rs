@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::provider::DiffPatch;
+
+/// Scripts in `.grit/hooks/` that run around commit, push and pull.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PreCommit,
+    CommitMsg,
+    PrePush,
+    PostPull,
+}
+
+impl Hook {
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::PreCommit => "pre-commit",
+            Hook::CommitMsg => "commit-msg",
+            Hook::PrePush => "pre-push",
+            Hook::PostPull => "post-pull",
+        }
+    }
+
+    /// Pre-hooks can veto the operation; post-hooks only report failures.
+    fn can_abort(self) -> bool {
+        !matches!(self, Hook::PostPull)
+    }
+}
+
+/// JSON document written to the hook's stdin.
+#[derive(Serialize)]
+struct HookPayload<'a> {
+    hook: &'static str,
+    playlist_id: &'a str,
+    patch: &'a DiffPatch,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+}
+
+pub fn hook_path(grit_dir: &Path, hook: Hook) -> PathBuf {
+    grit_dir.join("hooks").join(hook.name())
+}
+
+/// Run `hook` if it is installed.
+///
+/// The hook receives the playlist ID, the relevant patch and (for
+/// `commit-msg`) the commit message as JSON on stdin. A nonzero exit from a
+/// pre-hook aborts the operation with an error.
+pub fn run(
+    grit_dir: &Path,
+    hook: Hook,
+    playlist_id: &str,
+    patch: &DiffPatch,
+    message: Option<&str>,
+) -> Result<()> {
+    let path = hook_path(grit_dir, hook);
+    if !is_executable(&path) {
+        return Ok(());
+    }
+
+    let payload = HookPayload {
+        hook: hook.name(),
+        playlist_id,
+        patch,
+        message,
+    };
+    let json = serde_json::to_vec(&payload).context("Failed to serialize hook payload")?;
+
+    let mut child = Command::new(&path)
+        .env("GRIT_HOOK", hook.name())
+        .env("GRIT_PLAYLIST_ID", playlist_id)
+        .env("GRIT_DIR", grit_dir)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {} hook {:?}", hook.name(), path))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook may exit without reading its input; that's not an error
+        let _ = stdin.write_all(&json);
+    }
+
+    let status = child
+        .wait()
+        .with_context(|| format!("Failed to wait for {} hook", hook.name()))?;
+
+    if !status.success() {
+        if hook.can_abort() {
+            anyhow::bail!("{} hook rejected the operation ({})", hook.name(), status);
+        }
+        eprintln!("Warning: {} hook failed ({})", hook.name(), status);
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn install(grit_dir: &Path, hook: Hook, script: &str) {
+        let path = hook_path(grit_dir, hook);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_missing_hook_is_ignored() {
+        let temp = TempDir::new().unwrap();
+        run(
+            temp.path(),
+            Hook::PreCommit,
+            "p1",
+            &DiffPatch::default(),
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pre_hook_failure_aborts() {
+        let temp = TempDir::new().unwrap();
+        install(temp.path(), Hook::PrePush, "#!/bin/sh\nexit 1\n");
+
+        let result = run(
+            temp.path(),
+            Hook::PrePush,
+            "p1",
+            &DiffPatch::default(),
+            None,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_post_hook_failure_is_not_fatal() {
+        let temp = TempDir::new().unwrap();
+        install(temp.path(), Hook::PostPull, "#!/bin/sh\nexit 1\n");
+
+        run(
+            temp.path(),
+            Hook::PostPull,
+            "p1",
+            &DiffPatch::default(),
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_hook_receives_payload() {
+        let temp = TempDir::new().unwrap();
+        let out = temp.path().join("payload.json");
+        install(
+            temp.path(),
+            Hook::CommitMsg,
+            &format!("#!/bin/sh\ncat > {:?}\n", out),
+        );
+
+        run(
+            temp.path(),
+            Hook::CommitMsg,
+            "p1",
+            &DiffPatch::default(),
+            Some("add chill vibes"),
+        )
+        .unwrap();
+
+        let payload: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(payload["hook"], "commit-msg");
+        assert_eq!(payload["playlist_id"], "p1");
+        assert_eq!(payload["message"], "add chill vibes");
+        assert!(payload["patch"]["changes"].as_array().unwrap().is_empty());
+    }
+}
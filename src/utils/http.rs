@@ -0,0 +1,503 @@
+//! HTTP client shared by the web API clients. Rate-limited and failed
+//! requests are retried with backoff, and a command can be capped at a
+//! number of requests so a runaway loop can't drain an API quota. In a dry
+//! run, requests that would change something are printed instead of sent.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, OnceLock,
+};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use rand::Rng;
+use reqwest::{header::HeaderMap, IntoUrl, Method, Response, StatusCode};
+use serde::Serialize;
+
+/// Body of the stand-in response to a request skipped by a dry run, with
+/// the fields the API clients read from mutation responses (including a
+/// successful Subsonic envelope).
+const DRY_RUN_BODY: &str =
+    r#"{"id":"dry-run","snapshot_id":"dry-run","subsonic-response":{"status":"ok"}}"#;
+
+/// Longest `Retry-After` grit waits for; longer ones fail the request.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Settings {
+    /// Retries of a request before its last response is returned
+    pub max_retries: u32,
+    /// Requests a command may send, retries included; 0 for no limit
+    pub max_requests: usize,
+    /// First backoff delay, doubled on every retry
+    pub base_delay: Duration,
+    /// Report retries on stderr
+    pub verbose: bool,
+    /// Print requests that change something instead of sending them
+    pub dry_run: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            max_requests: 0,
+            base_delay: Duration::from_millis(500),
+            verbose: false,
+            dry_run: false,
+        }
+    }
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+static SENT: OnceLock<Arc<AtomicUsize>> = OnceLock::new();
+
+/// Set the settings of every client created afterwards from the config,
+/// `--verbose` and `--dry-run`. Only the first call has an effect.
+pub fn configure(settings: Settings) {
+    let _ = SETTINGS.set(settings);
+}
+
+/// Called with the `cost` of every attempt that reached the server, for
+/// APIs that count quota per call.
+pub type Meter = Arc<dyn Fn(u64) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: reqwest::Client,
+    settings: Settings,
+    /// Requests sent, shared by every client of the command
+    sent: Arc<AtomicUsize>,
+    meter: Option<Meter>,
+}
+
+impl std::fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpClient")
+            .field("settings", &self.settings)
+            .field("metered", &self.meter.is_some())
+            .finish()
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        Self {
+            inner: reqwest::Client::new(),
+            settings: SETTINGS.get().copied().unwrap_or_default(),
+            sent: SENT.get_or_init(Arc::default).clone(),
+            meter: None,
+        }
+    }
+
+    /// Report the cost of each request to `meter`.
+    pub fn with_meter(mut self, meter: Meter) -> Self {
+        self.meter = Some(meter);
+        self
+    }
+
+    /// A client with its own settings and request count, for tests.
+    #[cfg(test)]
+    pub fn with_settings(settings: Settings) -> Self {
+        Self {
+            inner: reqwest::Client::new(),
+            settings,
+            sent: Arc::default(),
+            meter: None,
+        }
+    }
+
+    /// Whether requests that change something are only printed.
+    pub fn is_dry_run(&self) -> bool {
+        self.settings.dry_run
+    }
+
+    pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    pub fn put<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::PUT, url)
+    }
+
+    pub fn delete<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::DELETE, url)
+    }
+
+    fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
+        RequestBuilder {
+            client: self.clone(),
+            inner: self.inner.request(method, url),
+            read_only: false,
+            mutating: false,
+            idempotent: false,
+            cost: 0,
+        }
+    }
+
+    /// Send `request`, retrying it while the server asks to slow down
+    /// (429) or fails (5xx), or the connection can't be made.
+    ///
+    /// Only reads and requests marked `idempotent` are retried after a 5xx
+    /// or timeout, since the server may have acted on them; a 429 means it
+    /// didn't.
+    async fn execute(&self, request: reqwest::Request, flags: Flags) -> Result<Response> {
+        let changes = flags.mutating || request.method() != Method::GET;
+        if self.is_dry_run() && !flags.read_only && changes {
+            return Ok(dry_run_response(&request));
+        }
+
+        // GETs marked `mutating` may create something, so they need the flag too
+        let idempotent = flags.idempotent
+            || (!flags.mutating && matches!(*request.method(), Method::GET | Method::HEAD));
+
+        let mut attempt = 0;
+        loop {
+            let sent = self.sent.fetch_add(1, Ordering::SeqCst);
+            if self.settings.max_requests > 0 && sent >= self.settings.max_requests {
+                bail!(
+                    "Stopped after {} HTTP requests (http.max_requests)",
+                    self.settings.max_requests
+                );
+            }
+
+            // Streaming bodies can't be replayed, so those get one attempt
+            let Some(this_try) = request.try_clone() else {
+                let result = self.inner.execute(request).await;
+                self.charge(&result, flags.cost);
+                return Ok(result?);
+            };
+            let result = self.inner.execute(this_try).await;
+            self.charge(&result, flags.cost);
+
+            let (reason, delay) = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => (
+                    response.status().to_string(),
+                    retry_after(response.headers()),
+                ),
+                Ok(response) if response.status().is_server_error() && idempotent => (
+                    response.status().to_string(),
+                    retry_after(response.headers()),
+                ),
+                Err(e) if e.is_connect() || (e.is_timeout() && idempotent) => (e.to_string(), None),
+                _ => return Ok(result?),
+            };
+
+            let delay = delay.unwrap_or_else(|| backoff(self.settings.base_delay, attempt));
+            if attempt >= self.settings.max_retries || delay > MAX_RETRY_AFTER {
+                return Ok(result?);
+            }
+            attempt += 1;
+
+            if self.settings.verbose {
+                eprintln!(
+                    "  {} {}: {}; retry {}/{} in {:.1}s",
+                    request.method(),
+                    request.url().path(),
+                    reason,
+                    attempt,
+                    self.settings.max_retries,
+                    delay.as_secs_f64()
+                );
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+impl HttpClient {
+    /// Report `cost` for an attempt the server may have counted: one it
+    /// answered or that timed out, but not one that never connected.
+    fn charge(&self, result: &reqwest::Result<Response>, cost: u64) {
+        let Some(meter) = &self.meter else {
+            return;
+        };
+        let reached = match result {
+            Ok(_) => true,
+            Err(e) => !e.is_connect(),
+        };
+        if reached && cost > 0 {
+            meter(cost);
+        }
+    }
+}
+
+/// How `execute` treats a request, set on its `RequestBuilder`.
+#[derive(Debug, Clone, Copy)]
+struct Flags {
+    read_only: bool,
+    mutating: bool,
+    idempotent: bool,
+    cost: u64,
+}
+
+/// A request being built; mirrors `reqwest::RequestBuilder` but sends
+/// through the owning `HttpClient`.
+pub struct RequestBuilder {
+    client: HttpClient,
+    inner: reqwest::RequestBuilder,
+    read_only: bool,
+    mutating: bool,
+    idempotent: bool,
+    cost: u64,
+}
+
+impl RequestBuilder {
+    pub fn header(self, key: &str, value: impl AsRef<str>) -> Self {
+        self.map(|r| r.header(key, value.as_ref()))
+    }
+
+    pub fn bearer_auth(self, token: impl std::fmt::Display) -> Self {
+        self.map(|r| r.bearer_auth(token))
+    }
+
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        self.map(|r| r.query(query))
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        self.map(|r| r.json(json))
+    }
+
+    pub fn form<T: Serialize + ?Sized>(self, form: &T) -> Self {
+        self.map(|r| r.form(form))
+    }
+
+    /// Mark a request that changes nothing despite its method, such as an
+    /// OAuth token request, so dry runs still send it.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Mark a GET that changes something, as some APIs (Subsonic) make
+    /// every call a GET, so dry runs skip it and failures are only retried
+    /// if it is also marked `idempotent`.
+    pub fn mutating(mut self) -> Self {
+        self.mutating = true;
+        self
+    }
+
+    /// Mark a request that has the same effect when sent twice, such as
+    /// setting a value, so it is retried after a server error or timeout.
+    /// GETs always are; moves, inserts and deletes that fail when repeated
+    /// must not be marked.
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    /// Quota units each attempt costs, reported to the client's meter.
+    pub fn cost(mut self, units: u64) -> Self {
+        self.cost = units;
+        self
+    }
+
+    pub async fn send(self) -> Result<Response> {
+        let request = self.inner.build()?;
+        let flags = Flags {
+            read_only: self.read_only,
+            mutating: self.mutating,
+            idempotent: self.idempotent,
+            cost: self.cost,
+        };
+        self.client.execute(request, flags).await
+    }
+
+    fn map(self, f: impl FnOnce(reqwest::RequestBuilder) -> reqwest::RequestBuilder) -> Self {
+        Self {
+            client: self.client,
+            inner: f(self.inner),
+            read_only: self.read_only,
+            mutating: self.mutating,
+            idempotent: self.idempotent,
+            cost: self.cost,
+        }
+    }
+}
+
+/// Print a request a dry run skips and answer it with `DRY_RUN_BODY`.
+fn dry_run_response(request: &reqwest::Request) -> Response {
+    let url = request.url();
+    let target = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    println!("  Would send {} {}", request.method(), target);
+    if let Some(body) = request.body().and_then(|b| b.as_bytes()) {
+        println!("    {}", String::from_utf8_lossy(body));
+    }
+
+    http::Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(DRY_RUN_BODY)
+        .expect("static response")
+        .into()
+}
+
+/// The delay a `Retry-After` header asks for, in seconds.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get("Retry-After")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Exponential backoff with jitter: between half and all of
+/// `base * 2^attempt`, so clients retrying together spread out.
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    let delay = base * 2u32.saturating_pow(attempt.min(16));
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Request, TestServer};
+    use std::sync::Mutex;
+
+    fn settings() -> Settings {
+        Settings {
+            base_delay: Duration::from_millis(1),
+            ..Settings::default()
+        }
+    }
+
+    /// A server that fails the first `failures` requests with `status`.
+    async fn flaky_server(status: u16, failures: usize) -> TestServer {
+        let mut server = TestServer::bind().await;
+        let count = Mutex::new(0);
+        server.serve(move |_: &Request| {
+            let mut count = count.lock().unwrap();
+            *count += 1;
+            if *count <= failures {
+                (status, "{}".to_string())
+            } else {
+                (200, r#"{"ok":true}"#.to_string())
+            }
+        });
+        server
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limits_and_idempotent_failures() {
+        let server = flaky_server(429, 2).await;
+        let client = HttpClient::with_settings(settings());
+        let response = client.post(&server.url).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.requests().len(), 3);
+
+        let server = flaky_server(503, 1).await;
+        let response = client.get(&server.url).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_failures_only_when_idempotent() {
+        let client = HttpClient::with_settings(settings());
+        for method in [Method::POST, Method::PUT, Method::DELETE] {
+            let server = flaky_server(503, 1).await;
+            let response = client.request(method, &server.url).send().await.unwrap();
+            assert_eq!(response.status(), 503);
+            assert_eq!(server.requests().len(), 1);
+        }
+
+        let server = flaky_server(503, 1).await;
+        let response = client.put(&server.url).idempotent().send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.requests().len(), 2);
+
+        let server = flaky_server(503, 1).await;
+        let response = client.get(&server.url).mutating().send().await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries_and_budget() {
+        let server = flaky_server(500, usize::MAX).await;
+        let client = HttpClient::with_settings(Settings {
+            max_retries: 2,
+            ..settings()
+        });
+        let response = client.get(&server.url).send().await.unwrap();
+        assert_eq!(response.status(), 500);
+        assert_eq!(server.requests().len(), 3);
+
+        let client = HttpClient::with_settings(Settings {
+            max_requests: 2,
+            ..settings()
+        });
+        assert!(client.get(&server.url).send().await.is_err());
+        assert_eq!(server.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_sends_only_reads() {
+        let server = flaky_server(500, 0).await;
+        let client = HttpClient::with_settings(Settings {
+            dry_run: true,
+            ..settings()
+        });
+
+        let response = client.delete(&server.url).send().await.unwrap();
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["snapshot_id"], "dry-run");
+        assert!(server.requests().is_empty());
+
+        client.get(&server.url).mutating().send().await.unwrap();
+        assert!(server.requests().is_empty());
+
+        client.get(&server.url).send().await.unwrap();
+        client.post(&server.url).read_only().send().await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_meter_counts_every_attempt_sent() {
+        let spent = Arc::new(AtomicUsize::new(0));
+        let meter_spent = spent.clone();
+        let meter: Meter = Arc::new(move |units| {
+            meter_spent.fetch_add(units as usize, Ordering::SeqCst);
+        });
+
+        let server = flaky_server(429, 2).await;
+        let client = HttpClient::with_settings(settings()).with_meter(meter.clone());
+        client.get(&server.url).cost(1).send().await.unwrap();
+        assert_eq!(spent.load(Ordering::SeqCst), 3);
+
+        // A dry run still sends and pays for reads, but not skipped changes
+        let client = HttpClient::with_settings(Settings {
+            dry_run: true,
+            ..settings()
+        })
+        .with_meter(meter);
+        client.get(&server.url).cost(1).send().await.unwrap();
+        client.delete(&server.url).cost(50).send().await.unwrap();
+        assert_eq!(spent.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert("Retry-After", "3".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+    }
+}
@@ -1 +1,5 @@
 pub mod crypto;
+pub mod hooks;
+pub mod http;
+#[cfg(test)]
+pub mod test_server;
@@ -0,0 +1,276 @@
+//! End-to-end tests: the `grit` binary against a mock Spotify Web API.
+
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{free_port, Grit, MockServer, Request};
+use serde_json::{json, Value};
+
+/// Tracks per page of playlist items, small so pagination is exercised.
+const PAGE_SIZE: usize = 2;
+
+fn track(id: &str) -> Value {
+    json!({
+        "id": id,
+        "name": format!("Song {}", id),
+        "duration_ms": 200_000,
+        "artists": [{"name": "Artist"}]
+    })
+}
+
+fn page(base_url: &str, tracks: &[String], offset: usize) -> Value {
+    let end = (offset + PAGE_SIZE).min(tracks.len());
+    let next = (end < tracks.len())
+        .then(|| format!("{}/v1/playlists/pl1/tracks?offset={}", base_url, end));
+    json!({
+        "items": tracks[offset..end].iter().map(|id| json!({"track": track(id)})).collect::<Vec<_>>(),
+        "next": next
+    })
+}
+
+/// A Spotify account owning playlist `pl1`. Issued tokens expire at once,
+/// so the first API call of every command has to refresh them.
+fn spotify(tracks: Arc<Mutex<Vec<String>>>) -> MockServer {
+    let mut server = MockServer::bind();
+    let base_url = server.url.clone();
+
+    server.serve(move |request: &Request| {
+        if request.path == "/api/token" {
+            let form = request.form();
+            return match form.get("grant_type").map(String::as_str) {
+                Some("authorization_code") if form.get("code").map(String::as_str) == Some("code-1") => (
+                    200,
+                    json!({"access_token": "token-1", "token_type": "Bearer", "expires_in": 0, "refresh_token": "refresh-1"}).to_string(),
+                ),
+                Some("refresh_token") if form.get("refresh_token").map(String::as_str) == Some("refresh-1") => (
+                    200,
+                    json!({"access_token": "token-2", "token_type": "Bearer", "expires_in": 3600}).to_string(),
+                ),
+                _ => (400, json!({"error": "invalid_grant"}).to_string()),
+            };
+        }
+
+        if request.headers.get("authorization").map(String::as_str) != Some("Bearer token-2") {
+            return (401, json!({"error": {"status": 401, "message": "Invalid access token"}}).to_string());
+        }
+
+        let mut tracks = tracks.lock().unwrap();
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/v1/me") => (200, json!({"id": "me"}).to_string()),
+            ("GET", "/v1/playlists/pl1") => (
+                200,
+                json!({
+                    "id": "pl1",
+                    "name": "Road trip",
+                    "description": null,
+                    "snapshot_id": format!("snap-{}", tracks.join("-")),
+                    "owner": {"id": "me"},
+                    "collaborative": false,
+                    "tracks": page(&base_url, &tracks, 0)
+                })
+                .to_string(),
+            ),
+            ("GET", "/v1/playlists/pl1/tracks") => {
+                let offset = request.query["offset"].parse().unwrap();
+                (200, page(&base_url, &tracks, offset).to_string())
+            }
+            ("GET", path) if path.starts_with("/v1/tracks/") => {
+                (200, track(path.trim_start_matches("/v1/tracks/")).to_string())
+            }
+            ("DELETE", "/v1/playlists/pl1/tracks") => {
+                for item in request.json()["tracks"].as_array().unwrap() {
+                    let id = item["uri"].as_str().unwrap().trim_start_matches("spotify:track:");
+                    tracks.retain(|t| t != id);
+                }
+                (200, json!({"snapshot_id": "s"}).to_string())
+            }
+            ("POST", "/v1/playlists/pl1/tracks") => {
+                for uri in request.json()["uris"].as_array().unwrap() {
+                    let id = uri.as_str().unwrap().trim_start_matches("spotify:track:");
+                    tracks.push(id.to_string());
+                }
+                (201, json!({"snapshot_id": "s"}).to_string())
+            }
+            ("PUT", "/v1/playlists/pl1/tracks") => {
+                let body = request.json();
+                let start = body["range_start"].as_u64().unwrap() as usize;
+                let length = body["range_length"].as_u64().unwrap_or(1) as usize;
+                let before = body["insert_before"].as_u64().unwrap() as usize;
+                let moved: Vec<String> = tracks.drain(start..start + length).collect();
+                let target = if before > start { before - length } else { before };
+                tracks.splice(target..target, moved);
+                (200, json!({"snapshot_id": "s"}).to_string())
+            }
+            _ => (404, json!({"error": {"status": 404, "message": "Not found"}}).to_string()),
+        }
+    });
+
+    server
+}
+
+/// Complete `grit auth spotify` by calling its redirect URI like a browser would.
+fn authenticate(grit: &Grit, auth_port: u16) {
+    let mut child = grit
+        .command(&["auth", "spotify"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let state = loop {
+        let mut line = String::new();
+        assert!(
+            stdout.read_line(&mut line).unwrap() > 0,
+            "no auth URL printed"
+        );
+        if let Some((_, rest)) = line.split_once("state=") {
+            break rest.split('&').next().unwrap().trim().to_string();
+        }
+    };
+
+    // The callback listener starts right after the URL is printed
+    let mut stream = (0..50)
+        .find_map(|_| {
+            TcpStream::connect(("127.0.0.1", auth_port))
+                .map_err(|_| std::thread::sleep(Duration::from_millis(100)))
+                .ok()
+        })
+        .expect("callback listener never started");
+    write!(
+        stream,
+        "GET /callback?code=code-1&state={} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        state
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.contains("Success"));
+
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).unwrap();
+    let status = child.wait().unwrap();
+    let mut stderr = String::new();
+    child.stderr.unwrap().read_to_string(&mut stderr).unwrap();
+    assert!(status.success(), "grit auth failed:\n{}{}", rest, stderr);
+}
+
+/// A repository signed in to the mock Spotify account at `server`.
+fn signed_in(server: &MockServer) -> Grit {
+    let grit = Grit::new();
+    let auth_port = free_port();
+    grit.configure(&format!(
+        r#"
+[auth]
+port = {port}
+
+[spotify]
+client_id = "client"
+client_secret = "secret"
+api_url = "{url}/v1"
+auth_url = "{url}/authorize"
+token_url = "{url}/api/token"
+"#,
+        port = auth_port,
+        url = server.url
+    ));
+
+    authenticate(&grit, auth_port);
+    grit
+}
+
+#[test]
+fn test_spotify_init_commit_push_pull() {
+    let remote_tracks = Arc::new(Mutex::new(vec![
+        "t1".to_string(),
+        "t2".to_string(),
+        "t3".to_string(),
+    ]));
+    let server = spotify(remote_tracks.clone());
+    let grit = signed_in(&server);
+
+    // Three tracks over two pages; the expired token is refreshed first
+    let out = grit.ok(&["init", "https://open.spotify.com/playlist/pl1"]);
+    assert!(out.contains("Tracks: 3"), "{}", out);
+    assert_eq!(grit.track_ids("pl1"), vec!["t1", "t2", "t3"]);
+    assert!(server
+        .requests()
+        .iter()
+        .any(|r| r.path == "/api/token" && r.form()["grant_type"] == "refresh_token"));
+
+    grit.ok(&["add", "t4"]);
+    grit.ok(&["remove", "t2"]);
+    grit.ok(&["commit", "-m", "Swap t2 for t4"]);
+    grit.ok(&["move", "t3", "0"]);
+    grit.ok(&["commit", "-m", "Open with t3"]);
+    let local = grit.track_ids("pl1");
+    assert_eq!(local, vec!["t3", "t1", "t4"]);
+
+    let out = grit.ok(&["push"]);
+    assert!(out.contains("Successfully pushed"), "{}", out);
+    assert_eq!(*remote_tracks.lock().unwrap(), local);
+
+    let out = grit.ok(&["push"]);
+    assert!(out.contains("No changes to push"), "{}", out);
+
+    // Someone else adds a track on Spotify
+    remote_tracks.lock().unwrap().push("t5".to_string());
+    grit.ok(&["pull"]);
+    assert_eq!(grit.track_ids("pl1"), vec!["t3", "t1", "t4", "t5"]);
+
+    let log = grit.ok(&["log", "--oneline"]);
+    for operation in ["init", "commit", "push", "pull"] {
+        assert!(
+            log.contains(operation),
+            "{} missing from log:\n{}",
+            operation,
+            log
+        );
+    }
+}
+
+#[test]
+fn test_push_moves_adjacent_tracks_as_one_block() {
+    let remote_tracks = Arc::new(Mutex::new(
+        ["t1", "t2", "t3", "t4", "t5"].map(String::from).to_vec(),
+    ));
+    let server = spotify(remote_tracks.clone());
+    let grit = signed_in(&server);
+
+    grit.ok(&["init", "https://open.spotify.com/playlist/pl1"]);
+    grit.ok(&["move", "t4", "0"]);
+    grit.ok(&["move", "t5", "1"]);
+    grit.ok(&["commit", "-m", "Open with t4 and t5"]);
+    let local = grit.track_ids("pl1");
+    assert_eq!(local, vec!["t4", "t5", "t1", "t2", "t3"]);
+
+    let out = grit.ok(&["push"]);
+    assert!(out.contains("Successfully pushed"), "{}", out);
+    assert_eq!(*remote_tracks.lock().unwrap(), local);
+
+    let moves: Vec<Value> = server
+        .requests()
+        .iter()
+        .filter(|r| r.method == "PUT")
+        .map(|r| r.json())
+        .collect();
+    assert_eq!(moves.len(), 1, "{:?}", moves);
+    assert_eq!(moves[0]["range_start"], 3);
+    assert_eq!(moves[0]["range_length"], 2);
+    assert_eq!(moves[0]["insert_before"], 0);
+}
+
+#[test]
+fn test_commands_fail_without_credentials() {
+    let grit = Grit::new();
+    let output = grit.run(&["init", "https://open.spotify.com/playlist/pl1"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("grit auth spotify"), "{}", stderr);
+}